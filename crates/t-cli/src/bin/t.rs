@@ -1,33 +1,208 @@
+use chrono::DateTime;
+use chrono::Local;
+use chrono::NaiveDate;
+use chrono::TimeZone;
+use chrono::Utc;
+use fs2::FileExt;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::ffi::OsString;
 use std::fs;
+use std::io;
+use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
+use std::process;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+use structopt::clap::AppSettings;
+use structopt::clap::Shell;
 use structopt::StructOpt;
+use t::archive::ArchivedTask;
+use t::date;
 use t::db::Db;
+use t::diff;
+use t::history::History;
+use t::priority::Priority;
+use t::recurrence::Recurrence;
+use t::render::Color;
+use t::render::DateStyle;
+use t::render::Glyphs;
+use t::render::GroupBy;
+use t::render::RenderOptions;
+use t::render::StatusStyle;
+use t::render::TaskOrder;
+use t::status::Status;
+use t::storage;
+use t::storage::JsonFileStorage;
+use t::storage::Storage;
 use t::task::Task;
 use t::task_list::TaskList;
 
 fn main() -> anyhow::Result<()> {
-    let opts = Opts::from_args();
+    let mut opts = Opts::from_args();
 
-    let db_path = get_db_path()?;
+    // Generating completions doesn’t need the database, so we do it before touching it.
+    if let Some(Subcommand::Completions { shell }) = opts.subcommand {
+        return write_completions(shell, &mut io::stdout());
+    }
+
+    let db_path = resolve_db_path(opts.db.take(), env::var_os("T_DB_PATH"))?;
+    let format = opts
+        .store
+        .unwrap_or_else(|| storage::Format::for_path(&db_path));
+
+    // We hold the lock until `t` exits so that nobody else can change the database between us
+    // reading and saving it.
+    let _lock = lock_db(&db_path, LOCK_TIMEOUT)?;
+
+    let mut storage = JsonFileStorage::with_format(db_path.clone(), format);
 
-    let db = if db_path.exists() {
-        read_db(&db_path)?
+    let config = read_config(db_path.with_file_name("config.toml"))?;
+
+    let mut db = if db_path.exists() {
+        storage.load()?
     } else {
-        let default_db = Db::default();
-        save_db(&db_path, &default_db)?;
+        let default_db = Db::with_default_list(config.default_list.clone());
+        storage.save(&default_db)?;
 
         default_db
     };
 
-    if let Some(subcommand) = opts.subcommand {
-        let mut db = db;
-        subcommand.execute(&mut db)?;
+    // The repair is only kept in memory, so it is saved along with the next change rather than by
+    // commands that only read the database. Doctor is left to find the problem and report it.
+    let is_doctor = matches!(opts.subcommand, Some(Subcommand::Doctor { .. }));
+    let dangling_list = db.current_list().to_string();
 
-        save_db(&db_path, &db)?;
-    } else {
-        // In this case we just print the database to the user.
-        println!("{}", db);
+    if !is_doctor && db.repair_current_list(&config.default_list) {
+        eprintln!(
+            "The current task list ‘{}’ does not exist, so ‘{}’ is used instead (run `t doctor --fix` \
+             to make this permanent)",
+            dangling_list,
+            db.current_list()
+        );
+    }
+
+    if let Some(subcommand) = opts.subcommand.take() {
+        let target_list = resolve_target_list(&db, &opts)?;
+
+        // Nothing is saved in a dry run, so there is nothing to confirm.
+        if let Some(prompt) = subcommand
+            .confirmation_prompt(&target_list)
+            .filter(|_| !opts.dry_run)
+        {
+            if !should_proceed(&prompt, opts.yes, atty::is(atty::Stream::Stdout))? {
+                return Ok(());
+            }
+        }
+
+        let history_path = companion_path(&db_path, "history.json");
+        let mut history = read_history(&history_path)?;
+
+        let is_undo = matches!(subcommand, Subcommand::Undo);
+        let is_archive = matches!(subcommand, Subcommand::Archive);
+        let audit_description = subcommand.audit_description(&target_list);
+        let db_before = db.clone();
+
+        subcommand.execute(&mut db, &mut history, &db_path, &opts, &config)?;
+
+        // Subcommands that only read the database behave as usual in a dry run.
+        if opts.dry_run {
+            if db != db_before {
+                print!("{}", format_dry_run(&db_before, &db));
+            }
+
+            return ensure_healthy(&db, is_doctor);
+        }
+
+        // Subcommands that only read the database leave both files untouched.
+        if db != db_before {
+            // Only changes are recorded, so undoing never appears to do nothing because it
+            // restored an identical database.
+            if !is_undo {
+                history.push(db_before.clone());
+            }
+
+            storage.save(&db)?;
+
+            // The archive is only changed once the database is saved, so that a failed save
+            // never leaves a task both in the database and in the archive.
+            let archive_path = companion_path(&db_path, "archive.json");
+
+            if is_archive {
+                let mut archive = read_archive(&archive_path)?;
+                archive.extend(db_before.clone().archive_completed());
+                save_archive(&archive_path, &archive)?;
+            } else if is_undo {
+                unarchive_undone(&archive_path, &db, &db_before)?;
+            }
+
+            save_history(&history_path, &history)?;
+            append_to_audit_log(
+                companion_path(&db_path, "audit.log"),
+                Utc::now(),
+                &audit_description,
+            )?;
+        }
+
+        // Checked only once any fixes are saved, so that those fixes aren’t lost to the problems
+        // that remain.
+        ensure_healthy(&db, is_doctor)?;
+
+        if !opts.print {
+            return Ok(());
+        }
+    }
+
+    // Either no subcommand was given, in which case we just print the database to the user, or
+    // the user asked to see the database after it was changed.
+    print_db(&db, &opts, &config)
+}
+
+/// Fails if `doctor` was run and the database still has problems, so that scripts can tell.
+fn ensure_healthy(db: &Db, is_doctor: bool) -> anyhow::Result<()> {
+    let num_issues = if is_doctor { db.validate().len() } else { 0 };
+
+    if num_issues > 0 {
+        anyhow::bail!("found {} problem(s) in the database", num_issues);
+    }
+
+    Ok(())
+}
+
+fn print_db(db: &Db, opts: &Opts, config: &Config) -> anyhow::Result<()> {
+    match opts.format {
+        Format::Text => {
+            let options = RenderOptions {
+                use_color: should_use_color(),
+                dim_completed: opts.dim_completed,
+                max_lists: opts.max_lists,
+                group_by: opts.group_by,
+                order: opts.by,
+                ascii: opts.ascii,
+                status_style: status_style(opts),
+                verbose: opts.verbose,
+                width: Some(terminal_width()),
+                today: Some(Local::today().naive_local()),
+                dates: opts.dates,
+                glyphs: config.glyphs.clone(),
+                summary: true,
+                tag_colors: HashMap::new(),
+            };
+
+            let rendered = if opts.lists.is_empty() {
+                db.render(&options)
+            } else {
+                let names: Vec<_> = opts.lists.iter().map(String::as_str).collect();
+                db.render_subset(&names, &options)?
+            };
+
+            print_paged(&rendered)?;
+        }
+        Format::Json => println!("{}", db.to_json()),
     }
 
     Ok(())
@@ -35,42 +210,1030 @@ fn main() -> anyhow::Result<()> {
 
 #[derive(StructOpt)]
 struct Opts {
+    /// The database file to use instead of the default one (also settable with T_DB_PATH)
+    #[structopt(long, parse(from_os_str))]
+    db: Option<PathBuf>,
+    /// The format the database is stored in (json or toml), which is otherwise worked out from the
+    /// database file’s extension
+    #[structopt(long)]
+    store: Option<storage::Format>,
+    /// The format the database is printed in (text or json)
+    #[structopt(long, default_value = "text")]
+    format: Format,
+    /// Prints the database after running a subcommand
+    #[structopt(long)]
+    print: bool,
+    /// Shows what a subcommand would change without saving anything
+    #[structopt(long)]
+    dry_run: bool,
+    /// Renders completed tasks faintly when color is enabled
+    #[structopt(long)]
+    dim_completed: bool,
+    /// Shows at most this many task lists, as well as the current one
+    #[structopt(long)]
+    max_lists: Option<usize>,
+    /// Shows only this task list (can be given more than once)
+    #[structopt(short = "l", long = "list", name = "name")]
+    lists: Vec<String>,
+    /// The order tasks are shown in within each task list (priority or age)
+    #[structopt(long, default_value = "priority")]
+    by: TaskOrder,
+    /// Groups the tasks within each task list (accepts ‘tag’)
+    #[structopt(long)]
+    group_by: Option<GroupBy>,
+    /// Transliterates the output to ASCII for terminals that can’t render anything else
+    #[structopt(long)]
+    ascii: bool,
+    /// Shows statuses as `[ ]` and `[x]` checkboxes (implied by --ascii and T_ASCII=1)
+    #[structopt(long)]
+    plain: bool,
+    /// Makes the subcommand act on this task list instead of the current one
+    #[structopt(long = "in", name = "list")]
+    target_list: Option<String>,
+    /// Skips asking for confirmation before removing tasks or task lists, or replacing the database
+    #[structopt(short, long)]
+    yes: bool,
+    /// Doesn’t print what was removed by subcommands that remove tasks or task lists
+    #[structopt(short, long)]
+    quiet: bool,
+    /// Shows when each task was created and completed
+    #[structopt(short, long)]
+    verbose: bool,
+    /// How due dates and reminders are shown (absolute, such as 2024-05-01, or relative, such as
+    /// in 3 days)
+    #[structopt(long, default_value = "absolute")]
+    dates: DateStyle,
     #[structopt(subcommand)]
     subcommand: Option<Subcommand>,
 }
 
+enum Format {
+    Text,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(format!(
+                "unknown format ‘{}’ (expected ‘text’ or ‘json’)",
+                s
+            )),
+        }
+    }
+}
+
+enum ExportFormat {
+    Json,
+    Markdown,
+    Csv,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "markdown" => Ok(Self::Markdown),
+            "csv" => Ok(Self::Csv),
+            _ => Err(format!(
+                "cannot export to ‘{}’ (expected ‘json’, ‘markdown’ or ‘csv’)",
+                s
+            )),
+        }
+    }
+}
+
+enum ImportFormat {
+    Json,
+    Csv,
+}
+
+impl FromStr for ImportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            _ => Err(format!(
+                "cannot import from ‘{}’ (expected ‘json’ or ‘csv’)",
+                s
+            )),
+        }
+    }
+}
+
+enum SortKey {
+    Title,
+    Status,
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "title" => Ok(Self::Title),
+            "status" => Ok(Self::Status),
+            _ => Err(format!(
+                "cannot sort by ‘{}’ (expected ‘title’ or ‘status’)",
+                s
+            )),
+        }
+    }
+}
+
 #[derive(StructOpt)]
 enum Subcommand {
-    /// Adds a task to the database
-    Add { title: String },
+    /// Adds a task to the database and prints its ID
+    Add {
+        title: String,
+        /// The date the task is due, such as 2024-05-01, tomorrow, +3d or friday
+        #[structopt(long, parse(try_from_str = parse_date))]
+        due: Option<NaiveDate>,
+        /// A tag to add to the task (can be given more than once)
+        #[structopt(long = "tag", name = "tag")]
+        tags: Vec<String>,
+        /// The priority of the task (low, medium or high), instead of the task list’s default
+        #[structopt(long)]
+        priority: Option<Priority>,
+        /// The ID to give the task instead of the lowest available one
+        #[structopt(long)]
+        id: Option<u32>,
+        /// Prints the ID of a task with the same title instead of adding another, if there is one
+        #[structopt(long)]
+        unique: bool,
+    },
+    /// Sets the priority and tags that new tasks in the current task list start with, clearing
+    /// whichever isn’t given
+    SetListDefaults {
+        /// The priority new tasks start with (low, medium or high)
+        #[structopt(long)]
+        priority: Option<Priority>,
+        /// A tag new tasks start with (can be given more than once)
+        #[structopt(long = "tag", name = "tag")]
+        tags: Vec<String>,
+    },
+    /// Adds a task written as JSON, read from standard input if not given, and prints its ID
+    AddJson { json: Option<String> },
+    /// Adds a task for each line read from standard input and prints their IDs, skipping blank
+    /// lines and those starting with #
+    AddMany {
+        /// Reads the lines from a file instead of standard input
+        #[structopt(long)]
+        file: Option<PathBuf>,
+    },
+    /// Adds an incomplete copy of a task and prints its ID
+    Duplicate { id: u32 },
+    /// Shows everything about a task
+    Show { id: u32 },
     /// Removes a task from the database
-    Remove { id: u8 },
+    Remove { id: u32 },
+    /// Removes every task with an ID from start to end (inclusive)
+    RemoveRange { start: u32, end: u32 },
+    /// Moves a task one place up in its task list
+    MoveUp { id: u32 },
+    /// Moves a task one place down in its task list
+    MoveDown { id: u32 },
+    /// Swaps where two tasks are displayed
+    Swap { a: u32, b: u32 },
+    /// Reorders the tasks in the current task list (by title or status)
+    Sort { by: SortKey },
     /// Renames a task
-    Rename { id: u8, new_title: String },
-    /// Marks a task as completed
-    Complete { id: u8 },
+    Rename { id: u32, new_title: String },
+    /// Adds text to the start of a task’s title
+    Prepend { id: u32, text: String },
+    /// Adds text to the end of a task’s title
+    Append { id: u32, text: String },
+    /// Marks a task as being worked on
+    Start { id: u32 },
+    /// Marks one or more tasks as completed
+    Complete {
+        #[structopt(required = true)]
+        ids: Vec<u32>,
+        /// Completes tasks even if tasks they depend on are unfinished
+        #[structopt(long)]
+        force: bool,
+    },
+    /// Marks the incomplete task whose title contains some text as completed
+    CompleteText { text: String },
+    /// Marks a completed task as incomplete again
+    Uncomplete { id: u32 },
+    /// Marks a task as dropped rather than done
+    Cancel { id: u32 },
+    /// Edits the notes of a task in $EDITOR
+    Note {
+        id: u32,
+        /// Sets the notes to this text instead of opening an editor
+        #[structopt(long)]
+        text: Option<String>,
+    },
+    /// Sets the date a task is due, such as 2024-05-01, tomorrow, +3d or friday
+    SetDue {
+        id: u32,
+        #[structopt(parse(try_from_str = parse_date))]
+        date: NaiveDate,
+    },
+    /// Adds a reminder to a task on a date, such as 2024-05-01, tomorrow, +3d or friday
+    Remind {
+        id: u32,
+        #[structopt(parse(try_from_str = parse_date))]
+        date: NaiveDate,
+    },
+    /// Removes a reminder from a task
+    Unremind {
+        id: u32,
+        #[structopt(parse(try_from_str = parse_date))]
+        date: NaiveDate,
+    },
+    /// Makes a task wait for another task in the same task list to be finished
+    Block { id: u32, on: u32 },
+    /// Stops a task from waiting for another task
+    Unblock { id: u32, on: u32 },
+    /// Adds a step to a task and prints its index
+    AddSub { id: u32, title: String },
+    /// Completes a step of a task, counting from 0 in the order they are listed
+    CompleteSub {
+        id: u32,
+        index: usize,
+        /// Completes the task too if this was its last incomplete step
+        #[structopt(long)]
+        complete_parent: bool,
+    },
+    /// Pins a task so that it is listed before every unpinned task
+    Pin { id: u32 },
+    /// Unpins a task, returning it to its usual place
+    Unpin { id: u32 },
+    /// Prints the tasks with a reminder on or before today across all task lists
+    Reminders,
+    /// Makes a task come due again every day, week or month when it is completed
+    Recur { id: u32, every: Recurrence },
+    /// Sets the priority of a task (low, medium or high)
+    Prioritize { id: u32, priority: Priority },
+    /// Moves a task from the current task list to another one
+    Move {
+        id: u32,
+        to: String,
+        /// Keeps the task’s ID in the other task list, unless a task there already has it
+        #[structopt(long)]
+        keep_id: bool,
+    },
+    /// Adds a tag to a task
+    Tag { id: u32, tag: String },
+    /// Removes a tag from a task
+    Untag { id: u32, tag: String },
+    /// Prints how many tasks are in the current task list, optionally only those with a status
+    /// (incomplete, in-progress, complete or cancelled)
+    Count { status: Option<Status> },
+    /// Prints the tasks carrying a tag across all task lists
+    Filter { tag: String },
+    /// Prints the tasks whose titles contain some text across all task lists
+    Search { query: String },
+    /// Prints the tasks due today across all task lists
+    Today,
+    /// Prints the incomplete tasks due before today across all task lists
+    Overdue,
+    /// Prints the tasks due between two dates (inclusive) across all task lists
+    DueBetween {
+        #[structopt(parse(try_from_str = parse_date))]
+        start: NaiveDate,
+        #[structopt(parse(try_from_str = parse_date))]
+        end: NaiveDate,
+    },
+    /// Prints the tasks completed on or after a date (today by default), most recent first, across
+    /// all task lists
+    Done {
+        #[structopt(parse(try_from_str = parse_date))]
+        since: Option<NaiveDate>,
+    },
     /// Removes all completed tasks
-    RemoveCompleted,
+    RemoveCompleted {
+        /// Removes completed tasks from every task list rather than only the current one
+        #[structopt(long)]
+        all_lists: bool,
+    },
+    /// Removes the tasks completed before a date, such as 2024-05-01 or today
+    Cleanup {
+        #[structopt(parse(try_from_str = parse_date))]
+        before: NaiveDate,
+    },
+    /// Checks the database for problems, such as those left by editing it by hand, and exits
+    /// unsuccessfully if there are any
+    Doctor {
+        /// Repairs the problems that can be repaired without losing anything
+        #[structopt(long)]
+        fix: bool,
+    },
+    /// Edits the whole database as JSON in $EDITOR, keeping the changes only if the result has no
+    /// problems
+    Edit,
+    /// Moves all completed tasks out of every task list and into the archive
+    Archive,
+    /// Prints the tasks in the archive
+    ShowArchive,
+    /// Removes all tasks from the current task list, keeping the task list itself
+    Clear,
     /// Creates a new empty task list and sets it as current
     AddTaskList { name: String },
     /// Removes a task list
     RemoveTaskList { name: String },
     /// Renames a task list
-    RenameTaskList { old_name: String, new_name: String },
-    /// Sets the current task list
+    RenameTaskList {
+        old_name: String,
+        new_name: String,
+        /// Merges the task list into the task list with the new name if there already is one
+        #[structopt(long)]
+        force: bool,
+    },
+    /// Moves every task from one task list into another and removes the first
+    Merge { source: String, dest: String },
+    /// Moves a task list to a position among the other task lists
+    MoveListTo { name: String, index: usize },
+    /// Prints the name of each task list, marking the current one with *
+    Lists,
+    /// Sets the current task list, by name or by its position among the task lists (from 0)
     SetCurrent { name: String },
+    /// Switches back to the task list that was current before the current one
+    SetPrevious,
+    /// Prints the incomplete task with the highest priority
+    Next {
+        /// Considers tasks from all task lists rather than only the current one
+        #[structopt(long)]
+        all: bool,
+    },
+    /// Renames a tag on every task in every task list
+    RenameTag { old: String, new: String },
+    /// Sets the color a tag is drawn in (red, green, yellow, blue, magenta or cyan)
+    LabelColor { tag: String, color: Color },
+    /// Prints how many tasks carry each tag across all task lists
+    TagSummary,
+    /// Prints how many tasks have been completed in each task list and overall
+    Stats,
+    /// Prints the database as JSON, Markdown or CSV
+    Export {
+        #[structopt(default_value = "json")]
+        format: ExportFormat,
+    },
+    /// Adds the task lists from an exported database
+    Import {
+        path: PathBuf,
+        /// The format of the file (json or csv)
+        #[structopt(long, default_value = "json")]
+        format: ImportFormat,
+    },
+    /// Replaces the entire database with an exported one
+    Restore { path: PathBuf },
+    /// Resets the database to how it was when t was first run, removing every task list and task
+    Purge,
+    /// Reverts the most recent change to the database
+    Undo,
+    /// Prints the most recent changes recorded in the audit log
+    History {
+        /// How many changes to print
+        #[structopt(long, default_value = "20")]
+        limit: usize,
+    },
+    /// Prints a completion script for a shell (bash, zsh, fish or powershell)
+    #[structopt(setting = AppSettings::Hidden)]
+    Completions { shell: Shell },
 }
 
 impl Subcommand {
-    fn execute(self, db: &mut Db) -> anyhow::Result<()> {
-        let current_task_list = db.get_current_task_list_mut();
+    /// Returns what to ask the user before running the subcommand, if it removes tasks or task
+    /// lists.
+    fn confirmation_prompt(&self, target_list: &str) -> Option<String> {
+        match self {
+            Self::Remove { id } => Some(format!("Remove task {} from ‘{}’?", id, target_list)),
+            Self::RemoveRange { start, end } => Some(format!(
+                "Remove tasks {} to {} from ‘{}’?",
+                start, end, target_list
+            )),
+            Self::RemoveCompleted { all_lists: true } => {
+                Some("Remove all completed tasks from every task list?".to_string())
+            }
+            Self::RemoveCompleted { all_lists: false } => Some(format!(
+                "Remove all completed tasks from ‘{}’?",
+                target_list
+            )),
+            Self::Cleanup { before } => Some(format!(
+                "Remove tasks completed before {} from ‘{}’?",
+                before, target_list
+            )),
+            Self::Clear => Some(format!("Remove all tasks from ‘{}’?", target_list)),
+            Self::RemoveTaskList { name } => {
+                Some(format!("Remove task list ‘{}’ and all its tasks?", name))
+            }
+            _ => None,
+        }
+    }
+
+    /// Describes the subcommand for the audit log as its name followed by its arguments, such as
+    /// `complete ids=3 list=Tasks`.
+    fn audit_description(&self, target_list: &str) -> String {
+        let list = || ("list", target_list.to_string());
+
+        let (name, args): (&str, Vec<(&str, String)>) = match self {
+            Self::Add {
+                title,
+                due,
+                tags,
+                priority,
+                id,
+                unique,
+            } => {
+                let mut args = vec![("title", title.clone())];
+
+                if let Some(id) = id {
+                    args.push(("id", id.to_string()));
+                }
+
+                if *unique {
+                    args.push(("unique", unique.to_string()));
+                }
+
+                if let Some(due) = due {
+                    args.push(("due", due.to_string()));
+                }
+
+                if !tags.is_empty() {
+                    args.push(("tags", tags.join(",")));
+                }
+
+                if let Some(priority) = priority {
+                    args.push(("priority", format!("{:?}", priority).to_lowercase()));
+                }
+
+                args.push(list());
+
+                ("add", args)
+            }
+            Self::SetListDefaults { priority, tags } => {
+                let mut args = Vec::new();
+
+                if let Some(priority) = priority {
+                    args.push(("priority", format!("{:?}", priority).to_lowercase()));
+                }
+
+                if !tags.is_empty() {
+                    args.push(("tags", tags.join(",")));
+                }
+
+                args.push(list());
+
+                ("set-list-defaults", args)
+            }
+            Self::AddJson { .. } => ("add-json", vec![list()]),
+            Self::AddMany { file } => {
+                let mut args = Vec::new();
+
+                if let Some(file) = file {
+                    args.push(("file", file.display().to_string()));
+                }
+
+                args.push(list());
+
+                ("add-many", args)
+            }
+            Self::Duplicate { id } => ("duplicate", vec![("id", id.to_string()), list()]),
+            Self::Show { id } => ("show", vec![("id", id.to_string()), list()]),
+            Self::Remove { id } => ("remove", vec![("id", id.to_string()), list()]),
+            Self::RemoveRange { start, end } => (
+                "remove-range",
+                vec![
+                    ("start", start.to_string()),
+                    ("end", end.to_string()),
+                    list(),
+                ],
+            ),
+            Self::MoveUp { id } => ("move-up", vec![("id", id.to_string()), list()]),
+            Self::MoveDown { id } => ("move-down", vec![("id", id.to_string()), list()]),
+            Self::Swap { a, b } => (
+                "swap",
+                vec![("a", a.to_string()), ("b", b.to_string()), list()],
+            ),
+            Self::Sort { by } => {
+                let by = match by {
+                    SortKey::Title => "title",
+                    SortKey::Status => "status",
+                };
+
+                ("sort", vec![("by", by.to_string()), list()])
+            }
+            Self::Rename { id, new_title } => (
+                "rename",
+                vec![
+                    ("id", id.to_string()),
+                    ("new_title", new_title.clone()),
+                    list(),
+                ],
+            ),
+            Self::Complete { ids, force } => {
+                let ids: Vec<_> = ids.iter().map(u32::to_string).collect();
+                let mut args = vec![("ids", ids.join(","))];
+
+                if *force {
+                    args.push(("force", force.to_string()));
+                }
+
+                args.push(list());
+
+                ("complete", args)
+            }
+            Self::CompleteText { text } => ("complete-text", vec![("text", text.clone()), list()]),
+            Self::Uncomplete { id } => ("uncomplete", vec![("id", id.to_string()), list()]),
+            Self::Prepend { id, text } => (
+                "prepend",
+                vec![("id", id.to_string()), ("text", text.clone()), list()],
+            ),
+            Self::Append { id, text } => (
+                "append",
+                vec![("id", id.to_string()), ("text", text.clone()), list()],
+            ),
+            Self::Start { id } => ("start", vec![("id", id.to_string()), list()]),
+            Self::Cancel { id } => ("cancel", vec![("id", id.to_string()), list()]),
+            Self::Note { id, .. } => ("note", vec![("id", id.to_string()), list()]),
+            Self::SetDue { id, date } => (
+                "set-due",
+                vec![("id", id.to_string()), ("date", date.to_string()), list()],
+            ),
+            Self::Remind { id, date } => (
+                "remind",
+                vec![("id", id.to_string()), ("date", date.to_string()), list()],
+            ),
+            Self::Unremind { id, date } => (
+                "unremind",
+                vec![("id", id.to_string()), ("date", date.to_string()), list()],
+            ),
+            Self::Block { id, on } => (
+                "block",
+                vec![("id", id.to_string()), ("on", on.to_string()), list()],
+            ),
+            Self::Unblock { id, on } => (
+                "unblock",
+                vec![("id", id.to_string()), ("on", on.to_string()), list()],
+            ),
+            Self::AddSub { id, title } => (
+                "add-sub",
+                vec![("id", id.to_string()), ("title", title.clone()), list()],
+            ),
+            Self::CompleteSub {
+                id,
+                index,
+                complete_parent,
+            } => {
+                let mut args = vec![("id", id.to_string()), ("index", index.to_string())];
+
+                if *complete_parent {
+                    args.push(("complete_parent", complete_parent.to_string()));
+                }
+
+                args.push(list());
+
+                ("complete-sub", args)
+            }
+            Self::Pin { id } => ("pin", vec![("id", id.to_string()), list()]),
+            Self::Unpin { id } => ("unpin", vec![("id", id.to_string()), list()]),
+            Self::Reminders => ("reminders", Vec::new()),
+            Self::Recur { id, every } => (
+                "recur",
+                vec![
+                    ("id", id.to_string()),
+                    ("every", format!("{:?}", every).to_lowercase()),
+                    list(),
+                ],
+            ),
+            Self::Prioritize { id, priority } => (
+                "prioritize",
+                vec![
+                    ("id", id.to_string()),
+                    ("priority", format!("{:?}", priority).to_lowercase()),
+                    list(),
+                ],
+            ),
+            Self::Move { id, to, keep_id } => {
+                let mut args = vec![("id", id.to_string()), list(), ("to", to.clone())];
+
+                if *keep_id {
+                    args.push(("keep_id", keep_id.to_string()));
+                }
+
+                ("move", args)
+            }
+            Self::Tag { id, tag } => (
+                "tag",
+                vec![("id", id.to_string()), ("tag", tag.clone()), list()],
+            ),
+            Self::Untag { id, tag } => (
+                "untag",
+                vec![("id", id.to_string()), ("tag", tag.clone()), list()],
+            ),
+            Self::Count { status } => match status {
+                Some(status) => ("count", vec![("status", status.name().to_string()), list()]),
+                None => ("count", vec![list()]),
+            },
+            Self::Filter { tag } => ("filter", vec![("tag", tag.clone())]),
+            Self::Search { query } => ("search", vec![("query", query.clone())]),
+            Self::Today => ("today", Vec::new()),
+            Self::Overdue => ("overdue", Vec::new()),
+            Self::DueBetween { start, end } => (
+                "due-between",
+                vec![("start", start.to_string()), ("end", end.to_string())],
+            ),
+            Self::Done { since } => (
+                "done",
+                since
+                    .iter()
+                    .map(|since| ("since", since.to_string()))
+                    .collect(),
+            ),
+            Self::RemoveCompleted { all_lists: true } => {
+                ("remove-completed", vec![("all_lists", true.to_string())])
+            }
+            Self::RemoveCompleted { all_lists: false } => ("remove-completed", vec![list()]),
+            Self::Cleanup { before } => ("cleanup", vec![("before", before.to_string()), list()]),
+            Self::Doctor { .. } => ("doctor", Vec::new()),
+            Self::Edit => ("edit", Vec::new()),
+            Self::Archive => ("archive", Vec::new()),
+            Self::ShowArchive => ("show-archive", Vec::new()),
+            Self::Clear => ("clear", vec![list()]),
+            Self::AddTaskList { name } => ("add-task-list", vec![("name", name.clone())]),
+            Self::RemoveTaskList { name } => ("remove-task-list", vec![("name", name.clone())]),
+            Self::RenameTaskList {
+                old_name,
+                new_name,
+                force,
+            } => {
+                let mut args = vec![
+                    ("old_name", old_name.clone()),
+                    ("new_name", new_name.clone()),
+                ];
+
+                if *force {
+                    args.push(("force", force.to_string()));
+                }
+
+                ("rename-task-list", args)
+            }
+            Self::Merge { source, dest } => (
+                "merge",
+                vec![("source", source.clone()), ("dest", dest.clone())],
+            ),
+            Self::MoveListTo { name, index } => (
+                "move-list-to",
+                vec![("name", name.clone()), ("index", index.to_string())],
+            ),
+            Self::Lists => ("lists", Vec::new()),
+            Self::SetCurrent { name } => ("set-current", vec![("name", name.clone())]),
+            Self::SetPrevious => ("set-previous", Vec::new()),
+            Self::Next { all } => ("next", vec![("all", all.to_string())]),
+            Self::RenameTag { old, new } => (
+                "rename-tag",
+                vec![("old", old.clone()), ("new", new.clone())],
+            ),
+            Self::LabelColor { tag, color } => (
+                "label-color",
+                vec![("tag", tag.clone()), ("color", color.to_string())],
+            ),
+            Self::TagSummary => ("tag-summary", Vec::new()),
+            Self::Stats => ("stats", Vec::new()),
+            Self::Export { .. } => ("export", Vec::new()),
+            Self::Import { path, .. } => ("import", vec![("path", path.display().to_string())]),
+            Self::Restore { path } => ("restore", vec![("path", path.display().to_string())]),
+            Self::Purge => ("purge", Vec::new()),
+            Self::Undo => ("undo", Vec::new()),
+            Self::History { limit } => ("history", vec![("limit", limit.to_string())]),
+            Self::Completions { shell } => ("completions", vec![("shell", shell.to_string())]),
+        };
+
+        let mut description = name.to_string();
+
+        for (key, value) in args {
+            description.push_str(&format!(" {}={}", key, audit_value(&value)));
+        }
+
+        description
+    }
+
+    /// Runs the subcommand. Subcommands that act on a single task list act on the one given with
+    /// `--in`, and on the current task list otherwise. Files kept alongside the database, such as
+    /// the archive, are found next to `db_path`.
+    fn execute(
+        self,
+        db: &mut Db,
+        history: &mut History,
+        db_path: &Path,
+        opts: &Opts,
+        config: &Config,
+    ) -> anyhow::Result<()> {
+        let target_list = resolve_target_list(db, opts)?;
+
+        // Tasks printed on their own show statuses and dates the same way as the database does.
+        let render_options = RenderOptions {
+            status_style: status_style(opts),
+            today: Some(Local::today().naive_local()),
+            dates: opts.dates,
+            glyphs: config.glyphs.clone(),
+            ..RenderOptions::default()
+        };
+
+        // Prints a summary of what a subcommand removed, unless asked not to.
+        let summarize = |summary: String| {
+            if !opts.quiet {
+                println!("{}", summary);
+            }
+        };
 
         match self {
-            Self::Add { title } => current_task_list.add_task(Task::new(title)),
-            Self::Remove { id } => current_task_list.remove_task(id)?,
-            Self::Rename { id, new_title } => current_task_list.rename_task(id, new_title)?,
-            Self::Complete { id } => current_task_list.complete_task(id)?,
-            Self::RemoveCompleted => current_task_list.remove_completed_tasks(),
+            Self::Add {
+                title,
+                due,
+                tags,
+                priority,
+                id,
+                unique,
+            } => {
+                let task_list = db.get_task_list_mut(&target_list)?;
+
+                if unique {
+                    if let Some(id) = task_list.find_by_exact_title(&title) {
+                        println!("{}", id);
+                        return Ok(());
+                    }
+                }
+
+                let tags = if tags.is_empty() { None } else { Some(tags) };
+                let mut task = task_list.new_task(title, priority, tags);
+                task.set_due(due);
+
+                let id = match id {
+                    Some(id) => {
+                        task_list.insert_task_with_id(id, task)?;
+                        id
+                    }
+                    None => task_list.add_task(task)?,
+                };
+
+                println!("{}", id);
+            }
+            Self::SetListDefaults { priority, tags } => db
+                .get_task_list_mut(&target_list)?
+                .set_defaults(priority, tags),
+            Self::AddJson { json } => {
+                let json = match json {
+                    Some(json) => json,
+                    None => io::read_to_string(io::stdin())?,
+                };
+
+                let task = Task::from_json(&json)?;
+                let id = db
+                    .get_task_list_mut(&target_list)?
+                    .add_task_checking_dependencies(task)?;
+                println!("{}", id);
+            }
+            Self::AddMany { file } => {
+                let text = match file {
+                    Some(file) => fs::read_to_string(file)?,
+                    None => io::read_to_string(io::stdin())?,
+                };
+
+                let task_list = db.get_task_list_mut(&target_list)?;
+
+                for title in task_titles(&text) {
+                    let task = task_list.new_task(title.to_string(), None, None);
+                    let id = task_list.add_task(task)?;
+                    println!("{}", id);
+                }
+            }
+            Self::Remove { id } => {
+                db.get_task_list_mut(&target_list)?.remove_task(id)?;
+                summarize(format!("Removed task {}", id));
+            }
+            Self::MoveUp { id } => db.get_task_list_mut(&target_list)?.move_up(id)?,
+            Self::MoveDown { id } => db.get_task_list_mut(&target_list)?.move_down(id)?,
+            Self::Swap { a, b } => db.get_task_list_mut(&target_list)?.swap(a, b)?,
+            Self::Sort { by } => {
+                let task_list = db.get_task_list_mut(&target_list)?;
+
+                match by {
+                    SortKey::Title => task_list.sort_by_title(),
+                    SortKey::Status => task_list.sort_by_status(),
+                }
+            }
+            Self::Rename { id, new_title } => db
+                .get_task_list_mut(&target_list)?
+                .rename_task(id, new_title)?,
+            Self::Complete { ids, force } => {
+                let task_list = db.get_task_list_mut(&target_list)?;
+
+                if !force {
+                    for id in &ids {
+                        // Tasks completed alongside their dependencies aren’t held back by them.
+                        let unmet: Vec<_> = task_list
+                            .unmet_dependencies(*id)
+                            .unwrap_or_default()
+                            .into_iter()
+                            .filter(|dependency| !ids.contains(dependency))
+                            .map(|dependency| dependency.to_string())
+                            .collect();
+
+                        if !unmet.is_empty() {
+                            anyhow::bail!(
+                                "task {} is waiting for unfinished task(s) {} (use --force to complete it anyway)",
+                                id,
+                                unmet.join(", ")
+                            );
+                        }
+                    }
+                }
+
+                if let Err(non_existent_ids) = task_list.complete_tasks(&ids) {
+                    let non_existent_ids: Vec<_> =
+                        non_existent_ids.iter().map(u32::to_string).collect();
+
+                    eprintln!(
+                        "Skipped tasks that do not exist: {}",
+                        non_existent_ids.join(", ")
+                    );
+                }
+            }
+            Self::CompleteText { text } => {
+                let id = db
+                    .get_task_list_mut(&target_list)?
+                    .complete_by_title(&text)?;
+                println!("Completed task {}", id);
+            }
+            Self::Uncomplete { id } => db.get_task_list_mut(&target_list)?.uncomplete_task(id)?,
+            Self::Prepend { id, text } => db
+                .get_task_list_mut(&target_list)?
+                .edit_title(id, |title| format!("{} {}", text, title))?,
+            Self::Append { id, text } => db
+                .get_task_list_mut(&target_list)?
+                .edit_title(id, |title| format!("{} {}", title, text))?,
+            Self::Start { id } => db.get_task_list_mut(&target_list)?.start_task(id)?,
+            Self::Cancel { id } => db.get_task_list_mut(&target_list)?.cancel_task(id)?,
+            Self::Note { id, text } => {
+                let task_list = db.get_task_list_mut(&target_list)?;
+
+                let notes = match text {
+                    Some(text) => text,
+                    None => edit_in_editor(task_list.notes(id)?.unwrap_or(""))?,
+                };
+
+                task_list.set_notes(id, Some(notes))?;
+            }
+            Self::SetDue { id, date } => db
+                .get_task_list_mut(&target_list)?
+                .set_due(id, Some(date))?,
+            Self::Remind { id, date } => {
+                db.get_task_list_mut(&target_list)?.add_reminder(id, date)?
+            }
+            Self::Unremind { id, date } => db
+                .get_task_list_mut(&target_list)?
+                .remove_reminder(id, date)?,
+            Self::Block { id, on } => db.get_task_list_mut(&target_list)?.add_dependency(id, on)?,
+            Self::Unblock { id, on } => db
+                .get_task_list_mut(&target_list)?
+                .remove_dependency(id, on)?,
+            Self::AddSub { id, title } => {
+                let index = db.get_task_list_mut(&target_list)?.add_subtask(id, title)?;
+                println!("{}", index);
+            }
+            Self::CompleteSub {
+                id,
+                index,
+                complete_parent,
+            } => {
+                db.get_task_list_mut(&target_list)?
+                    .complete_subtask(id, index, complete_parent)?
+            }
+            Self::Pin { id } => db.get_task_list_mut(&target_list)?.pin(id)?,
+            Self::Unpin { id } => db.get_task_list_mut(&target_list)?.unpin(id)?,
+            Self::Reminders => {
+                let today = Local::today().naive_local();
+                print!(
+                    "{}",
+                    format_reminders(db.with_reminders_due(today), &render_options)
+                )
+            }
+            Self::Recur { id, every } => db
+                .get_task_list_mut(&target_list)?
+                .set_recurrence(id, Some(every))?,
+            Self::Prioritize { id, priority } => db
+                .get_task_list_mut(&target_list)?
+                .set_priority(id, priority)?,
+            Self::Move { id, to, keep_id } => {
+                let new_id = db.move_task(id, &target_list, &to, keep_id)?;
+
+                if keep_id && new_id != id {
+                    println!(
+                        "Moved task to ‘{}’ with ID {}, since ID {} was taken",
+                        to, new_id, id
+                    );
+                } else {
+                    println!("Moved task to ‘{}’ with ID {}", to, new_id);
+                }
+            }
+            Self::Tag { id, tag } => db.get_task_list_mut(&target_list)?.add_tag(id, tag)?,
+            Self::Untag { id, tag } => db.get_task_list_mut(&target_list)?.remove_tag(id, &tag)?,
+            Self::Count { status } => {
+                println!("{}", db.get_task_list_mut(&target_list)?.count(status));
+            }
+            Self::Filter { tag } => {
+                for (name, id, task) in db.tasks_with_tag(&tag) {
+                    println!("{}: [{}] {}", name, id, task);
+                }
+            }
+            Self::Search { query } => print_grouped_by_list(db.search(&query), &render_options),
+            Self::Today => {
+                print_grouped_by_list(db.due_on(Local::today().naive_local()), &render_options)
+            }
+            Self::Overdue => {
+                print_grouped_by_list(db.overdue(Local::today().naive_local()), &render_options)
+            }
+            Self::DueBetween { start, end } => {
+                print_grouped_by_list(db.due_between(start, end)?, &render_options)
+            }
+            Self::Done { since } => {
+                // This cannot fail because every date exists in the local time zone, even if some
+                // of its times don’t.
+                let since =
+                    since.map_or_else(Local::today, |since| Local.from_local_date(&since).unwrap());
+                print_grouped_by_list(db.completed_since(since), &render_options);
+            }
+            Self::Show { id } => {
+                let task = db
+                    .get_task_list_mut(&target_list)?
+                    .get(id)
+                    .ok_or(t::task_list::Error::NonExistentTaskId(id))?;
+
+                print!("{}", task.render_detail(&render_options));
+            }
+            Self::Duplicate { id } => {
+                let new_id = db.get_task_list_mut(&target_list)?.duplicate_task(id)?;
+                println!("{}", new_id);
+            }
+            Self::RemoveRange { start, end } => {
+                let num_removed = db
+                    .get_task_list_mut(&target_list)?
+                    .remove_range(start..=end);
+                summarize(format!("Removed {} task(s)", num_removed));
+            }
+            Self::RemoveCompleted { all_lists } => {
+                let num_removed = if all_lists {
+                    db.remove_completed_everywhere()
+                } else {
+                    db.get_task_list_mut(&target_list)?.remove_completed_tasks()
+                };
+
+                summarize(format!("Removed {} completed task(s)", num_removed));
+            }
+            Self::Cleanup { before } => {
+                let num_removed = db
+                    .get_task_list_mut(&target_list)?
+                    .remove_completed_before(Local.from_local_date(&before).unwrap());
+                summarize(format!("Removed {} completed task(s)", num_removed));
+            }
+            Self::Doctor { fix } => {
+                let issues = db.validate();
+                let remaining = if fix {
+                    db.fix(&config.default_list)
+                } else {
+                    issues.clone()
+                };
+
+                for issue in &issues {
+                    if remaining.contains(issue) {
+                        println!("Problem: {}", issue);
+                    } else {
+                        println!("Fixed: {}", issue);
+                    }
+                }
+
+                if issues.is_empty() {
+                    println!("No problems found");
+                }
+            }
+            Self::Edit => *db = parse_edited_db(&edit_in_editor(&db.to_json())?)?,
+            Self::Archive => {
+                // The archive itself is only written once the database is saved, by `main`.
+                let num_archived = db.archive_completed().len();
+                summarize(format!("Archived {} completed task(s)", num_archived));
+            }
+            Self::ShowArchive => {
+                for archived in read_archive(companion_path(db_path, "archive.json"))? {
+                    println!("{}", archived);
+                }
+            }
+            Self::Clear => {
+                let num_removed = db.get_task_list_mut(&target_list)?.clear();
+                summarize(format!("Removed {} task(s)", num_removed));
+            }
             Self::AddTaskList { name } => {
                 db.add_task_list(name.clone(), TaskList::default());
 
@@ -78,48 +1241,1391 @@ impl Subcommand {
                 // it must exist.
                 db.set_current(name).unwrap();
             }
-            Self::RemoveTaskList { name } => db.remove_task_list(name)?,
-            Self::RenameTaskList { old_name, new_name } => {
-                db.rename_task_list(old_name, new_name)?
+            Self::RemoveTaskList { name } => {
+                db.remove_task_list(name.clone(), &config.default_list)?;
+                summarize(format!("Removed task list ‘{}’", name));
+            }
+            Self::RenameTaskList {
+                old_name,
+                new_name,
+                force,
+            } => {
+                if force {
+                    db.rename_or_merge_task_list(old_name, new_name)?
+                } else {
+                    db.rename_task_list(old_name, new_name)?
+                }
+            }
+            Self::Merge { source, dest } => db.merge_lists(&source, &dest)?,
+            Self::MoveListTo { name, index } => db.move_list_to(name, index)?,
+            Self::SetCurrent { name } => {
+                // A task list named like a number is picked over the task list at that position.
+                match name.parse() {
+                    Ok(index) if !db.list_names().contains(&name.as_str()) => {
+                        db.set_current_by_index(index)?
+                    }
+                    _ => db.set_current(name)?,
+                }
+            }
+            Self::SetPrevious => {
+                if !db.switch_to_previous() {
+                    println!("There is no previous task list to switch back to");
+                }
+            }
+            Self::Lists => print!("{}", format_list_names(db)),
+            Self::Next { all } => match db.next_actionable(Local::today().naive_local(), all) {
+                Some((name, id, task)) => {
+                    println!("{}: [{}] {}", name, id, task.render(&render_options))
+                }
+                None => println!("Nothing actionable"),
+            },
+            Self::RenameTag { old, new } => {
+                let num_renamed = db.rename_tag(&old, &new);
+                println!("Renamed tag on {} task(s)", num_renamed);
+            }
+            Self::LabelColor { tag, color } => db.set_tag_color(tag, color),
+            Self::TagSummary => {
+                for (tag, incomplete, complete) in db.tag_counts() {
+                    println!("{}: {} incomplete, {} complete", tag, incomplete, complete);
+                }
+            }
+            Self::Stats => {
+                let stats = db.stats();
+
+                for (name, counts) in stats.task_lists {
+                    println!("{}: {}", name, counts);
+                }
+
+                println!("Total: {}", stats.total);
+            }
+            Self::Export { format } => match format {
+                ExportFormat::Json => println!("{}", db.to_json()),
+                ExportFormat::Markdown => print!("{}", db.to_markdown()),
+                ExportFormat::Csv => print!("{}", db.to_csv()),
+            },
+            Self::Import { path, format } => {
+                let other = match format {
+                    ImportFormat::Json => Db::from_json(&fs::read_to_string(path)?)?,
+                    ImportFormat::Csv => Db::from_csv(fs::File::open(path)?)?,
+                };
+
+                db.import(other)?;
+            }
+            Self::Restore { path } => {
+                let backup = Db::from_json(&fs::read_to_string(&path)?)?;
+
+                let prompt = format!(
+                    "This replaces all task lists with those in ‘{}’. Continue?",
+                    path.display()
+                );
+
+                // Nothing is saved in a dry run, so there is nothing to confirm.
+                if opts.yes || opts.dry_run || confirm(&prompt)? {
+                    db.restore(backup)?;
+                }
+            }
+            Self::Purge => {
+                // Nothing is saved in a dry run, so there is nothing to confirm.
+                if !opts.yes && !opts.dry_run {
+                    // Scripts have no way of confirming, and purging by accident is too costly to
+                    // go ahead with a warning as removals do.
+                    if !atty::is(atty::Stream::Stdout) {
+                        anyhow::bail!(
+                            "refusing to purge the database without confirmation since t is not \
+                             running interactively (pass --yes to purge it anyway)"
+                        );
+                    }
+
+                    if !confirm_by_typing(
+                        "This removes every task list and task. Type ‘purge’ to continue:",
+                        "purge",
+                    )? {
+                        println!("Nothing was purged");
+                        return Ok(());
+                    }
+                }
+
+                *db = Db::with_default_list(config.default_list.clone());
+                println!("Reset the database to its default state");
+            }
+            Self::Completions { shell } => write_completions(shell, &mut io::stdout())?,
+            Self::Undo => match history.pop() {
+                Some(previous_db) => *db = previous_db,
+                None => println!("Nothing to undo"),
+            },
+            Self::History { limit } => {
+                let log_path = companion_path(db_path, "audit.log");
+
+                if !log_path.exists() {
+                    println!("No changes have been recorded yet");
+                    return Ok(());
+                }
+
+                let log = fs::read_to_string(log_path)?;
+                let lines: Vec<_> = log.lines().collect();
+                let now = Utc::now();
+
+                for line in &lines[lines.len().saturating_sub(limit)..] {
+                    match parse_audit_entry(line) {
+                        Some(entry) => println!("{}", entry.render(now)),
+                        // A line we can’t make sense of is still worth seeing as it is.
+                        None => println!("{}", line),
+                    }
+                }
             }
-            Self::SetCurrent { name } => db.set_current(name)?,
         }
 
         Ok(())
     }
 }
 
-fn read_db(path: impl AsRef<Path>) -> anyhow::Result<Db> {
-    Ok(serde_json::from_reader(fs::File::open(&path)?)?)
+/// Works out which task list a subcommand acts on: the one given with `--in`, which can be named
+/// loosely, or the current one otherwise.
+fn resolve_target_list(db: &Db, opts: &Opts) -> anyhow::Result<String> {
+    match &opts.target_list {
+        Some(name) => Ok(db.resolve_list_name(name)?.to_string()),
+        None => Ok(db.current_list().to_string()),
+    }
 }
 
-fn save_db(path: impl AsRef<Path>, db: &Db) -> anyhow::Result<()> {
-    create_dir_if_missing(&path)?;
+/// Prints tasks from several task lists under the name of the task list each belongs to.
+fn print_grouped_by_list(tasks: Vec<(String, u32, &Task)>, options: &RenderOptions) {
+    let mut previous_name = None;
 
-    Ok(fs::write(path, serde_json::to_vec(db)?)?)
+    for (name, id, task) in tasks {
+        if previous_name.as_ref() != Some(&name) {
+            println!("{}", name);
+        }
+
+        println!("  [{}] {}", id, task.render(options));
+        previous_name = Some(name);
+    }
 }
 
-fn create_dir_if_missing(path: impl AsRef<Path>) -> anyhow::Result<()> {
-    let path = path.as_ref();
+/// Formats tasks grouped by task list like [`print_grouped_by_list`], with each task’s reminders
+/// listed beneath it in chronological order.
+fn format_reminders(tasks: Vec<(String, u32, &Task)>, options: &RenderOptions) -> String {
+    let mut formatted = String::new();
+    let mut previous_name = None;
 
-    if let Some(parent_path) = path.parent() {
-        if !parent_path.exists() {
-            fs::create_dir_all(parent_path)?;
+    for (name, id, task) in tasks {
+        if previous_name.as_ref() != Some(&name) {
+            formatted.push_str(&format!("{}\n", name));
+        }
+
+        formatted.push_str(&format!("  [{}] {}\n", id, task.render(options)));
+
+        let mut reminders = task.reminders().to_vec();
+        reminders.sort_unstable();
+
+        for reminder in reminders {
+            formatted.push_str(&format!(
+                "      reminder {}\n",
+                options.dates.format(reminder, options.today)
+            ));
         }
+
+        previous_name = Some(name);
+    }
+
+    formatted
+}
+
+/// Finds the title of each task in text with one task per line, skipping blank lines and comments
+/// (lines starting with #).
+fn task_titles(text: &str) -> impl Iterator<Item = &str> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// Describes what a subcommand run with `--dry-run` would have changed.
+fn format_dry_run(before: &Db, after: &Db) -> String {
+    let mut formatted = "Dry run: nothing was saved, but these changes would be made\n".to_string();
+
+    for change in diff::diff(before, after) {
+        formatted.push_str(&format!("  {}\n", change));
     }
 
+    formatted
+}
+
+fn format_list_names(db: &Db) -> String {
+    db.list_names()
+        .into_iter()
+        .map(|name| {
+            if name == db.current_list() {
+                format!("{} *\n", name)
+            } else {
+                format!("{}\n", name)
+            }
+        })
+        .collect()
+}
+
+fn write_completions(shell: Shell, out: &mut impl Write) -> anyhow::Result<()> {
+    Opts::clap().gen_completions_to("t", shell, out);
     Ok(())
 }
 
-fn get_db_path() -> anyhow::Result<PathBuf> {
-    use etcetera::app_strategy::AppStrategy;
+fn edit_in_editor(text: &str) -> anyhow::Result<String> {
+    let editor = env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
+    let (path, mut file) = create_temp_file("json")?;
 
-    let strategy =
-        etcetera::app_strategy::choose_app_strategy(etcetera::app_strategy::AppStrategyArgs {
-            top_level_domain: "com".to_string(),
-            author: "arzg".to_string(),
-            app_name: "t".to_string(),
-        })?;
+    file.write_all(text.as_bytes())?;
+    drop(file);
 
-    Ok(strategy.in_data_dir("db.json"))
+    let status = process::Command::new(&editor).arg(&path).status();
+    let edited = fs::read_to_string(&path);
+    fs::remove_file(&path)?;
+
+    if !status?.success() {
+        anyhow::bail!("editor ‘{}’ exited unsuccessfully", editor);
+    }
+
+    Ok(edited?)
+}
+
+/// Creates a file in the temporary directory that didn’t exist before, so that nobody else can
+/// have put anything there first. The file name ends in `extension` so that editors recognize it.
+fn create_temp_file(extension: &str) -> anyhow::Result<(PathBuf, fs::File)> {
+    for attempt in 0..100 {
+        let path = env::temp_dir().join(format!("t-{}-{}.{}", process::id(), attempt, extension));
+
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(file) => return Ok((path, file)),
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(error.into()),
+        }
+    }
+
+    anyhow::bail!("could not create a temporary file for the editor")
+}
+
+/// Reads a database edited by hand, refusing it if it has any of the problems `doctor` looks for.
+fn parse_edited_db(json: &str) -> anyhow::Result<Db> {
+    let db = Db::from_json(json)?;
+    let issues = db.validate();
+
+    if !issues.is_empty() {
+        let issues: Vec<_> = issues.iter().map(ToString::to_string).collect();
+        anyhow::bail!(
+            "the edited database was not saved because {}",
+            issues.join(", and ")
+        );
+    }
+
+    Ok(db)
+}
+
+/// Decides whether a subcommand that removes things may go ahead. Without `--yes` the user is
+/// asked first, unless `t` isn’t being run interactively, in which case it goes ahead with a
+/// warning so that scripts and pipes keep working.
+fn should_proceed(prompt: &str, yes: bool, interactive: bool) -> anyhow::Result<bool> {
+    if yes {
+        return Ok(true);
+    }
+
+    if !interactive {
+        eprintln!(
+            "Warning: going ahead without confirmation since t is not running interactively \
+             (pass --yes to silence this warning)"
+        );
+        return Ok(true);
+    }
+
+    confirm(prompt)
+}
+
+fn confirm(prompt: &str) -> anyhow::Result<bool> {
+    print!("{} [y/N] ", prompt);
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// Asks the user to type a word to go ahead, for changes too drastic for a quick `y`.
+fn confirm_by_typing(prompt: &str, word: &str) -> anyhow::Result<bool> {
+    print!("{} ", prompt);
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+
+    Ok(answer.trim() == word)
+}
+
+/// Reads a date given on the command line, which can be relative to today, such as `tomorrow`.
+fn parse_date(s: &str) -> Result<NaiveDate, date::Error> {
+    date::parse_due(s, Local::today().naive_local())
+}
+
+/// The number of columns in the terminal, or 80 if stdout isn’t a terminal.
+fn terminal_width() -> usize {
+    terminal_size::terminal_size().map_or(80, |(terminal_size::Width(width), _)| usize::from(width))
+}
+
+/// Prints text through the user’s pager if it wouldn’t fit on the screen, or directly otherwise.
+fn print_paged(text: &str) -> anyhow::Result<()> {
+    let num_lines = text.lines().count();
+    let terminal_height =
+        terminal_size::terminal_size().map(|(_, terminal_size::Height(height))| height.into());
+
+    let pager = pager_command(env::var("PAGER").ok())
+        .filter(|_| should_page(atty::is(atty::Stream::Stdout), num_lines, terminal_height));
+
+    let (program, args) = match pager.as_ref().and_then(|pager| pager.split_first()) {
+        Some(command) => command,
+        None => {
+            println!("{}", text);
+            return Ok(());
+        }
+    };
+
+    let mut child = match process::Command::new(program)
+        .args(args)
+        .stdin(process::Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        // A pager that can’t be run shouldn’t stop the user from seeing their tasks.
+        Err(_) => {
+            println!("{}", text);
+            return Ok(());
+        }
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // The user quitting the pager early closes the pipe, which isn’t an error.
+        let _ = writeln!(stdin, "{}", text);
+    }
+
+    child.wait()?;
+
+    Ok(())
+}
+
+/// Splits the pager command into a program and its arguments, defaulting to `less -R` (which
+/// passes colors through). An empty `$PAGER` turns paging off.
+fn pager_command(pager: Option<String>) -> Option<Vec<String>> {
+    let pager = pager.unwrap_or_else(|| "less -R".to_string());
+    let command: Vec<_> = pager.split_whitespace().map(str::to_string).collect();
+
+    if command.is_empty() {
+        None
+    } else {
+        Some(command)
+    }
+}
+
+/// Output is only paged when it’s going to a terminal it wouldn’t fit on.
+fn should_page(is_tty: bool, num_lines: usize, terminal_height: Option<usize>) -> bool {
+    is_tty && terminal_height.is_some_and(|height| num_lines > height)
+}
+
+fn should_use_color() -> bool {
+    env::var_os("NO_COLOR").is_none() && atty::is(atty::Stream::Stdout)
+}
+
+fn status_style(opts: &Opts) -> StatusStyle {
+    if opts.plain || opts.ascii || env::var_os("T_ASCII").is_some_and(|value| value == "1") {
+        StatusStyle::Ascii
+    } else {
+        StatusStyle::Unicode
+    }
+}
+
+/// Settings read from `config.toml` next to the database.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(default)]
+struct Config {
+    /// The name of the task list a fresh database starts out with.
+    default_list: String,
+    /// Glyphs to draw statuses with, from the `[glyphs]` table.
+    glyphs: Glyphs,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            default_list: "Tasks".to_string(),
+            glyphs: Glyphs::default(),
+        }
+    }
+}
+
+fn read_config(path: impl AsRef<Path>) -> anyhow::Result<Config> {
+    let path = path.as_ref();
+
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+
+    let config: Config = toml::from_str(&fs::read_to_string(path)?)?;
+    config.glyphs.validate()?;
+
+    Ok(config)
+}
+
+fn read_history(path: impl AsRef<Path>) -> anyhow::Result<History> {
+    let path = path.as_ref();
+
+    if path.exists() {
+        Ok(serde_json::from_reader(fs::File::open(path)?)?)
+    } else {
+        Ok(History::default())
+    }
+}
+
+fn save_history(path: impl AsRef<Path>, history: &History) -> anyhow::Result<()> {
+    Ok(storage::write_atomically(
+        path.as_ref(),
+        &serde_json::to_vec(history)?,
+    )?)
+}
+
+/// Adds a line recording a change to the end of the audit log, creating the log if it doesn’t
+/// exist yet. Earlier lines are never rewritten, so the log survives across runs.
+fn append_to_audit_log(
+    path: impl AsRef<Path>,
+    time: DateTime<Utc>,
+    description: &str,
+) -> anyhow::Result<()> {
+    let path = path.as_ref();
+    create_dir_if_missing(path)?;
+
+    let mut log = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+
+    writeln!(log, "{} {}", time.format("%Y-%m-%dT%H:%M:%SZ"), description)?;
+
+    Ok(())
+}
+
+/// Quotes a value in an audit log line if it would otherwise be hard to tell where it ends.
+fn audit_value(value: &str) -> String {
+    if value.is_empty() || value.contains(|c: char| c.is_whitespace() || c == '"' || c == '=') {
+        format!("{:?}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// A change recorded in the audit log.
+#[derive(Debug, PartialEq)]
+struct AuditEntry {
+    timestamp: DateTime<Utc>,
+    action: String,
+    args: Vec<(String, String)>,
+}
+
+impl AuditEntry {
+    /// Renders the entry as it was written, but with how long ago it was made in place of the
+    /// timestamp.
+    fn render(&self, now: DateTime<Utc>) -> String {
+        let mut rendered = format!("{}: {}", relative_time(self.timestamp, now), self.action);
+
+        for (key, value) in &self.args {
+            rendered.push_str(&format!(" {}={}", key, audit_value(value)));
+        }
+
+        rendered
+    }
+}
+
+/// Parses a line written by [`append_to_audit_log`], giving `None` if it isn’t in that form.
+fn parse_audit_entry(line: &str) -> Option<AuditEntry> {
+    let (timestamp, rest) = line.split_once(' ')?;
+    let timestamp = DateTime::parse_from_rfc3339(timestamp)
+        .ok()?
+        .with_timezone(&Utc);
+
+    let (action, mut rest) = rest.split_once(' ').unwrap_or((rest, ""));
+
+    if action.is_empty() {
+        return None;
+    }
+
+    let mut args = Vec::new();
+
+    while !rest.is_empty() {
+        let (key, value) = rest.split_once('=')?;
+
+        if key.is_empty() || key.contains(' ') {
+            return None;
+        }
+
+        let (value, remaining) = parse_audit_value(value)?;
+        args.push((key.to_string(), value));
+
+        rest = match remaining.strip_prefix(' ') {
+            Some(remaining) => remaining,
+            None if remaining.is_empty() => remaining,
+            None => return None,
+        };
+    }
+
+    Some(AuditEntry {
+        timestamp,
+        action: action.to_string(),
+        args,
+    })
+}
+
+/// Reads a value written by [`audit_value`] off the start of `s`, returning it along with whatever
+/// follows it.
+fn parse_audit_value(s: &str) -> Option<(String, &str)> {
+    let quoted = match s.strip_prefix('"') {
+        Some(quoted) => quoted,
+        None => {
+            let end = s.find(' ').unwrap_or(s.len());
+            return Some((s[..end].to_string(), &s[end..]));
+        }
+    };
+
+    let mut value = String::new();
+    let mut chars = quoted.char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, &quoted[i + 1..])),
+            '\\' => value.push(match chars.next()?.1 {
+                'n' => '\n',
+                't' => '\t',
+                'r' => '\r',
+                c @ ('"' | '\\' | '\'') => c,
+                _ => return None,
+            }),
+            c => value.push(c),
+        }
+    }
+
+    None
+}
+
+/// Describes how long before `now` something happened, such as “3 hours ago”.
+fn relative_time(time: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let seconds = (now - time).num_seconds();
+
+    let (amount, unit) = if seconds < 60 {
+        return "just now".to_string();
+    } else if seconds < 60 * 60 {
+        (seconds / 60, "minute")
+    } else if seconds < 24 * 60 * 60 {
+        (seconds / (60 * 60), "hour")
+    } else {
+        (seconds / (24 * 60 * 60), "day")
+    };
+
+    format!(
+        "{} {}{} ago",
+        amount,
+        unit,
+        if amount == 1 { "" } else { "s" }
+    )
+}
+
+fn read_archive(path: impl AsRef<Path>) -> anyhow::Result<Vec<ArchivedTask>> {
+    let path = path.as_ref();
+
+    if path.exists() {
+        Ok(serde_json::from_reader(fs::File::open(path)?)?)
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+/// Takes the tasks that an undone `archive` moved to the archive back out of it, since undoing put
+/// them back in the database. The archive is left alone if the undone change wasn’t an `archive`.
+fn unarchive_undone(path: &Path, restored: &Db, undone: &Db) -> anyhow::Result<()> {
+    let archived = restored.clone().archive_completed();
+
+    // Archiving leaves no completed tasks behind, so an undone change that did is not an archive.
+    if archived.is_empty() || !undone.clone().archive_completed().is_empty() {
+        return Ok(());
+    }
+
+    let mut archive = read_archive(path)?;
+
+    if archive.ends_with(&archived) {
+        archive.truncate(archive.len() - archived.len());
+        save_archive(path, &archive)?;
+    }
+
+    Ok(())
+}
+
+fn save_archive(path: impl AsRef<Path>, archive: &[ArchivedTask]) -> anyhow::Result<()> {
+    Ok(storage::write_atomically(
+        path.as_ref(),
+        &serde_json::to_vec_pretty(archive)?,
+    )?)
+}
+
+const LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Locks the database so that other instances of `t` can’t access it until the returned file is
+/// dropped. We lock a separate file rather than the database itself, since saving replaces the
+/// database file.
+fn lock_db(db_path: &Path, timeout: Duration) -> anyhow::Result<fs::File> {
+    create_dir_if_missing(db_path)?;
+
+    let lock_file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path_with_suffix(db_path, ".lock"))?;
+
+    let start = Instant::now();
+
+    loop {
+        match lock_file.try_lock_exclusive() {
+            Ok(()) => return Ok(lock_file),
+            Err(_) if start.elapsed() < timeout => thread::sleep(Duration::from_millis(50)),
+            Err(_) => anyhow::bail!(
+                "the database is in use by another instance of t; try again once it has finished"
+            ),
+        }
+    }
+}
+
+/// Returns the path of a file kept alongside a database, such as its undo history. The file is
+/// named after the database, such as `work.history.json` for `work.json`, so that databases in the
+/// same directory each get their own.
+fn companion_path(db_path: &Path, name: &str) -> PathBuf {
+    let mut file_name = db_path.file_stem().unwrap_or_default().to_os_string();
+    file_name.push(".");
+    file_name.push(name);
+
+    db_path.with_file_name(file_name)
+}
+
+fn path_with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(suffix);
+
+    path.with_file_name(file_name)
+}
+
+fn create_dir_if_missing(path: impl AsRef<Path>) -> anyhow::Result<()> {
+    let path = path.as_ref();
+
+    if let Some(parent_path) = path.parent() {
+        if !parent_path.exists() {
+            fs::create_dir_all(parent_path)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Works out which database file to use: the one given with `--db` takes precedence over the one
+/// in `T_DB_PATH`, and the default one is used if neither is set.
+fn resolve_db_path(flag: Option<PathBuf>, env_var: Option<OsString>) -> anyhow::Result<PathBuf> {
+    let env_var = env_var.filter(|env_var| !env_var.is_empty());
+
+    match flag.or_else(|| env_var.map(PathBuf::from)) {
+        Some(path) => Ok(path),
+        None => get_db_path(),
+    }
+}
+
+fn get_db_path() -> anyhow::Result<PathBuf> {
+    use etcetera::app_strategy::AppStrategy;
+
+    let strategy =
+        etcetera::app_strategy::choose_app_strategy(etcetera::app_strategy::AppStrategyArgs {
+            top_level_domain: "com".to_string(),
+            author: "arzg".to_string(),
+            app_name: "t".to_string(),
+        })?;
+
+    Ok(strategy.in_data_dir("db.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("t-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+
+    #[test]
+    fn saving_over_existing_db_leaves_well_formed_db() {
+        let dir = temp_dir("save");
+        let db_path = dir.join("db.json");
+        let mut storage = JsonFileStorage::new(db_path.clone());
+
+        storage.save(&Db::default()).unwrap();
+
+        let mut db = storage.load().unwrap();
+        db.get_current_task_list_mut()
+            .unwrap()
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        storage.save(&db).unwrap();
+
+        assert_eq!(storage.load().unwrap(), db);
+        assert!(!path_with_suffix(&db_path, ".tmp").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn db_can_be_saved_and_read_as_toml() {
+        let dir = temp_dir("toml");
+        let db_path = dir.join("db.toml");
+
+        let mut db = Db::default();
+        db.get_current_task_list_mut()
+            .unwrap()
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+
+        let mut storage = JsonFileStorage::new(db_path.clone());
+        storage.save(&db).unwrap();
+
+        assert!(fs::read_to_string(&db_path)
+            .unwrap()
+            .contains("title = 'Buy some milk'"));
+        assert_eq!(storage.load().unwrap(), db);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn tasks_can_be_added_to_task_list_other_than_current() {
+        let mut db = Db::default();
+        db.add_task_list("Work".to_string(), TaskList::default());
+
+        Subcommand::Add {
+            title: "Write report".to_string(),
+            due: None,
+            tags: Vec::new(),
+            priority: None,
+            id: None,
+            unique: false,
+        }
+        .execute(
+            &mut db,
+            &mut History::default(),
+            Path::new("db.json"),
+            &Opts::from_iter(&["t", "--in", "Work"]),
+            &Config::default(),
+        )
+        .unwrap();
+
+        let stats = db.stats();
+        assert_eq!(db.current_list(), "Tasks");
+        assert_eq!(stats.task_lists[0].1.total(), 0);
+        assert_eq!(stats.task_lists[1].1.total(), 1);
+    }
+
+    #[test]
+    fn target_task_list_can_be_named_loosely() {
+        let mut db = Db::default();
+        db.add_task_list("Work".to_string(), TaskList::default());
+
+        assert_eq!(
+            resolve_target_list(&db, &Opts::from_iter(&["t", "--in", "wrk"])).unwrap(),
+            "Work"
+        );
+        assert_eq!(
+            resolve_target_list(&db, &Opts::from_iter(&["t"])).unwrap(),
+            "Tasks"
+        );
+    }
+
+    #[test]
+    fn purging_with_yes_resets_db_to_default() {
+        let mut db = Db::default();
+        db.add_task_list("Work".to_string(), TaskList::default());
+        db.set_current("Work".to_string()).unwrap();
+        db.get_current_task_list_mut()
+            .unwrap()
+            .add_task(Task::new("Write report".to_string()))
+            .unwrap();
+
+        Subcommand::Purge
+            .execute(
+                &mut db,
+                &mut History::default(),
+                Path::new("db.json"),
+                &Opts::from_iter(&["t", "--yes"]),
+                &Config::default(),
+            )
+            .unwrap();
+
+        assert_eq!(db, Db::default());
+    }
+
+    #[test]
+    fn purging_resets_db_to_the_configured_default_list() {
+        let mut db = Db::default();
+        let config = Config {
+            default_list: "Inbox".to_string(),
+            ..Config::default()
+        };
+
+        Subcommand::Purge
+            .execute(
+                &mut db,
+                &mut History::default(),
+                Path::new("db.json"),
+                &Opts::from_iter(&["t", "--yes"]),
+                &config,
+            )
+            .unwrap();
+
+        assert_eq!(db, Db::with_default_list("Inbox".to_string()));
+    }
+
+    #[test]
+    fn targeting_non_existent_task_list_gives_error() {
+        let mut db = Db::default();
+
+        let result = Subcommand::Add {
+            title: "Write report".to_string(),
+            due: None,
+            tags: Vec::new(),
+            priority: None,
+            id: None,
+            unique: false,
+        }
+        .execute(
+            &mut db,
+            &mut History::default(),
+            Path::new("db.json"),
+            &Opts::from_iter(&["t", "--in", "Work"]),
+            &Config::default(),
+        );
+
+        assert_eq!(
+            result.unwrap_err().downcast::<t::db::Error>().unwrap(),
+            t::db::Error::NonExistentTaskList("Work".to_string())
+        );
+    }
+
+    #[test]
+    fn acting_on_dangling_current_task_list_gives_error() {
+        let mut db: Db =
+            serde_json::from_str(r#"{"task_lists":{"Tasks":{"tasks":{}}},"current_list":"Gone"}"#)
+                .unwrap();
+
+        let result = Subcommand::Add {
+            title: "Write report".to_string(),
+            due: None,
+            tags: Vec::new(),
+            priority: None,
+            id: None,
+            unique: false,
+        }
+        .execute(
+            &mut db,
+            &mut History::default(),
+            Path::new("db.json"),
+            &Opts::from_iter(&["t"]),
+            &Config::default(),
+        );
+
+        assert_eq!(
+            result.unwrap_err().downcast::<t::db::Error>().unwrap(),
+            t::db::Error::NonExistentTaskList("Gone".to_string())
+        );
+    }
+
+    #[test]
+    fn current_task_list_can_be_set_by_position_unless_a_task_list_has_that_name() {
+        let mut db = Db::default();
+        db.add_task_list("Work".to_string(), TaskList::default());
+        db.add_task_list("0".to_string(), TaskList::default());
+
+        let set_current = |db: &mut Db, name: &str| {
+            Subcommand::SetCurrent {
+                name: name.to_string(),
+            }
+            .execute(
+                db,
+                &mut History::default(),
+                Path::new("db.json"),
+                &Opts::from_iter(&["t"]),
+                &Config::default(),
+            )
+        };
+
+        set_current(&mut db, "1").unwrap();
+        assert_eq!(db.current_list(), "Work");
+
+        set_current(&mut db, "0").unwrap();
+        assert_eq!(db.current_list(), "0");
+
+        assert_eq!(
+            set_current(&mut db, "5")
+                .unwrap_err()
+                .downcast::<t::db::Error>()
+                .unwrap(),
+            t::db::Error::TaskListIndexOutOfRange { index: 5, len: 3 }
+        );
+    }
+
+    #[test]
+    fn dry_run_lists_what_would_change() {
+        let before = Db::default();
+        let mut after = before.clone();
+        after
+            .get_current_task_list_mut()
+            .unwrap()
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+
+        assert_eq!(
+            format_dry_run(&before, &after),
+            "Dry run: nothing was saved, but these changes would be made\n  + Tasks: [0] Buy some milk\n"
+        );
+    }
+
+    #[test]
+    fn list_names_are_printed_with_current_marked() {
+        let mut db = Db::default();
+        db.add_task_list("Work".to_string(), TaskList::default());
+        db.add_task_list("Home".to_string(), TaskList::default());
+        db.set_current("Work".to_string()).unwrap();
+
+        assert_eq!(format_list_names(&db), "Tasks\nWork *\nHome\n");
+    }
+
+    #[test]
+    fn temp_files_are_new_json_files() {
+        let (first_path, _first) = create_temp_file("json").unwrap();
+        let (second_path, _second) = create_temp_file("json").unwrap();
+
+        assert_ne!(first_path, second_path);
+        assert_eq!(first_path.extension().unwrap(), "json");
+
+        fs::remove_file(first_path).unwrap();
+        fs::remove_file(second_path).unwrap();
+    }
+
+    #[test]
+    fn edited_db_is_read_back() {
+        let mut db = Db::default();
+        db.add_task_list("Work".to_string(), TaskList::default());
+
+        let edited = db.to_json().replace("\"Work\"", "\"Office\"");
+
+        assert_eq!(
+            parse_edited_db(&edited).unwrap().list_names(),
+            ["Tasks", "Office"]
+        );
+    }
+
+    #[test]
+    fn edited_db_that_does_not_parse_is_refused() {
+        assert!(parse_edited_db("{ not json").is_err());
+    }
+
+    #[test]
+    fn edited_db_with_problems_is_refused() {
+        let edited = Db::default()
+            .to_json()
+            .replace("\"current_list\": \"Tasks\"", "\"current_list\": \"Gone\"");
+
+        assert_eq!(
+            parse_edited_db(&edited).unwrap_err().to_string(),
+            "the edited database was not saved because the current task list ‘Gone’ does not exist"
+        );
+    }
+
+    #[test]
+    fn output_is_paged_only_on_a_terminal_it_does_not_fit_on() {
+        assert!(should_page(true, 50, Some(24)));
+        assert!(!should_page(true, 24, Some(24)));
+        assert!(!should_page(false, 50, Some(24)));
+        assert!(!should_page(true, 50, None));
+    }
+
+    #[test]
+    fn pager_defaults_to_less_and_can_be_turned_off() {
+        assert_eq!(
+            pager_command(None),
+            Some(vec!["less".to_string(), "-R".to_string()])
+        );
+        assert_eq!(
+            pager_command(Some("more".to_string())),
+            Some(vec!["more".to_string()])
+        );
+        assert_eq!(pager_command(Some(String::new())), None);
+        assert_eq!(pager_command(Some("  ".to_string())), None);
+    }
+
+    #[test]
+    fn task_titles_skip_blank_lines_and_comments() {
+        assert_eq!(
+            task_titles("Buy some milk\n\n  # Weekend\n  Fix the fence  \n#Call mum\n")
+                .collect::<Vec<_>>(),
+            ["Buy some milk", "Fix the fence"]
+        );
+    }
+
+    #[test]
+    fn reminders_are_listed_beneath_their_task_in_order() {
+        let mut task = Task::new("Buy some milk".to_string());
+        task.add_reminder(NaiveDate::from_ymd(2024, 5, 3));
+        task.add_reminder(NaiveDate::from_ymd(2024, 5, 1));
+
+        let options = RenderOptions {
+            today: Some(NaiveDate::from_ymd(2024, 5, 3)),
+            ..RenderOptions::default()
+        };
+
+        assert_eq!(
+            format_reminders(
+                vec![("Tasks".to_string(), 0, &task)],
+                &RenderOptions {
+                    dates: DateStyle::Relative,
+                    ..options.clone()
+                }
+            ),
+            "Tasks\n  [0] • Buy some milk\n      reminder 2 days ago\n      reminder today\n"
+        );
+        assert_eq!(
+            format_reminders(vec![("Tasks".to_string(), 0, &task)], &options),
+            "Tasks\n  [0] • Buy some milk\n      reminder 2024-05-01\n      reminder 2024-05-03\n"
+        );
+    }
+
+    #[test]
+    fn removing_with_yes_proceeds_without_asking() {
+        assert!(should_proceed("Remove task 0 from ‘Tasks’?", true, true).unwrap());
+    }
+
+    #[test]
+    fn only_subcommands_that_remove_things_ask_for_confirmation() {
+        assert_eq!(
+            Subcommand::Clear.confirmation_prompt("Tasks"),
+            Some("Remove all tasks from ‘Tasks’?".to_string())
+        );
+        assert_eq!(
+            Subcommand::Remove { id: 3 }.confirmation_prompt("Work"),
+            Some("Remove task 3 from ‘Work’?".to_string())
+        );
+        assert_eq!(Subcommand::Undo.confirmation_prompt("Tasks"), None);
+    }
+
+    #[test]
+    fn audit_descriptions_name_subcommand_and_arguments() {
+        assert_eq!(
+            Subcommand::Complete {
+                ids: vec![3, 4],
+                force: false
+            }
+            .audit_description("Tasks"),
+            "complete ids=3,4 list=Tasks"
+        );
+        assert_eq!(
+            Subcommand::RenameTaskList {
+                old_name: "Work".to_string(),
+                new_name: "Day job".to_string(),
+                force: false,
+            }
+            .audit_description("Tasks"),
+            "rename-task-list old_name=Work new_name=\"Day job\""
+        );
+        assert_eq!(
+            Subcommand::Count {
+                status: Some(Status::InProgress)
+            }
+            .audit_description("Tasks"),
+            "count status=in-progress list=Tasks"
+        );
+    }
+
+    #[test]
+    fn audit_log_is_appended_to() {
+        let dir = temp_dir("audit-log");
+        let log_path = dir.join("audit.log");
+        let time = Utc.ymd(2024, 5, 1).and_hms(10, 0, 0);
+
+        append_to_audit_log(&log_path, time, "complete ids=3 list=Tasks").unwrap();
+        append_to_audit_log(&log_path, time, "undo").unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&log_path).unwrap(),
+            "2024-05-01T10:00:00Z complete ids=3 list=Tasks\n2024-05-01T10:00:00Z undo\n"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn audit_log_lines_are_parsed_into_entries() {
+        let time = Utc.ymd(2024, 5, 1).and_hms(10, 0, 0);
+
+        assert_eq!(
+            parse_audit_entry("2024-05-01T10:00:00Z complete ids=3,4 list=Tasks"),
+            Some(AuditEntry {
+                timestamp: time,
+                action: "complete".to_string(),
+                args: vec![
+                    ("ids".to_string(), "3,4".to_string()),
+                    ("list".to_string(), "Tasks".to_string()),
+                ],
+            })
+        );
+        assert_eq!(
+            parse_audit_entry(r#"2024-05-01T10:00:00Z add title="Say \"hi\"" list="Day job""#),
+            Some(AuditEntry {
+                timestamp: time,
+                action: "add".to_string(),
+                args: vec![
+                    ("title".to_string(), "Say \"hi\"".to_string()),
+                    ("list".to_string(), "Day job".to_string()),
+                ],
+            })
+        );
+        assert_eq!(
+            parse_audit_entry("2024-05-01T10:00:00Z undo"),
+            Some(AuditEntry {
+                timestamp: time,
+                action: "undo".to_string(),
+                args: Vec::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn malformed_audit_log_lines_are_not_parsed() {
+        assert_eq!(parse_audit_entry("yesterday undo"), None);
+        assert_eq!(parse_audit_entry("2024-05-01T10:00:00Z"), None);
+        assert_eq!(
+            parse_audit_entry(r#"2024-05-01T10:00:00Z add title="Buy some milk"#),
+            None
+        );
+        assert_eq!(parse_audit_entry("2024-05-01T10:00:00Z complete 3"), None);
+    }
+
+    #[test]
+    fn audit_entries_are_rendered_with_relative_times() {
+        let entry = parse_audit_entry(r#"2024-05-01T10:00:00Z add title="Buy some milk""#).unwrap();
+
+        assert_eq!(
+            entry.render(Utc.ymd(2024, 5, 1).and_hms(13, 30, 0)),
+            "3 hours ago: add title=\"Buy some milk\""
+        );
+    }
+
+    #[test]
+    fn relative_times_use_the_largest_whole_unit() {
+        let now = Utc.ymd(2024, 5, 1).and_hms(10, 0, 0);
+
+        assert_eq!(relative_time(now, now), "just now");
+        assert_eq!(
+            relative_time(Utc.ymd(2024, 5, 1).and_hms(9, 59, 0), now),
+            "1 minute ago"
+        );
+        assert_eq!(
+            relative_time(Utc.ymd(2024, 4, 28).and_hms(10, 0, 0), now),
+            "3 days ago"
+        );
+    }
+
+    #[test]
+    fn bash_completions_mention_subcommands() {
+        let mut completions = Vec::new();
+        write_completions(Shell::Bash, &mut completions).unwrap();
+        let completions = String::from_utf8(completions).unwrap();
+
+        assert!(!completions.is_empty());
+        assert!(completions.contains("add"));
+    }
+
+    #[test]
+    fn config_sets_default_task_list() {
+        let dir = temp_dir("config");
+        let config_path = dir.join("config.toml");
+        fs::write(&config_path, "default_list = \"Inbox\"\n").unwrap();
+
+        assert_eq!(
+            read_config(&config_path).unwrap(),
+            Config {
+                default_list: "Inbox".to_string(),
+                ..Config::default()
+            }
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn config_sets_status_glyphs() {
+        let dir = temp_dir("config-glyphs");
+        let config_path = dir.join("config.toml");
+        fs::write(&config_path, "[glyphs]\ncomplete = \"✅\"\n").unwrap();
+
+        assert_eq!(
+            read_config(&config_path).unwrap().glyphs,
+            Glyphs {
+                complete: Some("✅".to_string()),
+                ..Glyphs::default()
+            }
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn config_with_empty_glyph_is_refused() {
+        let dir = temp_dir("config-empty-glyph");
+        let config_path = dir.join("config.toml");
+        fs::write(&config_path, "[glyphs]\nin-progress = \"\"\n").unwrap();
+
+        assert_eq!(
+            read_config(&config_path).unwrap_err().to_string(),
+            "the glyph for ‘in-progress’ tasks is empty"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_config_gives_default_config() {
+        let dir = temp_dir("missing-config");
+
+        assert_eq!(
+            read_config(dir.join("config.toml")).unwrap(),
+            Config::default()
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_history_is_empty() {
+        let dir = temp_dir("missing-history");
+
+        assert!(read_history(dir.join("history.json")).unwrap().is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn db_path_flag_takes_precedence_over_env_var() {
+        assert_eq!(
+            resolve_db_path(
+                Some(PathBuf::from("flag.json")),
+                Some(OsString::from("env.json"))
+            )
+            .unwrap(),
+            PathBuf::from("flag.json")
+        );
+    }
+
+    #[test]
+    fn db_path_env_var_is_used_without_flag() {
+        assert_eq!(
+            resolve_db_path(None, Some(OsString::from("env.json"))).unwrap(),
+            PathBuf::from("env.json")
+        );
+    }
+
+    #[test]
+    fn default_db_path_is_used_without_flag_or_env_var() {
+        assert_eq!(resolve_db_path(None, None).unwrap(), get_db_path().unwrap());
+        assert_eq!(
+            resolve_db_path(None, Some(OsString::new())).unwrap(),
+            get_db_path().unwrap()
+        );
+    }
+
+    #[test]
+    fn unversioned_db_is_migrated_when_read() {
+        let dir = temp_dir("migrate");
+        let db_path = dir.join("db.json");
+
+        fs::write(
+            &db_path,
+            r#"{"task_lists":{"Tasks":{"tasks":{"0":{"title":"Buy some milk","status":"Incomplete"}}}},"current_list":"Tasks"}"#,
+        )
+        .unwrap();
+
+        let mut storage = JsonFileStorage::new(db_path.clone());
+        storage.save(&storage.load().unwrap()).unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&db_path).unwrap()).unwrap();
+        let task = &json["task_lists"]["Tasks"]["tasks"]["0"];
+        assert_eq!(json["version"], t::schema::CURRENT_VERSION);
+        assert_eq!(task["title"], "Buy some milk");
+        assert_eq!(task["reminders"], serde_json::json!([]));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn db_from_newer_version_of_t_is_rejected() {
+        let dir = temp_dir("future");
+        let db_path = dir.join("db.json");
+
+        fs::write(
+            &db_path,
+            r#"{"version":99,"task_lists":{"Tasks":{"tasks":{}}},"current_list":"Tasks"}"#,
+        )
+        .unwrap();
+
+        let error = JsonFileStorage::new(db_path).load().unwrap_err();
+        assert!(matches!(
+            error,
+            storage::Error::Schema(t::schema::Error::UnsupportedVersion { found: 99, .. })
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn companion_path_is_named_after_db() {
+        assert_eq!(
+            companion_path(Path::new("/data/t/work.json"), "history.json"),
+            PathBuf::from("/data/t/work.history.json")
+        );
+    }
+
+    #[test]
+    fn suffixed_path_is_next_to_original() {
+        assert_eq!(
+            path_with_suffix(Path::new("/data/t/db.json"), ".tmp"),
+            PathBuf::from("/data/t/db.json.tmp")
+        );
+    }
+
+    #[test]
+    fn locked_db_cannot_be_locked_again() {
+        let dir = temp_dir("lock");
+        let db_path = dir.join("db.json");
+
+        let lock = lock_db(&db_path, Duration::from_millis(0)).unwrap();
+
+        let start = Instant::now();
+        assert!(lock_db(&db_path, Duration::from_millis(100)).is_err());
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        drop(lock);
+        assert!(lock_db(&db_path, Duration::from_millis(0)).is_ok());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
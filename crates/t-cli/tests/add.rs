@@ -0,0 +1,132 @@
+mod common;
+
+use common::t;
+use common::t_fails;
+use common::temp_data_dir;
+use std::fs;
+
+#[test]
+fn added_task_can_be_fully_specified_and_its_id_is_printed() {
+    let data_dir = temp_data_dir("add");
+
+    assert_eq!(t(&data_dir, &["add", "Buy some milk"]), "0\n");
+    assert_eq!(
+        t(
+            &data_dir,
+            &[
+                "add",
+                "Fix the fence",
+                "--tag",
+                "home",
+                "--tag",
+                "weekend",
+                "--due",
+                "2024-05-01",
+                "--priority",
+                "high",
+            ],
+        ),
+        "1\n"
+    );
+
+    let json: serde_json::Value =
+        serde_json::from_str(&t(&data_dir, &["--format", "json"])).unwrap();
+    let task = &json["task_lists"]["Tasks"]["tasks"]["1"];
+
+    assert_eq!(task["title"], "Fix the fence");
+    assert_eq!(task["tags"], serde_json::json!(["home", "weekend"]));
+    assert_eq!(task["due"], "2024-05-01");
+    assert_eq!(task["priority"], "High");
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
+
+#[test]
+fn added_task_can_be_given_an_id_that_is_free() {
+    let data_dir = temp_data_dir("add_id");
+
+    assert_eq!(t(&data_dir, &["add", "Buy some milk", "--id", "7"]), "7\n");
+    assert_eq!(t(&data_dir, &["add", "Fix the fence"]), "0\n");
+
+    t_fails(&data_dir, &["add", "Call mum", "--id", "7"]);
+
+    let output = t(&data_dir, &[]);
+    assert!(output.contains("[  7] ! • Buy some milk"), "{}", output);
+    assert!(!output.contains("Call mum"), "{}", output);
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
+
+#[test]
+fn unique_task_is_only_added_once() {
+    let data_dir = temp_data_dir("add_unique");
+
+    assert_eq!(t(&data_dir, &["add", "Buy some milk", "--unique"]), "0\n");
+    assert_eq!(t(&data_dir, &["add", "Fix the fence", "--unique"]), "1\n");
+    assert_eq!(t(&data_dir, &["add", "Buy some milk", "--unique"]), "0\n");
+    assert_eq!(t(&data_dir, &["add", "buy some milk", "--unique"]), "2\n");
+    assert_eq!(t(&data_dir, &["add", "Buy some milk"]), "3\n");
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
+
+#[test]
+fn task_can_be_added_as_json() {
+    let data_dir = temp_data_dir("add_json");
+
+    assert_eq!(
+        t(
+            &data_dir,
+            &[
+                "add-json",
+                r#"{"title":"Fix the fence","tags":["home"],"due":"2024-05-01"}"#,
+            ],
+        ),
+        "0\n"
+    );
+
+    let json: serde_json::Value =
+        serde_json::from_str(&t(&data_dir, &["--format", "json"])).unwrap();
+    let task = &json["task_lists"]["Tasks"]["tasks"]["0"];
+
+    assert_eq!(task["title"], "Fix the fence");
+    assert_eq!(task["tags"], serde_json::json!(["home"]));
+    assert_eq!(task["due"], "2024-05-01");
+
+    t_fails(
+        &data_dir,
+        &["add-json", r#"{"title":"Call mum","colour":"red"}"#],
+    );
+    t_fails(
+        &data_dir,
+        &["add-json", r#"{"title":"Call mum","depends_on":[7]}"#],
+    );
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
+
+#[test]
+fn new_tasks_start_with_the_task_list_defaults_unless_overridden() {
+    let data_dir = temp_data_dir("add_defaults");
+
+    t(
+        &data_dir,
+        &["set-list-defaults", "--priority", "high", "--tag", "urgent"],
+    );
+    t(&data_dir, &["add", "Fix the fence"]);
+    t(&data_dir, &["add", "Call mum", "--priority", "medium"]);
+    t(&data_dir, &["add-json", r#"{"title":"Buy some milk"}"#]);
+
+    let json: serde_json::Value =
+        serde_json::from_str(&t(&data_dir, &["--format", "json"])).unwrap();
+    let tasks = &json["task_lists"]["Tasks"]["tasks"];
+
+    assert_eq!(tasks["0"]["priority"], "High");
+    assert_eq!(tasks["0"]["tags"], serde_json::json!(["urgent"]));
+    assert_eq!(tasks["1"]["priority"], "Medium");
+    assert_eq!(tasks["1"]["tags"], serde_json::json!(["urgent"]));
+    assert_eq!(tasks["2"]["priority"], "Medium");
+    assert_eq!(tasks["2"]["tags"], serde_json::json!([]));
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
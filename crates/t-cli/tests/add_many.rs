@@ -0,0 +1,25 @@
+mod common;
+
+use common::t;
+use common::temp_data_dir;
+use std::fs;
+
+#[test]
+fn add_many_adds_a_task_for_each_line() {
+    let data_dir = temp_data_dir("add_many");
+
+    let file = data_dir.join("tasks.txt");
+    fs::write(&file, "Buy some milk\n# Weekend\n\n  Fix the fence\n").unwrap();
+
+    assert_eq!(
+        t(&data_dir, &["add-many", "--file", file.to_str().unwrap()]),
+        "0\n1\n"
+    );
+
+    let output = t(&data_dir, &[]);
+    assert!(output.contains("[  0] ! • Buy some milk"), "{}", output);
+    assert!(output.contains("[  1] ! • Fix the fence"), "{}", output);
+    assert!(!output.contains("Weekend"), "{}", output);
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
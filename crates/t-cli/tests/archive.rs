@@ -0,0 +1,80 @@
+mod common;
+
+use common::t;
+use common::temp_data_dir;
+use std::fs;
+
+#[test]
+fn archived_tasks_leave_the_task_list_for_the_archive() {
+    let data_dir = temp_data_dir("archive");
+
+    t(&data_dir, &["add", "Buy some milk"]);
+    t(&data_dir, &["add", "Fix the fence"]);
+    t(&data_dir, &["add", "Water plants"]);
+    t(&data_dir, &["complete", "0", "2"]);
+    t(&data_dir, &["archive"]);
+
+    let json: serde_json::Value =
+        serde_json::from_str(&t(&data_dir, &["--format", "json"])).unwrap();
+    let tasks = json["task_lists"]["Tasks"]["tasks"].as_object().unwrap();
+    assert_eq!(tasks.keys().collect::<Vec<_>>(), ["1"]);
+
+    let archive: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(data_dir.join("db.archive.json")).unwrap())
+            .unwrap();
+    let titles: Vec<_> = archive
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|archived| archived["title"].as_str().unwrap())
+        .collect();
+    assert_eq!(titles, ["Buy some milk", "Water plants"]);
+
+    // Archiving again adds to the archive rather than replacing it.
+    t(&data_dir, &["complete", "1"]);
+    t(&data_dir, &["archive"]);
+
+    let archived = t(&data_dir, &["show-archive"]);
+    assert_eq!(archived.lines().count(), 3);
+    assert!(archived
+        .lines()
+        .last()
+        .unwrap()
+        .starts_with("Tasks: Fix the fence"));
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
+
+#[test]
+fn undoing_an_archive_takes_its_tasks_back_out_of_the_archive() {
+    let data_dir = temp_data_dir("archive_undo");
+
+    t(&data_dir, &["add", "Buy some milk"]);
+    t(&data_dir, &["add", "Fix the fence"]);
+    t(&data_dir, &["complete", "0"]);
+    t(&data_dir, &["archive"]);
+    t(&data_dir, &["undo"]);
+
+    assert_eq!(t(&data_dir, &["show-archive"]), "");
+
+    t(&data_dir, &["archive"]);
+
+    let archived = t(&data_dir, &["show-archive"]);
+    assert_eq!(archived.lines().count(), 1);
+    assert!(archived.starts_with("Tasks: Buy some milk"));
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
+
+#[test]
+fn archiving_in_a_dry_run_leaves_the_archive_alone() {
+    let data_dir = temp_data_dir("archive_dry_run");
+
+    t(&data_dir, &["add", "Buy some milk"]);
+    t(&data_dir, &["complete", "0"]);
+    t(&data_dir, &["--dry-run", "archive"]);
+
+    assert!(!data_dir.join("db.archive.json").exists());
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
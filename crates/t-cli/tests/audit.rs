@@ -0,0 +1,58 @@
+mod common;
+
+use common::t;
+use common::temp_data_dir;
+use std::fs;
+use std::io::Write;
+
+#[test]
+fn each_change_appends_a_line_to_the_audit_log() {
+    let data_dir = temp_data_dir("audit");
+
+    t(&data_dir, &["add", "Buy some milk"]);
+    t(&data_dir, &["complete", "0"]);
+    t(&data_dir, &["show", "0"]);
+
+    let log = fs::read_to_string(data_dir.join("db.audit.log")).unwrap();
+    let actions: Vec<_> = log
+        .lines()
+        .map(|line| line.split_once(' ').unwrap().1)
+        .collect();
+
+    assert_eq!(
+        actions,
+        [
+            "add title=\"Buy some milk\" list=Tasks",
+            "complete ids=0 list=Tasks",
+        ]
+    );
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
+
+#[test]
+fn history_prints_the_most_recent_changes() {
+    let data_dir = temp_data_dir("audit_history");
+
+    assert_eq!(
+        t(&data_dir, &["history"]),
+        "No changes have been recorded yet\n"
+    );
+
+    t(&data_dir, &["add", "Buy some milk"]);
+    t(&data_dir, &["complete", "0"]);
+
+    fs::OpenOptions::new()
+        .append(true)
+        .open(data_dir.join("db.audit.log"))
+        .unwrap()
+        .write_all(b"not an audit log line\n")
+        .unwrap();
+
+    assert_eq!(
+        t(&data_dir, &["history", "--limit", "2"]),
+        "just now: complete ids=0 list=Tasks\nnot an audit log line\n"
+    );
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use common::t;
+use common::t_fails;
+use common::temp_data_dir;
+use std::fs;
+
+#[test]
+fn blocked_task_can_only_be_completed_with_force() {
+    let data_dir = temp_data_dir("block");
+
+    t(&data_dir, &["add", "Buy paint"]);
+    t(&data_dir, &["add", "Paint the fence"]);
+    t(&data_dir, &["block", "1", "0"]);
+
+    let output = t(&data_dir, &[]);
+    assert!(output.contains("Paint the fence (blocked)"), "{}", output);
+
+    t_fails(&data_dir, &["complete", "1"]);
+    assert!(!t(&data_dir, &[]).contains("– Paint the fence"));
+
+    t(&data_dir, &["complete", "1", "--force"]);
+    assert!(t(&data_dir, &[]).contains("– Paint the fence"));
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
+
+#[test]
+fn removed_task_no_longer_blocks_anything() {
+    let data_dir = temp_data_dir("block_removed");
+
+    t(&data_dir, &["add", "Buy paint"]);
+    t(&data_dir, &["add", "Paint the fence"]);
+    t(&data_dir, &["block", "1", "0"]);
+    t(&data_dir, &["--yes", "remove", "0"]);
+    t(&data_dir, &["add", "Unrelated new task"]);
+
+    assert!(!t(&data_dir, &[]).contains("(blocked)"));
+    t(&data_dir, &["complete", "1"]);
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
@@ -0,0 +1,41 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Runs `t` with a database in a throwaway directory, so that tests never touch the real database.
+pub fn t(data_dir: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_t"))
+        .args(args)
+        .env("T_DB_PATH", data_dir.join("db.json"))
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "t {:?} failed", args);
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Runs `t` like [`t`], but expects it to fail and returns what it printed.
+// Not every test binary that includes this module runs a failing command.
+#[allow(dead_code)]
+pub fn t_fails(data_dir: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_t"))
+        .args(args)
+        .env("T_DB_PATH", data_dir.join("db.json"))
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success(), "t {:?} succeeded", args);
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+pub fn temp_data_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("t-test-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    dir
+}
@@ -0,0 +1,21 @@
+mod common;
+
+use common::t;
+use common::temp_data_dir;
+use std::fs;
+
+#[test]
+fn count_prints_only_the_number_of_matching_tasks() {
+    let data_dir = temp_data_dir("count");
+
+    t(&data_dir, &["add", "Buy some milk"]);
+    t(&data_dir, &["add", "Fix the fence"]);
+    t(&data_dir, &["add", "Water plants"]);
+    t(&data_dir, &["complete", "1"]);
+
+    assert_eq!(t(&data_dir, &["count"]), "3\n");
+    assert_eq!(t(&data_dir, &["count", "complete"]), "1\n");
+    assert_eq!(t(&data_dir, &["count", "incomplete"]), "2\n");
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
@@ -0,0 +1,26 @@
+mod common;
+
+use common::t;
+use common::temp_data_dir;
+use std::fs;
+
+#[test]
+fn relative_dates_are_shown_by_every_listing() {
+    let data_dir = temp_data_dir("dates_relative");
+
+    t(&data_dir, &["add", "Buy some milk", "--due", "today"]);
+    t(&data_dir, &["add", "Fix the fence", "--due", "tomorrow"]);
+
+    assert_eq!(
+        t(&data_dir, &["--dates", "relative", "today"]),
+        "Tasks\n  [0] • Buy some milk (due today)\n"
+    );
+    assert_eq!(
+        t(&data_dir, &["--dates", "relative", "next"]),
+        "Tasks: [0] • Buy some milk (due today)\n"
+    );
+    assert!(t(&data_dir, &["--dates", "relative", "show", "1"]).contains("Due:      tomorrow\n"));
+    assert!(!t(&data_dir, &["show", "1"]).contains("tomorrow"));
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
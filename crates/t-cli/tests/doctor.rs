@@ -0,0 +1,60 @@
+mod common;
+
+use common::t;
+use common::t_fails;
+use common::temp_data_dir;
+use std::fs;
+
+#[test]
+fn doctor_reports_problems_and_fixes_what_it_can() {
+    let data_dir = temp_data_dir("doctor");
+
+    t(&data_dir, &["add", "Buy some milk"]);
+
+    let db_path = data_dir.join("db.json");
+    let db = fs::read_to_string(&db_path)
+        .unwrap()
+        .replace(r#""tags": []"#, r#""tags": ["dairy", "dairy"]"#);
+    fs::write(&db_path, db).unwrap();
+
+    assert_eq!(
+        t_fails(&data_dir, &["doctor"]),
+        "Problem: task 0 in ‘Tasks’ has a tag more than once\n"
+    );
+    assert_eq!(
+        t(&data_dir, &["doctor", "--fix"]),
+        "Fixed: task 0 in ‘Tasks’ has a tag more than once\n"
+    );
+    assert_eq!(t(&data_dir, &["doctor"]), "No problems found\n");
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
+
+#[test]
+fn doctor_reports_a_dangling_current_list_without_changing_the_database() {
+    let data_dir = temp_data_dir("doctor_dangling");
+
+    t(&data_dir, &["add", "Buy some milk"]);
+
+    let db_path = data_dir.join("db.json");
+    let db = fs::read_to_string(&db_path)
+        .unwrap()
+        .replace(r#""current_list": "Tasks""#, r#""current_list": "Gone""#);
+    fs::write(&db_path, &db).unwrap();
+
+    assert_eq!(
+        t_fails(&data_dir, &["doctor"]),
+        "Problem: the current task list ‘Gone’ does not exist\n"
+    );
+    t(&data_dir, &["--dry-run", "add", "Call mum"]);
+    t(&data_dir, &["count"]);
+    assert_eq!(fs::read_to_string(&db_path).unwrap(), db);
+
+    assert_eq!(
+        t(&data_dir, &["doctor", "--fix"]),
+        "Fixed: the current task list ‘Gone’ does not exist\n"
+    );
+    assert_eq!(t(&data_dir, &["doctor"]), "No problems found\n");
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::t;
+use common::temp_data_dir;
+use std::fs;
+
+#[test]
+fn dry_run_leaves_the_database_untouched() {
+    let data_dir = temp_data_dir("dry-run");
+
+    t(&data_dir, &["add", "Buy some milk"]);
+    t(&data_dir, &["complete", "0"]);
+
+    let db_before = fs::read_to_string(data_dir.join("db.json")).unwrap();
+
+    let output = t(&data_dir, &["--dry-run", "remove-completed"]);
+
+    assert!(output.contains("- Tasks: [0] Buy some milk"));
+    assert_eq!(
+        fs::read_to_string(data_dir.join("db.json")).unwrap(),
+        db_before
+    );
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
+
+#[test]
+fn purging_in_a_dry_run_shows_the_changes_without_asking() {
+    let data_dir = temp_data_dir("dry-run-purge");
+
+    t(&data_dir, &["add", "Buy some milk"]);
+
+    let db_before = fs::read_to_string(data_dir.join("db.json")).unwrap();
+
+    let output = t(&data_dir, &["--dry-run", "purge"]);
+
+    assert!(output.contains("- Tasks: [0] Buy some milk"), "{}", output);
+    assert_eq!(
+        fs::read_to_string(data_dir.join("db.json")).unwrap(),
+        db_before
+    );
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
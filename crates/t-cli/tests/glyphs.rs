@@ -0,0 +1,29 @@
+mod common;
+
+use common::t;
+use common::temp_data_dir;
+use std::fs;
+
+#[test]
+fn custom_glyphs_are_used_by_every_listing() {
+    let data_dir = temp_data_dir("glyphs");
+    fs::write(
+        data_dir.join("config.toml"),
+        "[glyphs]\nincomplete = \"☐\"\n",
+    )
+    .unwrap();
+
+    t(&data_dir, &["add", "Buy some milk", "--due", "today"]);
+
+    assert_eq!(
+        t(&data_dir, &["--dates", "relative", "today"]),
+        "Tasks\n  [0] ☐ Buy some milk (due today)\n"
+    );
+    assert_eq!(
+        t(&data_dir, &["--dates", "relative", "next"]),
+        "Tasks: [0] ☐ Buy some milk (due today)\n"
+    );
+    assert!(t(&data_dir, &["show", "0"]).contains("Status:   ☐ incomplete\n"));
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
@@ -0,0 +1,49 @@
+mod common;
+
+use common::t;
+use common::temp_data_dir;
+use std::fs;
+
+#[test]
+fn database_can_be_printed_as_json() {
+    let data_dir = temp_data_dir("json");
+
+    t(&data_dir, &["add", "Buy some milk"]);
+    t(&data_dir, &["add-task-list", "Work"]);
+    t(&data_dir, &["add", "Write report"]);
+
+    let json: serde_json::Value =
+        serde_json::from_str(&t(&data_dir, &["--format", "json"])).unwrap();
+
+    assert_eq!(
+        json["task_lists"]["Tasks"]["tasks"]["0"]["title"],
+        "Buy some milk"
+    );
+    assert_eq!(
+        json["task_lists"]["Work"]["tasks"]["0"]["title"],
+        "Write report"
+    );
+    assert_eq!(json["current_list"], "Work");
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
+
+#[test]
+fn database_can_be_printed_after_subcommand() {
+    let data_dir = temp_data_dir("print");
+
+    assert_eq!(t(&data_dir, &["add", "Buy some milk"]), "0\n");
+
+    let json: serde_json::Value = serde_json::from_str(&t(
+        &data_dir,
+        &["--format", "json", "--print", "complete", "0"],
+    ))
+    .unwrap();
+
+    assert_eq!(
+        json["task_lists"]["Tasks"]["tasks"]["0"]["status"],
+        "Complete"
+    );
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
@@ -0,0 +1,23 @@
+mod common;
+
+use common::t;
+use common::temp_data_dir;
+use std::fs;
+
+#[test]
+fn plain_flag_shows_statuses_as_checkboxes() {
+    let data_dir = temp_data_dir("plain");
+
+    t(&data_dir, &["add", "Buy some milk"]);
+    t(&data_dir, &["add", "Fix the fence"]);
+    t(&data_dir, &["complete", "1"]);
+
+    let output = t(&data_dir, &["--plain"]);
+    assert!(output.contains("[  0] ! [ ] Buy some milk"), "{}", output);
+    assert!(output.contains("[  1] ! [x] Fix the fence"), "{}", output);
+
+    let output = t(&data_dir, &[]);
+    assert!(output.contains("[  0] ! • Buy some milk"), "{}", output);
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
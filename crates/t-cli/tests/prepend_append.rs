@@ -0,0 +1,24 @@
+mod common;
+
+use common::t;
+use common::temp_data_dir;
+use std::fs;
+
+#[test]
+fn text_can_be_prepended_and_appended_to_titles() {
+    let data_dir = temp_data_dir("prepend-append");
+
+    t(&data_dir, &["add", "Buy some milk"]);
+    t(&data_dir, &["prepend", "0", "(urgent)"]);
+    t(&data_dir, &["append", "0", "before noon"]);
+
+    let json: serde_json::Value =
+        serde_json::from_str(&t(&data_dir, &["--format", "json"])).unwrap();
+
+    assert_eq!(
+        json["task_lists"]["Tasks"]["tasks"]["0"]["title"],
+        "(urgent) Buy some milk before noon"
+    );
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use common::t;
+use common::temp_data_dir;
+use std::fs;
+
+#[test]
+fn db_is_replaced_by_backup() {
+    let data_dir = temp_data_dir("restore");
+
+    t(&data_dir, &["add", "Buy some milk"]);
+    fs::write(
+        data_dir.join("backup.json"),
+        t(&data_dir, &["--format", "json"]),
+    )
+    .unwrap();
+    t(&data_dir, &["add", "Fix the fence"]);
+
+    let backup = data_dir.join("backup.json");
+    t(&data_dir, &["--yes", "restore", backup.to_str().unwrap()]);
+
+    let output = t(&data_dir, &[]);
+    assert!(output.contains("Buy some milk"), "{}", output);
+    assert!(!output.contains("Fix the fence"), "{}", output);
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
+
+#[test]
+fn restoring_can_be_undone() {
+    let data_dir = temp_data_dir("restore_undo");
+
+    fs::write(
+        data_dir.join("backup.json"),
+        t(&data_dir, &["--format", "json"]),
+    )
+    .unwrap();
+    t(&data_dir, &["add", "Buy some milk"]);
+    let db_before = fs::read_to_string(data_dir.join("db.json")).unwrap();
+
+    let backup = data_dir.join("backup.json");
+    t(&data_dir, &["--yes", "restore", backup.to_str().unwrap()]);
+    assert!(!t(&data_dir, &[]).contains("Buy some milk"));
+
+    t(&data_dir, &["undo"]);
+
+    assert_eq!(
+        fs::read_to_string(data_dir.join("db.json")).unwrap(),
+        db_before
+    );
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
+
+#[test]
+fn restoring_in_a_dry_run_shows_the_changes_without_asking() {
+    let data_dir = temp_data_dir("restore_dry_run");
+
+    fs::write(
+        data_dir.join("backup.json"),
+        t(&data_dir, &["--format", "json"]),
+    )
+    .unwrap();
+    t(&data_dir, &["add", "Buy some milk"]);
+    let db_before = fs::read_to_string(data_dir.join("db.json")).unwrap();
+
+    let backup = data_dir.join("backup.json");
+    let output = t(
+        &data_dir,
+        &["--dry-run", "restore", backup.to_str().unwrap()],
+    );
+
+    assert!(!output.contains("Continue?"), "{}", output);
+    assert!(output.contains("Buy some milk"), "{}", output);
+    assert_eq!(
+        fs::read_to_string(data_dir.join("db.json")).unwrap(),
+        db_before
+    );
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use common::t;
+use common::temp_data_dir;
+use std::fs;
+
+#[test]
+fn show_prints_task_detail_without_saving() {
+    let data_dir = temp_data_dir("show");
+
+    t(
+        &data_dir,
+        &[
+            "add",
+            "Fix the fence",
+            "--tag",
+            "home",
+            "--due",
+            "2024-05-01",
+        ],
+    );
+
+    let db_before = fs::read_to_string(data_dir.join("db.json")).unwrap();
+    let modified_before = fs::metadata(data_dir.join("db.json"))
+        .unwrap()
+        .modified()
+        .unwrap();
+
+    assert_eq!(
+        t(&data_dir, &["show", "0"]),
+        "\
+Title:    Fix the fence
+Status:   • incomplete
+Due:      2024-05-01
+Tags:     #home
+Priority: medium
+Notes:    none
+"
+    );
+
+    assert_eq!(
+        fs::read_to_string(data_dir.join("db.json")).unwrap(),
+        db_before
+    );
+    assert_eq!(
+        fs::metadata(data_dir.join("db.json"))
+            .unwrap()
+            .modified()
+            .unwrap(),
+        modified_before
+    );
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use common::t;
+use common::temp_data_dir;
+use std::fs;
+
+#[test]
+fn subtasks_are_shown_beneath_their_task_with_progress() {
+    let data_dir = temp_data_dir("subtasks");
+
+    t(&data_dir, &["add", "Fix the fence"]);
+    assert_eq!(t(&data_dir, &["add-sub", "0", "Buy nails"]), "0\n");
+    assert_eq!(t(&data_dir, &["add-sub", "0", "Paint"]), "1\n");
+    t(&data_dir, &["complete-sub", "0", "0"]);
+
+    let output = t(&data_dir, &[]);
+    assert!(
+        output.contains(
+            "[  0] ! • Fix the fence [1/2]\n            – Buy nails\n            • Paint"
+        ),
+        "{}",
+        output
+    );
+
+    t(&data_dir, &["complete-sub", "0", "1", "--complete-parent"]);
+
+    let output = t(&data_dir, &[]);
+    assert!(
+        output.contains("[  0] ! – Fix the fence [2/2]"),
+        "{}",
+        output
+    );
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use common::t;
+use common::temp_data_dir;
+use std::fs;
+
+#[test]
+fn adding_task_can_be_undone() {
+    let data_dir = temp_data_dir("undo");
+
+    t(&data_dir, &["add", "Buy some milk"]);
+    t(&data_dir, &["undo"]);
+
+    let json: serde_json::Value =
+        serde_json::from_str(&t(&data_dir, &["--format", "json"])).unwrap();
+
+    assert_eq!(json["task_lists"]["Tasks"]["tasks"], serde_json::json!({}));
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
+
+#[test]
+fn changes_are_undone_most_recent_first() {
+    let data_dir = temp_data_dir("undo-order");
+
+    t(&data_dir, &["add", "Buy some milk"]);
+    t(&data_dir, &["complete", "0"]);
+    t(&data_dir, &["undo"]);
+
+    let json: serde_json::Value =
+        serde_json::from_str(&t(&data_dir, &["--format", "json"])).unwrap();
+
+    assert_eq!(
+        json["task_lists"]["Tasks"]["tasks"]["0"]["status"],
+        "Incomplete"
+    );
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
+
+#[test]
+fn undoing_without_history_says_so() {
+    let data_dir = temp_data_dir("undo-empty");
+
+    assert_eq!(t(&data_dir, &["undo"]), "Nothing to undo\n");
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
+
+#[test]
+fn databases_in_the_same_directory_have_separate_histories() {
+    let data_dir = temp_data_dir("undo-separate");
+    let home = data_dir.join("home.json");
+    let home = home.to_str().unwrap();
+
+    t(&data_dir, &["add", "Write report"]);
+    t(&data_dir, &["add", "Book meeting room"]);
+    t(&data_dir, &["--db", home, "add-task-list", "Garden"]);
+    t(&data_dir, &["undo"]);
+
+    let json: serde_json::Value =
+        serde_json::from_str(&t(&data_dir, &["--format", "json"])).unwrap();
+    let tasks = json["task_lists"]["Tasks"]["tasks"].as_object().unwrap();
+
+    assert_eq!(tasks.len(), 1);
+    assert!(data_dir.join("home.history.json").exists());
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
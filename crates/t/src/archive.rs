@@ -0,0 +1,60 @@
+use crate::task;
+use chrono::DateTime;
+use chrono::Utc;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt;
+
+/// A completed task that was moved out of the database to be kept for later review.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedTask {
+    /// The name of the task list the task was in when it was archived.
+    pub list: String,
+    pub title: String,
+    /// When the task was completed, which is unknown for tasks completed before completion times
+    /// were recorded.
+    pub completed: Option<DateTime<Utc>>,
+}
+
+impl fmt::Display for ArchivedTask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.list, self.title)?;
+
+        if let Some(completed) = self.completed {
+            write!(f, " (completed {})", task::format_time(completed))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn archived_tasks_are_displayed_with_list_and_completion_time() {
+        let archived = ArchivedTask {
+            list: "Home".to_string(),
+            title: "Fix the fence".to_string(),
+            completed: Some(Utc.ymd(2024, 5, 1).and_hms(17, 0, 0)),
+        };
+
+        assert_eq!(
+            archived.to_string(),
+            "Home: Fix the fence (completed 2024-05-01 17:00 UTC)"
+        );
+    }
+
+    #[test]
+    fn archived_tasks_without_completion_time_leave_it_out() {
+        let archived = ArchivedTask {
+            list: "Home".to_string(),
+            title: "Fix the fence".to_string(),
+            completed: None,
+        };
+
+        assert_eq!(archived.to_string(), "Home: Fix the fence");
+    }
+}
@@ -0,0 +1,152 @@
+use chrono::Datelike;
+use chrono::Duration;
+use chrono::NaiveDate;
+use chrono::Weekday;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum Error {
+    #[error(
+        "cannot understand the date ‘{0}’ (expected a date such as 2024-05-01, ‘today’, \
+         ‘tomorrow’, an offset such as ‘+3d’ or ‘+2w’, or a weekday such as ‘monday’)"
+    )]
+    UnknownDate(String),
+    #[error("the date ‘{0}’ is too far in the future")]
+    OutOfRange(String),
+}
+
+/// Reads a due date, which can be written relative to `today` as well as in full:
+///
+/// - `today` and `tomorrow`
+/// - an offset in days or weeks, such as `+3d` or `+2w`
+/// - a weekday, such as `monday` or `mon`, meaning the next one after today
+/// - a full date, such as `2024-05-01`
+///
+/// `today` should be the local date, so that relative dates match the user’s calendar.
+pub fn parse_due(input: &str, today: NaiveDate) -> Result<NaiveDate, Error> {
+    let unknown = || Error::UnknownDate(input.to_string());
+    let out_of_range = || Error::OutOfRange(input.to_string());
+
+    let lowercase = input.trim().to_lowercase();
+
+    match lowercase.as_str() {
+        "today" => return Ok(today),
+        "tomorrow" => return today.succ_opt().ok_or_else(out_of_range),
+        _ => {}
+    }
+
+    if let Some(offset) = lowercase.strip_prefix('+') {
+        let (count, days_per_unit) = if let Some(count) = offset.strip_suffix('d') {
+            (count, 1)
+        } else if let Some(count) = offset.strip_suffix('w') {
+            (count, 7)
+        } else {
+            return Err(unknown());
+        };
+
+        let count: u32 = count.parse().map_err(|_| unknown())?;
+        let days = i64::from(count) * days_per_unit;
+
+        return today
+            .checked_add_signed(Duration::days(days))
+            .ok_or_else(out_of_range);
+    }
+
+    if let Ok(weekday) = lowercase.parse::<Weekday>() {
+        let days_until =
+            (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+
+        // Today’s weekday means the same day next week, since a task due today would say so.
+        let days_until = if days_until == 0 { 7 } else { days_until };
+
+        return today
+            .checked_add_signed(Duration::days(days_until.into()))
+            .ok_or_else(out_of_range);
+    }
+
+    lowercase.parse().map_err(|_| unknown())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A Wednesday.
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd(2024, 5, 1)
+    }
+
+    #[test]
+    fn today_and_tomorrow_are_relative_to_today() {
+        assert_eq!(parse_due("today", today()), Ok(today()));
+        assert_eq!(
+            parse_due("Tomorrow", today()),
+            Ok(NaiveDate::from_ymd(2024, 5, 2))
+        );
+    }
+
+    #[test]
+    fn offsets_can_be_in_days_or_weeks() {
+        assert_eq!(
+            parse_due("+3d", today()),
+            Ok(NaiveDate::from_ymd(2024, 5, 4))
+        );
+        assert_eq!(
+            parse_due("+2w", today()),
+            Ok(NaiveDate::from_ymd(2024, 5, 15))
+        );
+        assert_eq!(parse_due("+0d", today()), Ok(today()));
+    }
+
+    #[test]
+    fn weekdays_mean_the_next_one_after_today() {
+        assert_eq!(
+            parse_due("friday", today()),
+            Ok(NaiveDate::from_ymd(2024, 5, 3))
+        );
+        assert_eq!(
+            parse_due("mon", today()),
+            Ok(NaiveDate::from_ymd(2024, 5, 6))
+        );
+        assert_eq!(
+            parse_due("Wednesday", today()),
+            Ok(NaiveDate::from_ymd(2024, 5, 8))
+        );
+    }
+
+    #[test]
+    fn full_dates_are_taken_as_they_are() {
+        assert_eq!(
+            parse_due("2024-12-25", today()),
+            Ok(NaiveDate::from_ymd(2024, 12, 25))
+        );
+    }
+
+    #[test]
+    fn unknown_dates_give_error() {
+        for input in &[
+            "someday",
+            "+3",
+            "+d",
+            "+3m",
+            "+-3d",
+            "+3é",
+            "+ü",
+            "2024-13-01",
+            "",
+        ] {
+            assert_eq!(
+                parse_due(input, today()),
+                Err(Error::UnknownDate(input.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn dates_too_far_in_the_future_give_error() {
+        assert_eq!(
+            parse_due("+4000000000w", today()),
+            Err(Error::OutOfRange("+4000000000w".to_string()))
+        );
+    }
+}
@@ -1,319 +1,3243 @@
+use crate::archive::ArchivedTask;
+use crate::render;
+use crate::render::Color;
+use crate::render::RenderOptions;
+use crate::schema;
+use crate::spreadsheet;
+use crate::spreadsheet::Row;
+use crate::stats::Counts;
+use crate::stats::Stats;
+use crate::status::Status;
+use crate::task::Task;
+use crate::task_list;
+use crate::task_list::Columns;
 use crate::task_list::TaskList;
+use chrono::Date;
+use chrono::NaiveDate;
+use chrono::TimeZone;
 use indexmap::IndexMap;
 use serde::Deserialize;
 use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::HashMap;
 use std::fmt;
+use std::io;
+use std::mem;
 use thiserror::Error;
 
 #[derive(Debug, Error, PartialEq)]
 pub enum Error {
     #[error("task list with name ‘{0}’ does not exist")]
     NonExistentTaskList(String),
-    #[error("cannot remove current task list ‘{0}’")]
-    CannotRemoveCurrentTaskList(String),
+    #[error("task lists with these names do not exist: {}", .0.join(", "))]
+    NonExistentTaskLists(Vec<String>),
+    #[error("no current task list has been set")]
+    NoCurrentTaskList,
+    #[error("task list with name ‘{0}’ already exists")]
+    ExistingTaskList(String),
+    #[error("‘{name}’ could refer to several task lists: {}", .candidates.join(", "))]
+    AmbiguousTaskList {
+        name: String,
+        candidates: Vec<String>,
+    },
+    #[error("there is no task list at position {index} (there are only {len})")]
+    TaskListIndexOutOfRange { index: usize, len: usize },
+    #[error("cannot merge task list ‘{0}’ into itself")]
+    MergeIntoItself(String),
+    #[error("cannot move a task to task list ‘{0}’, since it is already there")]
+    MoveIntoSameList(String),
+    #[error("the start of the date range ({start}) is after its end ({end})")]
+    InvalidDateRange { start: NaiveDate, end: NaiveDate },
+    #[error(transparent)]
+    TaskList(#[from] task_list::Error),
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// Something wrong with a database, usually because it was edited by hand.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationIssue {
+    NoTaskLists,
+    DanglingCurrentList(String),
+    BlankTitle { list: String, id: u32 },
+    UntidyReminders { list: String, id: u32 },
+    DuplicateTags { list: String, id: u32 },
+}
+
+impl ValidationIssue {
+    /// Whether [`Db::fix`] can repair the issue without losing anything.
+    pub fn is_fixable(&self) -> bool {
+        !matches!(self, Self::BlankTitle { .. })
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoTaskLists => write!(f, "there are no task lists"),
+            Self::DanglingCurrentList(name) => {
+                write!(f, "the current task list ‘{}’ does not exist", name)
+            }
+            Self::BlankTitle { list, id } => {
+                write!(f, "task {} in ‘{}’ has a blank title", id, list)
+            }
+            Self::UntidyReminders { list, id } => write!(
+                f,
+                "task {} in ‘{}’ has reminders out of order or repeated",
+                id, list
+            ),
+            Self::DuplicateTags { list, id } => {
+                write!(f, "task {} in ‘{}’ has a tag more than once", id, list)
+            }
+        }
+    }
+}
+
+/// The name of the task list a new database starts out with.
+const DEFAULT_LIST_NAME: &str = "Tasks";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Db {
     task_lists: IndexMap<String, TaskList>,
     current_list: String,
+    /// The task list that was current before the current one, for switching back to.
+    #[serde(default)]
+    previous_list: Option<String>,
+    /// The colors tags are drawn in, by tag name.
+    #[serde(default)]
+    tag_colors: HashMap<String, Color>,
 }
 
 impl Db {
+    /// Creates a database without any task lists.
+    ///
+    /// Unlike [`Db::default`], which starts out with a task list called “Tasks”, the database
+    /// returned here is completely empty. A task list has to be added and made current with
+    /// [`Db::set_current`] before it can be used; until then, operations on the current task list
+    /// return [`Error::NoCurrentTaskList`].
+    pub fn empty() -> Self {
+        Self {
+            task_lists: IndexMap::new(),
+            current_list: String::new(),
+            previous_list: None,
+            tag_colors: HashMap::new(),
+        }
+    }
+
+    /// Creates a database with a single empty task list called “Tasks” that is only kept in
+    /// memory. Nothing is read from or written to disk unless the database is handed to a
+    /// [`Storage`](crate::storage::Storage).
+    pub fn new_in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Creates a database with a single empty task list, which is current.
+    pub fn with_default_list(name: String) -> Self {
+        Self {
+            task_lists: {
+                let mut task_lists = IndexMap::new();
+                task_lists.insert(name.clone(), TaskList::default());
+                task_lists
+            },
+            current_list: name,
+            previous_list: None,
+            tag_colors: HashMap::new(),
+        }
+    }
+
     pub fn add_task_list(&mut self, name: String, task_list: TaskList) {
         self.task_lists.insert(name, task_list);
     }
 
-    pub fn remove_task_list(&mut self, name: String) -> Result<(), Error> {
-        if name == self.current_list {
-            return Err(Error::CannotRemoveCurrentTaskList(name));
+    /// Removes a task list.
+    ///
+    /// If the removed task list was the current one, the first remaining task list becomes
+    /// current. If no task lists remain, an empty task list called `default_list` is added and
+    /// made current.
+    pub fn remove_task_list(&mut self, name: String, default_list: &str) -> Result<(), Error> {
+        if self.task_lists.shift_remove(&name).is_none() {
+            return Err(Error::NonExistentTaskList(name));
         }
 
-        self.task_lists
-            .remove(&name)
-            .ok_or_else(|| Error::NonExistentTaskList(name))
-            .map(|_| ())
+        self.ensure_nonempty(default_list);
+
+        Ok(())
     }
 
+    /// Renames a task list, keeping its position among the other task lists.
     pub fn rename_task_list(&mut self, old_name: String, new_name: String) -> Result<(), Error> {
+        if !self.task_lists.contains_key(&old_name) {
+            return Err(Error::NonExistentTaskList(old_name));
+        }
+
+        if self.task_lists.contains_key(&new_name) {
+            return Err(Error::ExistingTaskList(new_name));
+        }
+
+        // IndexMap can’t change a key in place, so we rebuild the map with the new name in the old
+        // name’s slot.
+        self.task_lists = self
+            .task_lists
+            .drain(..)
+            .map(|(name, task_list)| {
+                if name == old_name {
+                    (new_name.clone(), task_list)
+                } else {
+                    (name, task_list)
+                }
+            })
+            .collect();
+
+        if self.previous_list.as_ref() == Some(&old_name) {
+            self.previous_list = Some(new_name.clone());
+        }
+
         if self.current_list == old_name {
-            self.current_list = new_name.clone();
+            self.current_list = new_name;
         }
 
-        let task_list = self.task_lists.remove(&old_name);
+        Ok(())
+    }
 
-        if let Some(task_list) = task_list {
-            self.task_lists.insert(new_name, task_list);
-            Ok(())
+    /// Renames a task list like [`Db::rename_task_list`], except that renaming it to the name of
+    /// an existing task list merges it into that task list with [`Db::merge_lists`].
+    pub fn rename_or_merge_task_list(
+        &mut self,
+        old_name: String,
+        new_name: String,
+    ) -> Result<(), Error> {
+        if self.task_lists.contains_key(&new_name) {
+            self.merge_lists(&old_name, &new_name)
         } else {
-            Err(Error::NonExistentTaskList(old_name))
+            self.rename_task_list(old_name, new_name)
+        }
+    }
+
+    /// Moves a task list to the given position among the other task lists.
+    ///
+    /// Indices past the last task list move it to the end.
+    pub fn move_list_to(&mut self, name: String, index: usize) -> Result<(), Error> {
+        let from = self
+            .task_lists
+            .get_index_of(&name)
+            .ok_or(Error::NonExistentTaskList(name))?;
+
+        let to = index.min(self.task_lists.len() - 1);
+        self.task_lists.move_index(from, to);
+
+        Ok(())
+    }
+
+    /// Moves a task from one task list to another, returning the ID it was given in the
+    /// destination. With `keep_id`, the task keeps its ID if no task in the destination has it;
+    /// otherwise, or without `keep_id`, it gets the lowest available ID. Moving a task to the task
+    /// list it is already in gives an error.
+    pub fn move_task(
+        &mut self,
+        id: u32,
+        from_list: &str,
+        to_list: &str,
+        keep_id: bool,
+    ) -> Result<u32, Error> {
+        if from_list == to_list {
+            return Err(Error::MoveIntoSameList(to_list.to_string()));
+        }
+
+        if !self.task_lists.contains_key(to_list) {
+            return Err(Error::NonExistentTaskList(to_list.to_string()));
+        }
+
+        let task = self
+            .task_lists
+            .get_mut(from_list)
+            .ok_or_else(|| Error::NonExistentTaskList(from_list.to_string()))?
+            .take_task(id)?;
+
+        let destination = &mut self.task_lists[to_list];
+
+        if keep_id && destination.get(id).is_none() {
+            destination.insert_task_with_id(id, task)?;
+            return Ok(id);
+        }
+
+        Ok(destination.add_task(task)?)
+    }
+
+    /// Moves every task from one task list into another, giving them new IDs in the destination,
+    /// and then removes the source task list. If the source was current, the destination becomes
+    /// current instead. Nothing changes if an error is returned.
+    pub fn merge_lists(&mut self, source: &str, dest: &str) -> Result<(), Error> {
+        if source == dest {
+            return Err(Error::MergeIntoItself(source.to_string()));
+        }
+
+        let source_list = self
+            .task_lists
+            .get(source)
+            .ok_or_else(|| Error::NonExistentTaskList(source.to_string()))?;
+
+        let mut merged = self
+            .task_lists
+            .get(dest)
+            .ok_or_else(|| Error::NonExistentTaskList(dest.to_string()))?
+            .clone();
+
+        merged.append(source_list)?;
+
+        self.task_lists[dest] = merged;
+        self.task_lists.shift_remove(source);
+
+        if self.current_list == source {
+            self.current_list = dest.to_string();
         }
+
+        Ok(())
     }
 
+    /// Sets the current task list, which can be named loosely as with [`Db::resolve_list_name`].
     pub fn set_current(&mut self, new_current_list: String) -> Result<(), Error> {
-        if self.task_lists.contains_key(&new_current_list) {
-            self.current_list = new_current_list;
-            Ok(())
+        let name = self.resolve_list_name(&new_current_list)?.to_string();
+        self.switch_to(name);
+
+        Ok(())
+    }
+
+    /// Sets the current task list to the one at a position among the task lists, counting from 0
+    /// in the order they are displayed.
+    pub fn set_current_by_index(&mut self, index: usize) -> Result<(), Error> {
+        let (name, _) = self
+            .task_lists
+            .get_index(index)
+            .ok_or(Error::TaskListIndexOutOfRange {
+                index,
+                len: self.task_lists.len(),
+            })?;
+
+        let name = name.clone();
+        self.switch_to(name);
+
+        Ok(())
+    }
+
+    /// Makes the task list that was current before the current one current again, like `cd -` in
+    /// a shell, so that repeating this switches back and forth. Returns whether there was such a
+    /// task list to switch to.
+    pub fn switch_to_previous(&mut self) -> bool {
+        match self.previous_list.take() {
+            Some(name) if self.task_lists.contains_key(&name) => {
+                self.switch_to(name);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Makes a task list current, remembering the one it replaces.
+    fn switch_to(&mut self, name: String) {
+        if name != self.current_list {
+            self.previous_list = Some(mem::replace(&mut self.current_list, name));
+        }
+    }
+
+    /// Finds the task list a name refers to, so that task lists don’t have to be named exactly.
+    ///
+    /// A task list with exactly that name is preferred. Failing that, the name is matched ignoring
+    /// case against the task list names containing it, and then against those that are only a
+    /// typo or two away from it. An error is returned if no task list is close enough, or if
+    /// several are equally close.
+    pub fn resolve_list_name(&self, name: &str) -> Result<&str, Error> {
+        if let Some((existing, _)) = self.task_lists.get_key_value(name) {
+            return Ok(existing);
+        }
+
+        let lowercase_name = name.to_lowercase();
+
+        let containing: Vec<_> = self
+            .task_lists
+            .keys()
+            .filter(|existing| existing.to_lowercase().contains(&lowercase_name))
+            .collect();
+
+        let candidates = if containing.is_empty() {
+            // Allow about one typo for every two characters, so short names aren’t matched by
+            // anything at all.
+            let max_distance = (name.chars().count() / 2).max(1);
+
+            let distances: Vec<_> = self
+                .task_lists
+                .keys()
+                .map(|existing| {
+                    (
+                        existing,
+                        levenshtein(&existing.to_lowercase(), &lowercase_name),
+                    )
+                })
+                .filter(|(_, distance)| *distance <= max_distance)
+                .collect();
+
+            let closest = distances.iter().map(|(_, distance)| *distance).min();
+
+            distances
+                .into_iter()
+                .filter(|(_, distance)| Some(*distance) == closest)
+                .map(|(existing, _)| existing)
+                .collect()
         } else {
-            Err(Error::NonExistentTaskList(new_current_list))
+            containing
+        };
+
+        match candidates.as_slice() {
+            [] => Err(Error::NonExistentTaskList(name.to_string())),
+            [existing] => Ok(existing),
+            _ => Err(Error::AmbiguousTaskList {
+                name: name.to_string(),
+                candidates: candidates.into_iter().cloned().collect(),
+            }),
         }
     }
 
-    pub fn get_current_task_list_mut(&mut self) -> &mut TaskList {
-        self.task_lists.get_mut(&self.current_list).unwrap()
+    /// Adds all task lists from another database to this one.
+    ///
+    /// Nothing is imported if any of the other database’s task lists has the same name as a task
+    /// list in this database.
+    pub fn import(&mut self, other: Db) -> Result<(), Error> {
+        if let Some(name) = other
+            .task_lists
+            .keys()
+            .find(|name| self.task_lists.contains_key(*name))
+        {
+            return Err(Error::ExistingTaskList(name.clone()));
+        }
+
+        self.task_lists.extend(other.task_lists);
+
+        Ok(())
     }
-}
 
-impl Default for Db {
-    fn default() -> Self {
-        Self {
-            task_lists: {
-                let mut task_lists = IndexMap::new();
-                task_lists.insert("Tasks".to_string(), TaskList::default());
-                task_lists
-            },
-            current_list: "Tasks".to_string(),
+    /// Replaces the entire database with a backup, returning the database as it was before.
+    ///
+    /// The backup is rejected if its current task list doesn’t exist.
+    pub fn restore(&mut self, backup: Db) -> Result<Db, Error> {
+        if !backup.task_lists.contains_key(&backup.current_list) {
+            return Err(Error::NoCurrentTaskList);
         }
+
+        Ok(mem::replace(self, backup))
     }
-}
 
-impl fmt::Display for Db {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fn print_task_list(
-            current_list: &str,
-            name: &str,
-            task_list: &TaskList,
-            f: &mut fmt::Formatter<'_>,
-        ) -> fmt::Result {
-            if name == current_list {
-                writeln!(f, "{} (current)", name)?;
+    /// Iterates over every task in every task list as `(list name, ID, task)`, going through the
+    /// task lists in the order they are displayed and the tasks within each in their usual order.
+    pub fn iter_tasks(&self) -> impl Iterator<Item = (&str, u32, &Task)> {
+        self.task_lists.iter().flat_map(|(name, task_list)| {
+            task_list
+                .iter()
+                .map(move |(id, task)| (name.as_str(), id, task))
+        })
+    }
+
+    /// Like [`Db::iter_tasks`], but allows the tasks to be changed in place.
+    pub fn iter_tasks_mut(&mut self) -> impl Iterator<Item = (&str, u32, &mut Task)> {
+        self.task_lists.iter_mut().flat_map(|(name, task_list)| {
+            task_list
+                .iter_mut()
+                .map(move |(id, task)| (name.as_str(), id, task))
+        })
+    }
+
+    /// Counts how many incomplete and complete tasks carry each tag across all task lists.
+    ///
+    /// The counts are returned as `(tag, incomplete, complete)`, sorted by the total number of
+    /// tasks with that tag from most to least, with ties broken alphabetically. Tasks without any
+    /// tags are counted under `(untagged)`. Cancelled tasks are neither, so they aren’t counted.
+    pub fn tag_counts(&self) -> Vec<(String, usize, usize)> {
+        let mut counts: IndexMap<&str, (usize, usize)> = IndexMap::new();
+
+        for (_, _, task) in self
+            .iter_tasks()
+            .filter(|(_, _, task)| !task.is_cancelled())
+        {
+            let tags = if task.tags().is_empty() {
+                vec!["(untagged)"]
             } else {
-                writeln!(f, "{}", name)?;
+                task.tags().iter().map(String::as_str).collect()
+            };
+
+            for tag in tags {
+                let (incomplete, complete) = counts.entry(tag).or_default();
+
+                if task.is_complete() {
+                    *complete += 1;
+                } else {
+                    *incomplete += 1;
+                }
             }
+        }
 
-            if task_list.is_empty() {
-                write!(f, "  No tasks have been added to this task list yet")
-            } else {
-                // Indent each line of output by two spaces by splitting by line, adding the
-                // indentation, and collecting back again.
-                write!(
-                    f,
-                    "{}",
-                    task_list
-                        .to_string()
-                        .lines()
-                        .map(|line| format!("  {}", line))
-                        .collect::<Vec<_>>()
-                        .join("\n")
-                )
+        let mut counts: Vec<_> = counts
+            .into_iter()
+            .map(|(tag, (incomplete, complete))| (tag.to_string(), incomplete, complete))
+            .collect();
+
+        counts.sort_by(
+            |(tag_a, incomplete_a, complete_a), (tag_b, incomplete_b, complete_b)| {
+                (incomplete_b + complete_b)
+                    .cmp(&(incomplete_a + complete_a))
+                    .then_with(|| tag_a.cmp(tag_b))
+            },
+        );
+
+        counts
+    }
+
+    /// Counts the complete and incomplete tasks in each task list and across all of them. Cancelled
+    /// tasks are left out, since they were dropped rather than done or still to do.
+    pub fn stats(&self) -> Stats {
+        let mut stats = Stats::default();
+
+        for (name, task_list) in &self.task_lists {
+            let mut counts = Counts::default();
+
+            for (_, task) in task_list.iter().filter(|(_, task)| !task.is_cancelled()) {
+                if task.is_complete() {
+                    counts.complete += 1;
+                } else {
+                    counts.incomplete += 1;
+                }
+            }
+
+            stats.total.complete += counts.complete;
+            stats.total.incomplete += counts.incomplete;
+            stats.task_lists.push((name.clone(), counts));
+        }
+
+        stats
+    }
+
+    /// Finds the task that should be worked on next.
+    ///
+    /// This is the incomplete task with the highest priority, preferring the one due soonest after
+    /// `today` when several share that priority (overdue tasks first, tasks without a due date
+    /// last), and the one that appears first otherwise. Cancelled tasks and tasks blocked by
+    /// unfinished ones are skipped. Only the current task list is considered unless `all_lists` is
+    /// set.
+    pub fn next_actionable(
+        &self,
+        today: NaiveDate,
+        all_lists: bool,
+    ) -> Option<(String, u32, &Task)> {
+        self.iter_tasks()
+            .filter(|(name, _, _)| all_lists || *name == self.current_list)
+            .filter(|(name, _, task)| {
+                !task.is_finished() && !self.task_lists[*name].is_blocked(task)
+            })
+            .min_by_key(|(_, _, task)| {
+                let days_left = task.due().map(|due| (due - today).num_days());
+                (Reverse(task.priority()), days_left.is_none(), days_left)
+            })
+            .map(|(name, id, task)| (name.to_string(), id, task))
+    }
+
+    /// Finds the tasks carrying a tag across all task lists, returned as `(list name, ID, task)`.
+    pub fn tasks_with_tag(&self, tag: &str) -> Vec<(String, u32, &Task)> {
+        self.iter_tasks()
+            .filter(|(_, _, task)| task.has_tag(tag))
+            .map(|(name, id, task)| (name.to_string(), id, task))
+            .collect()
+    }
+
+    /// Finds the tasks whose titles contain a query across all task lists, ignoring case and
+    /// surrounding whitespace. The matches are returned as `(list name, ID, task)`. An empty query
+    /// matches nothing.
+    pub fn search(&self, query: &str) -> Vec<(String, u32, &Task)> {
+        let query = query.trim().to_lowercase();
+
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        self.iter_tasks()
+            .filter(|(_, _, task)| task.title().to_lowercase().contains(&query))
+            .map(|(name, id, task)| (name.to_string(), id, task))
+            .collect()
+    }
+
+    /// Finds the tasks due on a date across all task lists, returned as `(list name, ID, task)`.
+    pub fn due_on(&self, date: NaiveDate) -> Vec<(String, u32, &Task)> {
+        self.iter_tasks()
+            .filter(|(_, _, task)| task.due() == Some(date))
+            .map(|(name, id, task)| (name.to_string(), id, task))
+            .collect()
+    }
+
+    /// Finds the tasks due between two dates (inclusive) across all task lists, returned as
+    /// `(list name, ID, task)`.
+    pub fn due_between(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<(String, u32, &Task)>, Error> {
+        if start > end {
+            return Err(Error::InvalidDateRange { start, end });
+        }
+
+        Ok(self
+            .iter_tasks()
+            .filter(|(_, _, task)| task.due().is_some_and(|due| start <= due && due <= end))
+            .map(|(name, id, task)| (name.to_string(), id, task))
+            .collect())
+    }
+
+    /// Finds the incomplete tasks due before `today` across all task lists, returned as
+    /// `(list name, ID, task)`. Cancelled tasks are never overdue.
+    pub fn overdue(&self, today: NaiveDate) -> Vec<(String, u32, &Task)> {
+        self.iter_tasks()
+            .filter(|(_, _, task)| !task.is_finished() && task.due().is_some_and(|due| due < today))
+            .map(|(name, id, task)| (name.to_string(), id, task))
+            .collect()
+    }
+
+    /// Finds the tasks with a reminder on or before `today` across all task lists, returned as
+    /// `(list name, ID, task)`.
+    pub fn with_reminders_due(&self, today: NaiveDate) -> Vec<(String, u32, &Task)> {
+        self.iter_tasks()
+            .filter(|(_, _, task)| task.reminders().iter().any(|reminder| *reminder <= today))
+            .map(|(name, id, task)| (name.to_string(), id, task))
+            .collect()
+    }
+
+    /// Finds the tasks completed on or after `since` (going by the date in the time zone of
+    /// `since`) across all task lists, returned as `(list name, ID, task)`. Tasks are kept
+    /// together by task list, most recently completed first; tasks without a completion time are
+    /// left out.
+    pub fn completed_since<Tz: TimeZone>(&self, since: Date<Tz>) -> Vec<(String, u32, &Task)> {
+        let mut tasks: Vec<_> = self
+            .iter_tasks()
+            .filter_map(|(name, id, task)| {
+                let completed = task.completed().filter(|_| task.is_complete())?;
+
+                if completed.with_timezone(&since.timezone()).date() < since {
+                    return None;
+                }
+
+                let list_index = self.task_lists.get_index_of(name)?;
+
+                Some((
+                    (list_index, Reverse(completed)),
+                    (name.to_string(), id, task),
+                ))
+            })
+            .collect();
+
+        tasks.sort_by_key(|(key, _)| *key);
+
+        tasks.into_iter().map(|(_, task)| task).collect()
+    }
+
+    /// Removes the completed tasks from every task list, returning how many were removed in all.
+    pub fn remove_completed_everywhere(&mut self) -> usize {
+        self.task_lists
+            .values_mut()
+            .map(TaskList::remove_completed_tasks)
+            .sum()
+    }
+
+    /// Removes the completed tasks from every task list, returning them in the order they were
+    /// displayed so they can be archived.
+    pub fn archive_completed(&mut self) -> Vec<ArchivedTask> {
+        let mut archived = Vec::new();
+
+        for (name, task_list) in &mut self.task_lists {
+            for task in task_list.take_completed_tasks() {
+                archived.push(ArchivedTask {
+                    list: name.clone(),
+                    title: task.title().to_string(),
+                    completed: task.completed(),
+                });
+            }
+        }
+
+        archived
+    }
+
+    /// Replaces a tag on every task in every task list, returning how many tasks were changed. The
+    /// tag keeps its color, unless the new tag already has one of its own.
+    pub fn rename_tag(&mut self, old: &str, new: &str) -> usize {
+        if let Some(color) = self.tag_colors.remove(old) {
+            self.tag_colors.entry(new.to_string()).or_insert(color);
+        }
+
+        self.task_lists
+            .values_mut()
+            .map(|task_list| task_list.rename_tag(old, new))
+            .sum()
+    }
+
+    /// Serializes the database to JSON, tagged with the current schema version.
+    pub fn to_json(&self) -> String {
+        #[derive(Serialize)]
+        struct Versioned<'a> {
+            version: u32,
+            #[serde(flatten)]
+            db: &'a Db,
+        }
+
+        // Serializing can only fail if the data contains something JSON can’t represent (such as
+        // a map with non-string keys), which is not the case here.
+        serde_json::to_string_pretty(&Versioned {
+            version: schema::CURRENT_VERSION,
+            db: self,
+        })
+        .unwrap()
+    }
+
+    /// Deserializes a database from JSON, migrating it from older schema versions if necessary.
+    pub fn from_json(json: &str) -> Result<Self, schema::Error> {
+        let db = schema::migrate(serde_json::from_str(json)?)?;
+
+        Ok(serde_json::from_value(db)?)
+    }
+
+    /// Serializes the database to TOML, which is friendlier to edit by hand than JSON. It holds
+    /// the same data as [`Db::to_json`], except that TOML has no null, so fields without a value
+    /// are left out.
+    pub fn to_toml(&self) -> String {
+        let mut db: serde_json::Value = serde_json::from_str(&self.to_json()).unwrap();
+        remove_nulls(&mut db);
+
+        // Converting to a TOML value first puts plain values before tables, which TOML requires.
+        // This can’t fail, since every key in the JSON is a string and there are no nulls left.
+        toml::to_string_pretty(&toml::Value::try_from(db).unwrap()).unwrap()
+    }
+
+    /// Deserializes a database from TOML written by [`Db::to_toml`], migrating it from older schema
+    /// versions if necessary.
+    pub fn from_toml(toml: &str) -> Result<Self, schema::Error> {
+        let db = schema::migrate(toml::from_str(toml)?)?;
+
+        Ok(serde_json::from_value(db)?)
+    }
+
+    /// Serializes the tasks as CSV with a row for each task, giving its task list, ID, status and
+    /// title.
+    pub fn to_csv(&self) -> String {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+
+        for (name, task_list) in &self.task_lists {
+            for (id, task) in task_list.iter() {
+                // Writing to a `Vec` can’t fail, and neither can serializing a flat struct.
+                writer
+                    .serialize(Row {
+                        list: name.clone(),
+                        id,
+                        status: task.status(),
+                        title: task.title().to_string(),
+                    })
+                    .unwrap();
             }
+        }
+
+        String::from_utf8(writer.into_inner().unwrap()).unwrap()
+    }
+
+    /// Reconstructs a database from CSV written by [`Db::to_csv`].
+    ///
+    /// Only what CSV holds survives: task lists without tasks are lost, the first task list
+    /// becomes the current one, and everything about a task other than its status and title is
+    /// reset. A CSV file without any tasks gives the default database.
+    pub fn from_csv(reader: impl io::Read) -> Result<Db, spreadsheet::Error> {
+        let mut db = Db::empty();
+
+        for row in csv::Reader::from_reader(reader).deserialize() {
+            let Row {
+                list,
+                id,
+                status,
+                title,
+            } = row?;
+
+            let mut task = Task::new(title);
+            task.set_status(status);
+
+            let task_list = db.task_lists.entry(list.clone()).or_default();
+
+            if task_list.insert_task_with_id(id, task).is_err() {
+                return Err(spreadsheet::Error::DuplicateTaskId { list, id });
+            }
+        }
+
+        match db.task_lists.keys().next() {
+            Some(name) => db.current_list = name.clone(),
+            None => db = Db::default(),
+        }
+
+        Ok(db)
+    }
+
+    /// Renders the database as Markdown, with a heading for each task list and a checklist of its
+    /// tasks beneath.
+    pub fn to_markdown(&self) -> String {
+        self.task_lists
+            .iter()
+            .map(|(name, task_list)| {
+                let mut section = format!("## {}", name);
+
+                if *name == self.current_list {
+                    section.push_str(" _(current)_");
+                }
+
+                section.push('\n');
+
+                if !task_list.is_empty() {
+                    section.push('\n');
+                }
+
+                for (_, task) in task_list.iter() {
+                    let checkbox = if task.is_complete() { "[x]" } else { "[ ]" };
+                    section.push_str(&format!("- {} {}\n", checkbox, task.title()));
+                }
+
+                section
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Sets the color a tag is drawn in, replacing any color it had before.
+    pub fn set_tag_color(&mut self, tag: String, color: Color) {
+        self.tag_colors.insert(tag, color);
+    }
+
+    /// Returns the color a tag is drawn in, if it has been given one.
+    pub fn tag_color(&self, tag: &str) -> Option<Color> {
+        self.tag_colors.get(tag).copied()
+    }
+
+    /// Renders the database with task titles wrapped to fit within `width` columns.
+    pub fn render_wrapped(&self, width: usize) -> String {
+        self.render(&RenderOptions {
+            width: Some(width),
+            ..RenderOptions::default()
+        })
+    }
+
+    /// Renders the database with tasks that are overdue or due on `today` marked as such.
+    pub fn render_with_date(&self, today: NaiveDate, use_color: bool) -> String {
+        self.render(&RenderOptions {
+            use_color,
+            today: Some(today),
+            ..RenderOptions::default()
+        })
+    }
+
+    pub fn render(&self, options: &RenderOptions) -> String {
+        if self.task_lists.is_empty() {
+            return "No task lists have been added yet".to_string();
+        }
+
+        let max_lists = options.max_lists.unwrap_or(usize::MAX);
+
+        let shown_lists: Vec<_> = self
+            .task_lists
+            .iter()
+            .enumerate()
+            .filter(|(i, (name, _))| *i < max_lists || **name == self.current_list)
+            .map(|(_, list)| list)
+            .collect();
+
+        let mut sections = self.render_sections(shown_lists, options);
+
+        let num_hidden = self.task_lists.len() - sections.len();
+
+        if num_hidden > 0 {
+            sections.push(format!(
+                "… and {} more {}",
+                num_hidden,
+                if num_hidden == 1 { "list" } else { "lists" }
+            ));
+        }
+
+        if options.summary {
+            sections.push(self.summary());
+        }
+
+        render::transliterate_if_ascii(sections.join("\n\n"), options)
+    }
+
+    /// Sums up how many tasks there are across every task list, such as “12 tasks across 3 lists,
+    /// 5 complete”.
+    pub fn summary(&self) -> String {
+        let (num_tasks, num_complete) =
+            self.iter_tasks()
+                .fold((0, 0), |(num_tasks, num_complete), (_, _, task)| {
+                    (
+                        num_tasks + 1,
+                        num_complete + usize::from(task.is_complete()),
+                    )
+                });
+
+        if num_tasks == 0 {
+            return "No tasks".to_string();
+        }
+
+        let num_lists = self.task_lists.len();
+
+        format!(
+            "{} {} across {} {}, {} complete",
+            num_tasks,
+            if num_tasks == 1 { "task" } else { "tasks" },
+            num_lists,
+            if num_lists == 1 { "list" } else { "lists" },
+            num_complete
+        )
+    }
+
+    /// Renders only the named task lists, in the order they are named.
+    pub fn display_subset(&self, names: &[&str]) -> Result<String, Error> {
+        self.render_subset(names, &RenderOptions::default())
+    }
+
+    /// Renders only the named task lists, in the order they are named. Limiting how many task
+    /// lists are shown has no effect, since only those named are shown anyway.
+    pub fn render_subset(&self, names: &[&str], options: &RenderOptions) -> Result<String, Error> {
+        let non_existent: Vec<_> = names
+            .iter()
+            .filter(|name| !self.task_lists.contains_key(**name))
+            .map(|name| name.to_string())
+            .collect();
+
+        if !non_existent.is_empty() {
+            return Err(Error::NonExistentTaskLists(non_existent));
+        }
+
+        let shown_lists = names
+            .iter()
+            .map(|name| self.task_lists.get_key_value(*name).unwrap())
+            .collect();
+
+        Ok(render::transliterate_if_ascii(
+            self.render_sections(shown_lists, options).join("\n\n"),
+            options,
+        ))
+    }
+
+    /// Renders each task list with the same columns, so that titles line up across task lists too.
+    fn render_sections(
+        &self,
+        shown_lists: Vec<(&String, &TaskList)>,
+        options: &RenderOptions,
+    ) -> Vec<String> {
+        let columns = shown_lists
+            .iter()
+            .map(|(_, task_list)| task_list.columns())
+            .fold(Columns::default(), Columns::widest);
+
+        let mut tag_colors = self.tag_colors.clone();
+        tag_colors.extend(options.tag_colors.clone());
+
+        let options = &RenderOptions {
+            tag_colors,
+            ..options.clone()
+        };
+
+        shown_lists
+            .into_iter()
+            .map(|(name, task_list)| self.render_task_list(name, task_list, columns, options))
+            .collect()
+    }
+
+    fn render_task_list(
+        &self,
+        name: &str,
+        task_list: &TaskList,
+        columns: Columns,
+        options: &RenderOptions,
+    ) -> String {
+        let mut header = name.to_string();
+
+        if name == self.current_list {
+            let marker = if options.use_color {
+                render::bold_green("(current)")
+            } else {
+                "(current)".to_string()
+            };
+
+            header.push_str(&format!(" {}", marker));
+        }
+
+        header.push_str(&format!(
+            " [{}/{}]",
+            task_list.count(Some(Status::Complete)),
+            task_list.count(None)
+        ));
+
+        let body = if task_list.is_empty() {
+            "  No tasks have been added to this task list yet".to_string()
+        } else {
+            // Indent each line of output by two spaces by splitting by line, adding the
+            // indentation, and collecting back again.
+            task_list
+                .render_in_columns(columns, &options.indented(2))
+                .lines()
+                .map(|line| {
+                    if line.is_empty() {
+                        String::new()
+                    } else {
+                        format!("  {}", line)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
         };
 
-        for (name, task_list) in self.task_lists.iter().take(self.task_lists.len() - 1) {
-            print_task_list(&self.current_list, name, task_list, f)?;
-            writeln!(f, "\n")?;
+        format!("{}\n{}", header, body)
+    }
+
+    /// Returns the names of the task lists in the order they are displayed.
+    pub fn list_names(&self) -> Vec<&str> {
+        self.task_lists.keys().map(String::as_str).collect()
+    }
+
+    pub fn current_list(&self) -> &str {
+        &self.current_list
+    }
+
+    /// Points the current task list at the first task list if it names one that doesn’t exist,
+    /// which can happen if the database was edited by hand. If there are no task lists, an empty
+    /// one called `default_list` is added. Returns whether anything changed.
+    pub fn repair_current_list(&mut self, default_list: &str) -> bool {
+        if self.task_lists.contains_key(&self.current_list) {
+            return false;
+        }
+
+        self.ensure_nonempty(default_list);
+
+        true
+    }
+
+    /// Makes sure there is at least one task list and that the current task list is one of them,
+    /// adding an empty task list called `default_list` if none are left and otherwise making the
+    /// first task list current if the current one is gone. Anything that removes task lists calls
+    /// this afterwards, so that there is always a current task list to add tasks to.
+    pub fn ensure_nonempty(&mut self, default_list: &str) {
+        if self.task_lists.is_empty() {
+            self.task_lists
+                .insert(default_list.to_string(), TaskList::default());
         }
 
-        if let Some((name, task_list)) = self.task_lists.iter().last() {
-            print_task_list(&self.current_list, name, task_list, f)?;
+        if !self.task_lists.contains_key(&self.current_list) {
+            self.current_list = self.task_lists.keys().next().unwrap().clone();
         }
 
-        Ok(())
+        if let Some(previous_list) = &self.previous_list {
+            if !self.task_lists.contains_key(previous_list) {
+                self.previous_list = None;
+            }
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::task::Task;
+    /// Checks the database for anything that t itself would never produce, returning what it finds.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        if self.task_lists.is_empty() {
+            issues.push(ValidationIssue::NoTaskLists);
+        }
+
+        if !self.task_lists.contains_key(&self.current_list) {
+            issues.push(ValidationIssue::DanglingCurrentList(
+                self.current_list.clone(),
+            ));
+        }
+
+        for (name, id, task) in self.iter_tasks() {
+            let list = name.to_string();
+
+            if task.title().trim().is_empty() {
+                issues.push(ValidationIssue::BlankTitle {
+                    list: list.clone(),
+                    id,
+                });
+            }
+
+            if !task.reminders_are_tidy() {
+                issues.push(ValidationIssue::UntidyReminders {
+                    list: list.clone(),
+                    id,
+                });
+            }
+
+            if task.has_duplicate_tags() {
+                issues.push(ValidationIssue::DuplicateTags { list, id });
+            }
+        }
+
+        issues
+    }
+
+    /// Repairs the issues [`Db::validate`] reports that can be repaired without losing anything,
+    /// returning those that remain. A dangling current task list is pointed at the first task
+    /// list, or a new task list called `default_list` if there are none.
+    pub fn fix(&mut self, default_list: &str) -> Vec<ValidationIssue> {
+        self.repair_current_list(default_list);
+
+        for (_, _, task) in self.iter_tasks_mut() {
+            task.tidy_reminders();
+            task.dedup_tags();
+        }
+
+        self.validate()
+    }
+
+    pub fn get_task_list_mut(&mut self, name: &str) -> Result<&mut TaskList, Error> {
+        self.task_lists
+            .get_mut(name)
+            .ok_or_else(|| Error::NonExistentTaskList(name.to_string()))
+    }
+
+    pub fn get_current_task_list_mut(&mut self) -> Result<&mut TaskList, Error> {
+        self.task_lists
+            .get_mut(&self.current_list)
+            .ok_or(Error::NoCurrentTaskList)
+    }
+}
+
+fn remove_nulls(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(fields) => {
+            *fields = mem::take(fields)
+                .into_iter()
+                .filter(|(_, field)| !field.is_null())
+                .collect();
+            fields.values_mut().for_each(remove_nulls);
+        }
+        serde_json::Value::Array(elements) => elements.iter_mut().for_each(remove_nulls),
+        _ => {}
+    }
+}
+
+/// Counts how many single-character insertions, deletions and substitutions it takes to turn one
+/// string into another.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<_> = b.chars().collect();
+
+    // We only keep the previous row of the distance matrix around.
+    let mut previous_row: Vec<_> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut row = vec![i + 1];
+
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous_row[j] + usize::from(a_char != *b_char);
+            let deletion = previous_row[j + 1] + 1;
+            let insertion = row[j] + 1;
+
+            row.push(substitution.min(deletion).min(insertion));
+        }
+
+        previous_row = row;
+    }
+
+    previous_row[b.len()]
+}
+
+impl Default for Db {
+    fn default() -> Self {
+        Self::with_default_list(DEFAULT_LIST_NAME.to_string())
+    }
+}
+
+impl fmt::Display for Db {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(&RenderOptions::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::priority::Priority;
+    use chrono::{Duration, FixedOffset, NaiveDate, Utc};
+
+    #[test]
+    fn empty_db_has_no_task_lists() {
+        assert_eq!(
+            Db::empty(),
+            Db {
+                task_lists: IndexMap::new(),
+                current_list: String::new(),
+                previous_list: None,
+                tag_colors: HashMap::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn operating_on_current_task_list_of_empty_db_gives_error() {
+        let mut db = Db::empty();
+
+        assert_eq!(
+            db.get_current_task_list_mut(),
+            Err(Error::NoCurrentTaskList)
+        );
+
+        db.add_task_list("Tasks".to_string(), TaskList::default());
+
+        // The task list has been added, but has not been made current yet.
+        assert_eq!(
+            db.get_current_task_list_mut(),
+            Err(Error::NoCurrentTaskList)
+        );
+
+        db.set_current("Tasks".to_string()).unwrap();
+        assert!(db.get_current_task_list_mut().is_ok());
+    }
+
+    #[test]
+    fn db_can_be_created_with_custom_default_task_list() {
+        let db = Db::with_default_list("Inbox".to_string());
+
+        assert_eq!(db.list_names(), ["Inbox"]);
+        assert_eq!(db.current_list, "Inbox");
+        assert!(db.task_lists["Inbox"].is_empty());
+    }
+
+    #[test]
+    fn default_db_has_task_list_called_tasks() {
+        assert_eq!(Db::default(), Db::with_default_list("Tasks".to_string()));
+    }
+
+    #[test]
+    fn task_lists_can_be_added() {
+        let mut db = Db::default();
+
+        let shopping_list = {
+            let mut tl = TaskList::default();
+            tl.add_task(Task::new("Milk".to_string())).unwrap();
+            tl.add_task(Task::new("Frozen pizza".to_string())).unwrap();
+            tl.add_task(Task::new("Yoghurt".to_string())).unwrap();
+
+            tl
+        };
+
+        let school_tasks = {
+            let mut tl = TaskList::default();
+            tl.add_task(Task::new("Finish history homework".to_string()))
+                .unwrap();
+            tl.add_task(Task::new("Write english essay".to_string()))
+                .unwrap();
+            tl.add_task(Task::new("Study for chemistry test".to_string()))
+                .unwrap();
+
+            tl
+        };
+
+        db.add_task_list("Shopping List".to_string(), shopping_list.clone());
+        db.add_task_list("School".to_string(), school_tasks.clone());
+
+        assert_eq!(
+            db,
+            Db {
+                task_lists: {
+                    let mut task_lists = IndexMap::new();
+                    task_lists.insert("Tasks".to_string(), TaskList::default());
+                    task_lists.insert("Shopping List".to_string(), shopping_list);
+                    task_lists.insert("School".to_string(), school_tasks);
+
+                    task_lists
+                },
+                current_list: "Tasks".to_string(),
+                previous_list: None,
+                tag_colors: HashMap::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn task_lists_can_be_removed() {
+        let mut db = Db::default();
+
+        db.add_task_list("Errands".to_string(), TaskList::default());
+        assert_eq!(db.task_lists.len(), 2); // 2 because there is also the default task list.
+
+        db.remove_task_list("Errands".to_string(), DEFAULT_LIST_NAME)
+            .unwrap();
+        assert_eq!(db.task_lists.len(), 1);
+    }
+
+    #[test]
+    fn trying_to_remove_non_existent_task_list_gives_error() {
+        let mut db = Db::default();
+
+        assert_eq!(
+            db.remove_task_list("Foo Bar Baz".to_string(), DEFAULT_LIST_NAME),
+            Err(Error::NonExistentTaskList("Foo Bar Baz".to_string()))
+        );
+    }
+
+    #[test]
+    fn removing_task_list_keeps_the_order_of_the_others() {
+        let mut db = db_with_lists(&["Home", "Work", "Garden", "Errands"]);
+
+        db.remove_task_list("Work".to_string(), DEFAULT_LIST_NAME)
+            .unwrap();
+
+        assert_eq!(
+            db.task_lists.keys().collect::<Vec<_>>(),
+            vec!["Home", "Garden", "Errands"]
+        );
+    }
+
+    #[test]
+    fn removing_current_task_list_makes_the_first_remaining_one_current() {
+        let mut db = db_with_lists(&["Home", "Work", "Garden"]);
+        db.set_current("Home".to_string()).unwrap();
+
+        db.remove_task_list("Home".to_string(), DEFAULT_LIST_NAME)
+            .unwrap();
+
+        assert_eq!(db.current_list, "Work".to_string());
+    }
+
+    #[test]
+    fn removing_the_last_task_list_recreates_the_default_one() {
+        let mut db = Db::default();
+        db.get_current_task_list_mut()
+            .unwrap()
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+
+        db.remove_task_list("Tasks".to_string(), DEFAULT_LIST_NAME)
+            .unwrap();
+
+        assert_eq!(db, Db::default());
+    }
+
+    #[test]
+    fn removing_every_task_list_recreates_the_default_one_as_current() {
+        let mut db = db_with_lists(&["Home", "Work"]);
+        db.set_tag_color("home".to_string(), Color::Blue);
+
+        for name in db
+            .list_names()
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>()
+        {
+            db.remove_task_list(name, DEFAULT_LIST_NAME).unwrap();
+        }
+
+        assert_eq!(db.list_names(), ["Tasks"]);
+        assert_eq!(db.current_list(), "Tasks");
+        assert!(db.get_current_task_list_mut().is_ok());
+        assert_eq!(db.tag_color("home"), Some(Color::Blue));
+    }
+
+    #[test]
+    fn removing_the_last_task_list_recreates_the_configured_default_one() {
+        let mut db = Db::with_default_list("Inbox".to_string());
+
+        db.remove_task_list("Inbox".to_string(), "Inbox").unwrap();
+
+        assert_eq!(db, Db::with_default_list("Inbox".to_string()));
+    }
+
+    #[test]
+    fn ensuring_nonempty_leaves_a_healthy_db_alone() {
+        let mut db = Db::default();
+        db.add_task_list("Work".to_string(), TaskList::default());
+        db.set_current("Work".to_string()).unwrap();
+        let before = db.clone();
+
+        db.ensure_nonempty(DEFAULT_LIST_NAME);
+
+        assert_eq!(db, before);
+    }
+
+    #[test]
+    fn task_lists_can_be_renamed() {
+        let mut db = Db::default();
+
+        let work_tasks = {
+            let mut tl = TaskList::default();
+            tl.add_task(Task::new("Finish spreadsheet".to_string()))
+                .unwrap();
+            tl.add_task(Task::new("Write report".to_string())).unwrap();
+
+            tl
+        };
+
+        db.add_task_list("Wokr".to_string(), work_tasks.clone());
+
+        db.rename_task_list("Wokr".to_string(), "Work".to_string())
+            .unwrap();
+
+        assert!(db.task_lists.get("Wokr").is_none());
+        assert_eq!(db.task_lists["Work"], work_tasks);
+    }
+
+    #[test]
+    fn renaming_non_existent_task_list_gives_error() {
+        let mut db = Db::default();
+
+        assert_eq!(
+            db.rename_task_list("Foo".to_string(), "Bar".to_string()),
+            Err(Error::NonExistentTaskList("Foo".to_string()))
+        );
+    }
+
+    #[test]
+    fn renaming_task_list_to_existing_name_gives_error() {
+        let mut db = db_with_lists(&["Home", "Work"]);
+
+        assert_eq!(
+            db.rename_task_list("Home".to_string(), "Work".to_string()),
+            Err(Error::ExistingTaskList("Work".to_string()))
+        );
+        assert_eq!(
+            db.task_lists.keys().collect::<Vec<_>>(),
+            vec!["Home", "Work"]
+        );
+    }
+
+    #[test]
+    fn switching_to_previous_task_list_alternates_between_two_task_lists() {
+        let mut db = db_with_lists(&["Home", "Work", "Garden"]);
+        db.set_current("Home".to_string()).unwrap();
+        db.set_current("Work".to_string()).unwrap();
+
+        assert!(db.switch_to_previous());
+        assert_eq!(db.current_list, "Home");
+        assert!(db.switch_to_previous());
+        assert_eq!(db.current_list, "Work");
+        assert!(db.switch_to_previous());
+        assert_eq!(db.current_list, "Home");
+    }
+
+    #[test]
+    fn switching_to_previous_task_list_without_one_does_nothing() {
+        let mut db = db_with_lists(&["Home", "Work"]);
+        let original = db.clone();
+
+        assert!(!db.switch_to_previous());
+        assert_eq!(db, original);
+    }
+
+    #[test]
+    fn previous_task_list_follows_renames_and_forgets_removals() {
+        let mut db = db_with_lists(&["Home", "Wokr", "Garden"]);
+        db.set_current("Wokr".to_string()).unwrap();
+        db.set_current_by_index(0).unwrap();
+        db.rename_task_list("Wokr".to_string(), "Work".to_string())
+            .unwrap();
+
+        assert!(db.switch_to_previous());
+        assert_eq!(db.current_list, "Work");
+
+        db.set_current("Garden".to_string()).unwrap();
+        db.remove_task_list("Work".to_string(), DEFAULT_LIST_NAME)
+            .unwrap();
+
+        assert!(!db.switch_to_previous());
+        assert_eq!(db.current_list, "Garden");
+    }
+
+    #[test]
+    fn databases_without_previous_task_list_can_be_deserialized() {
+        let db: Db =
+            serde_json::from_str(r#"{"task_lists":{"Tasks":{"tasks":{}}},"current_list":"Tasks"}"#)
+                .unwrap();
+
+        assert_eq!(db, Db::default());
+    }
+
+    #[test]
+    fn renaming_or_merging_task_list_to_new_name_renames_it() {
+        let mut db = db_with_lists(&["Home", "Wokr"]);
+        db.set_current("Wokr".to_string()).unwrap();
+
+        db.rename_or_merge_task_list("Wokr".to_string(), "Work".to_string())
+            .unwrap();
+
+        assert_eq!(db.list_names(), ["Home", "Work"]);
+        assert_eq!(db.current_list, "Work");
+    }
+
+    #[test]
+    fn renaming_or_merging_task_list_to_existing_name_merges_it() {
+        let mut db = db_with_lists(&["Home", "Chores"]);
+        db.task_lists["Home"]
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        db.task_lists["Chores"]
+            .add_task(Task::new("Fix the fence".to_string()))
+            .unwrap();
+        db.set_current("Chores".to_string()).unwrap();
+
+        db.rename_or_merge_task_list("Chores".to_string(), "Home".to_string())
+            .unwrap();
+
+        let tasks: Vec<_> = db.task_lists["Home"]
+            .iter()
+            .map(|(id, task)| (id, task.title()))
+            .collect();
+
+        assert_eq!(tasks, [(0, "Buy some milk"), (1, "Fix the fence")]);
+        assert_eq!(db.list_names(), ["Home"]);
+        assert_eq!(db.current_list, "Home");
+    }
+
+    #[test]
+    fn renamed_task_list_keeps_its_position() {
+        let mut db = db_with_lists(&["Home", "Wokr", "Garden"]);
+        db.set_current("Garden".to_string()).unwrap();
+
+        db.rename_task_list("Wokr".to_string(), "Work".to_string())
+            .unwrap();
+
+        assert_eq!(
+            format!("{}", db),
+            "\
+Home [0/0]
+  No tasks have been added to this task list yet
+
+Work [0/0]
+  No tasks have been added to this task list yet
+
+Garden (current) [0/0]
+  No tasks have been added to this task list yet"
+        );
+    }
+
+    #[test]
+    fn renaming_the_current_task_list_switches_to_it() {
+        let mut db = Db::default();
+
+        db.rename_task_list("Tasks".to_string(), "Todo".to_string())
+            .unwrap();
+        assert_eq!(db.current_list, "Todo".to_string());
+
+        db.add_task_list("Personal tasks".to_string(), TaskList::default());
+        assert_eq!(db.current_list, "Todo".to_string());
+
+        db.rename_task_list("Personal tasks".to_string(), "Personal".to_string())
+            .unwrap();
+        assert_eq!(db.current_list, "Todo".to_string());
+
+        db.set_current("Personal".to_string()).unwrap();
+        assert_eq!(db.current_list, "Personal".to_string());
+
+        db.rename_task_list("Personal".to_string(), "Personal tasks".to_string())
+            .unwrap();
+        assert_eq!(db.current_list, "Personal tasks".to_string());
+    }
+
+    fn db_with_lists(names: &[&str]) -> Db {
+        let mut db = Db::empty();
+
+        for name in names {
+            db.add_task_list(name.to_string(), TaskList::default());
+        }
+
+        db
+    }
+
+    #[test]
+    fn tasks_are_iterated_list_by_list_in_id_order() {
+        let mut db = db_with_lists(&["Home", "Work", "Someday"]);
+
+        for (name, title) in &[
+            ("Work", "Write report"),
+            ("Home", "Fix the fence"),
+            ("Work", "Reply to emails"),
+            ("Home", "Water plants"),
+        ] {
+            db.get_task_list_mut(name)
+                .unwrap()
+                .add_task(Task::new(title.to_string()))
+                .unwrap();
+        }
+
+        let tasks: Vec<_> = db
+            .iter_tasks()
+            .map(|(name, id, task)| (name, id, task.title()))
+            .collect();
+
+        assert_eq!(
+            tasks,
+            [
+                ("Home", 0, "Fix the fence"),
+                ("Home", 1, "Water plants"),
+                ("Work", 0, "Write report"),
+                ("Work", 1, "Reply to emails"),
+            ]
+        );
+    }
+
+    #[test]
+    fn tasks_can_be_changed_while_iterating() {
+        let mut db = db_with_lists(&["Home", "Work"]);
+
+        for name in &["Home", "Work"] {
+            db.get_task_list_mut(name)
+                .unwrap()
+                .add_task(Task::new("Tidy up".to_string()))
+                .unwrap();
+        }
+
+        for (_, _, task) in db.iter_tasks_mut() {
+            task.complete();
+        }
+
+        assert!(db.iter_tasks().all(|(_, _, task)| task.is_complete()));
+    }
+
+    #[test]
+    fn task_lists_can_be_moved_to_the_front() {
+        let mut db = db_with_lists(&["Home", "Work", "Garden", "Errands"]);
+
+        db.move_list_to("Garden".to_string(), 0).unwrap();
+
+        assert_eq!(
+            db.task_lists.keys().collect::<Vec<_>>(),
+            vec!["Garden", "Home", "Work", "Errands"]
+        );
+    }
+
+    #[test]
+    fn task_lists_can_be_moved_to_the_middle() {
+        let mut db = db_with_lists(&["Home", "Work", "Garden", "Errands"]);
+
+        db.move_list_to("Home".to_string(), 2).unwrap();
+
+        assert_eq!(
+            db.task_lists.keys().collect::<Vec<_>>(),
+            vec!["Work", "Garden", "Home", "Errands"]
+        );
+    }
+
+    #[test]
+    fn moving_task_list_past_the_end_moves_it_to_the_end() {
+        let mut db = db_with_lists(&["Home", "Work", "Garden", "Errands"]);
+
+        db.move_list_to("Work".to_string(), 100).unwrap();
+
+        assert_eq!(
+            db.task_lists.keys().collect::<Vec<_>>(),
+            vec!["Home", "Garden", "Errands", "Work"]
+        );
+    }
+
+    #[test]
+    fn moving_non_existent_task_list_gives_error() {
+        let mut db = Db::default();
+
+        assert_eq!(
+            db.move_list_to("Work".to_string(), 0),
+            Err(Error::NonExistentTaskList("Work".to_string()))
+        );
+    }
+
+    #[test]
+    fn tasks_can_be_moved_between_task_lists() {
+        let mut db = Db::default();
+
+        let tasks = db.get_current_task_list_mut().unwrap();
+        tasks
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        tasks
+            .add_task(Task::new("Write report".to_string()))
+            .unwrap();
+        tasks.complete_task(1).unwrap();
+
+        db.add_task_list("Work".to_string(), {
+            let mut tl = TaskList::default();
+            tl.add_task(Task::new("Email the accountant".to_string()))
+                .unwrap();
+
+            tl
+        });
+
+        assert_eq!(db.move_task(1, "Tasks", "Work", false), Ok(1));
+
+        assert_eq!(
+            db.task_lists["Tasks"].to_string(),
+            "[  0] ! • Buy some milk"
+        );
+        assert_eq!(
+            db.task_lists["Work"].to_string(),
+            "\
+[  0] ! • Email the accountant
+[  1] ! – Write report"
+        );
+    }
+
+    #[test]
+    fn moved_task_can_keep_its_id_if_it_is_free() {
+        let mut db = db_with_lists(&["Home", "Work"]);
+        let home = db.get_task_list_mut("Home").unwrap();
+        for title in &["Buy some milk", "Fix the fence", "Call mum"] {
+            home.add_task(Task::new(title.to_string())).unwrap();
+        }
+        db.get_task_list_mut("Work")
+            .unwrap()
+            .add_task(Task::new("Write report".to_string()))
+            .unwrap();
+
+        assert_eq!(db.move_task(2, "Home", "Work", true), Ok(2));
+        assert_eq!(db.task_lists["Work"].get(2).unwrap().title(), "Call mum");
+    }
+
+    #[test]
+    fn moved_task_with_taken_id_gets_lowest_available_id_even_if_keeping_it() {
+        let mut db = db_with_lists(&["Home", "Work"]);
+        let home = db.get_task_list_mut("Home").unwrap();
+        home.add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        home.add_task(Task::new("Fix the fence".to_string()))
+            .unwrap();
+        let work = db.get_task_list_mut("Work").unwrap();
+        work.insert_task_with_id(1, Task::new("Write report".to_string()))
+            .unwrap();
+
+        assert_eq!(db.move_task(1, "Home", "Work", true), Ok(0));
+        assert_eq!(
+            db.task_lists["Work"].get(0).unwrap().title(),
+            "Fix the fence"
+        );
+        assert_eq!(
+            db.task_lists["Work"].get(1).unwrap().title(),
+            "Write report"
+        );
+    }
+
+    #[test]
+    fn moved_task_leaves_its_dependencies_behind() {
+        let mut db = db_with_lists(&["Home", "Work"]);
+
+        let home = db.get_task_list_mut("Home").unwrap();
+        home.add_task(Task::new("Buy paint".to_string())).unwrap();
+        home.add_task(Task::new("Paint the fence".to_string()))
+            .unwrap();
+        home.add_dependency(1, 0).unwrap();
+
+        db.get_task_list_mut("Work")
+            .unwrap()
+            .add_task(Task::new("Write report".to_string()))
+            .unwrap();
+
+        let id = db.move_task(1, "Home", "Work", false).unwrap();
+
+        assert_eq!(
+            db.get_task_list_mut("Work").unwrap().unmet_dependencies(id),
+            Ok(Vec::new())
+        );
+    }
+
+    #[test]
+    fn moving_non_existent_task_gives_error() {
+        let mut db = db_with_lists(&["Home", "Work"]);
+
+        assert_eq!(
+            db.move_task(3, "Home", "Work", false),
+            Err(Error::TaskList(task_list::Error::NonExistentTaskId(3)))
+        );
+    }
+
+    #[test]
+    fn moving_task_to_its_own_task_list_gives_error() {
+        let mut db = Db::default();
+        db.get_current_task_list_mut()
+            .unwrap()
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        let db_before = db.clone();
+
+        assert_eq!(
+            db.move_task(0, "Tasks", "Tasks", false),
+            Err(Error::MoveIntoSameList("Tasks".to_string()))
+        );
+        assert_eq!(db, db_before);
+    }
+
+    #[test]
+    fn moving_task_to_non_existent_task_list_gives_error() {
+        let mut db = Db::default();
+        db.get_current_task_list_mut()
+            .unwrap()
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+
+        assert_eq!(
+            db.move_task(0, "Tasks", "Work", false),
+            Err(Error::NonExistentTaskList("Work".to_string()))
+        );
+        assert!(!db.task_lists["Tasks"].is_empty());
+    }
+
+    #[test]
+    fn task_lists_can_be_merged_without_id_collisions() {
+        let mut db = db_with_lists(&["Project", "Home"]);
+        db.set_current("Project".to_string()).unwrap();
+
+        let project = db.task_lists.get_mut("Project").unwrap();
+        project
+            .add_task(Task::new("Write report".to_string()))
+            .unwrap();
+        project
+            .add_task(Task::new("Book flights".to_string()))
+            .unwrap();
+
+        let home = db.task_lists.get_mut("Home").unwrap();
+        home.add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+
+        db.merge_lists("Project", "Home").unwrap();
+
+        let tasks: Vec<_> = db.task_lists["Home"]
+            .iter()
+            .map(|(id, task)| (id, task.title()))
+            .collect();
+
+        assert_eq!(
+            tasks,
+            [
+                (0, "Buy some milk"),
+                (1, "Write report"),
+                (2, "Book flights")
+            ]
+        );
+        assert_eq!(db.list_names(), ["Home"]);
+        assert_eq!(db.current_list, "Home");
+    }
+
+    #[test]
+    fn merging_task_list_into_itself_gives_error() {
+        let mut db = db_with_lists(&["Home"]);
+
+        assert_eq!(
+            db.merge_lists("Home", "Home"),
+            Err(Error::MergeIntoItself("Home".to_string()))
+        );
+    }
+
+    #[test]
+    fn merging_non_existent_task_lists_gives_error() {
+        let mut db = db_with_lists(&["Home"]);
+
+        assert_eq!(
+            db.merge_lists("Garden", "Home"),
+            Err(Error::NonExistentTaskList("Garden".to_string()))
+        );
+        assert_eq!(
+            db.merge_lists("Home", "Garden"),
+            Err(Error::NonExistentTaskList("Garden".to_string()))
+        );
+        assert_eq!(db.list_names(), ["Home"]);
+    }
+
+    #[test]
+    fn task_lists_can_be_imported_from_another_db() {
+        let mut db = Db::default();
+
+        let mut other = Db::empty();
+        other.add_task_list("Work".to_string(), {
+            let mut tl = TaskList::default();
+            tl.add_task(Task::new("Email the accountant".to_string()))
+                .unwrap();
+
+            tl
+        });
+
+        db.import(other.clone()).unwrap();
+
+        assert_eq!(
+            db.task_lists.keys().collect::<Vec<_>>(),
+            vec!["Tasks", "Work"]
+        );
+        assert_eq!(db.task_lists["Work"], other.task_lists["Work"]);
+        assert_eq!(db.current_list, "Tasks".to_string());
+    }
+
+    #[test]
+    fn importing_task_list_with_existing_name_gives_error() {
+        let mut db = Db::default();
+
+        let mut other = Db::empty();
+        other.add_task_list("Work".to_string(), TaskList::default());
+        other.add_task_list("Tasks".to_string(), TaskList::default());
+
+        assert_eq!(
+            db.import(other),
+            Err(Error::ExistingTaskList("Tasks".to_string()))
+        );
+        assert_eq!(db.task_lists.len(), 1);
+    }
+
+    #[test]
+    fn tag_counts_are_split_by_status_and_sorted_by_total() {
+        let mut db = Db::default();
+
+        let tasks = db.get_current_task_list_mut().unwrap();
+        tasks
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        tasks
+            .add_task(Task::new("Post letter".to_string()))
+            .unwrap();
+        tasks
+            .add_task(Task::new("Clean kitchen".to_string()))
+            .unwrap();
+        tasks.add_task(Task::new("Call mum".to_string())).unwrap();
+        tasks.add_tag(0, "errands".to_string()).unwrap();
+        tasks.add_tag(1, "errands".to_string()).unwrap();
+        tasks.add_tag(2, "home".to_string()).unwrap();
+        tasks.complete_task(1).unwrap();
+
+        db.add_task_list("Work".to_string(), {
+            let mut tl = TaskList::default();
+            tl.add_task(Task::new("Pick up printer paper".to_string()))
+                .unwrap();
+            tl.add_task(Task::new("Write report".to_string())).unwrap();
+            tl.add_tag(0, "errands".to_string()).unwrap();
+            tl.add_tag(0, "office".to_string()).unwrap();
+            tl.add_tag(1, "office".to_string()).unwrap();
+            tl.complete_task(1).unwrap();
+
+            tl
+        });
+
+        assert_eq!(
+            db.tag_counts(),
+            vec![
+                ("errands".to_string(), 2, 1),
+                ("office".to_string(), 1, 1),
+                ("(untagged)".to_string(), 1, 0),
+                ("home".to_string(), 1, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn tag_counts_of_db_without_tasks_are_empty() {
+        assert!(Db::default().tag_counts().is_empty());
+    }
+
+    #[test]
+    fn tags_can_be_renamed_across_all_task_lists() {
+        let mut db = Db::default();
+
+        let tasks = db.get_current_task_list_mut().unwrap();
+        tasks
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        tasks
+            .add_task(Task::new("Post letter".to_string()))
+            .unwrap();
+        tasks.add_task(Task::new("Vacuum".to_string())).unwrap();
+        tasks.add_tag(0, "errands".to_string()).unwrap();
+        tasks.add_tag(1, "errands".to_string()).unwrap();
+        tasks.add_tag(1, "town".to_string()).unwrap();
+        tasks.add_tag(2, "home".to_string()).unwrap();
+
+        db.add_task_list("Work".to_string(), {
+            let mut tl = TaskList::default();
+            tl.add_task(Task::new("Pick up printer paper".to_string()))
+                .unwrap();
+            tl.add_tag(0, "errands".to_string()).unwrap();
+
+            tl
+        });
+
+        assert_eq!(db.rename_tag("errands", "town"), 3);
+        assert_eq!(
+            db.tag_counts(),
+            vec![("town".to_string(), 3, 0), ("home".to_string(), 1, 0)]
+        );
+    }
+
+    #[test]
+    fn tasks_can_be_filtered_by_tag_across_all_task_lists() {
+        let mut db = db_with_lists(&["Home", "Work"]);
+
+        let home = db.task_lists.get_mut("Home").unwrap();
+        home.add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        home.add_task(Task::new("Fix the fence".to_string()))
+            .unwrap();
+        home.add_tag(1, "weekend".to_string()).unwrap();
+
+        let work = db.task_lists.get_mut("Work").unwrap();
+        work.add_task(Task::new("Write report".to_string()))
+            .unwrap();
+        work.add_tag(0, "weekend".to_string()).unwrap();
+
+        let matches: Vec<_> = db
+            .tasks_with_tag("weekend")
+            .into_iter()
+            .map(|(name, id, _)| (name, id))
+            .collect();
+
+        assert_eq!(matches, [("Home".to_string(), 1), ("Work".to_string(), 0)]);
+        assert!(db.tasks_with_tag("errands").is_empty());
+    }
+
+    #[test]
+    fn tasks_can_be_searched_across_all_task_lists() {
+        let mut db = db_with_lists(&["Home", "Work"]);
+
+        let home = db.task_lists.get_mut("Home").unwrap();
+        home.add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        home.add_task(Task::new("Fix the fence".to_string()))
+            .unwrap();
+
+        let work = db.task_lists.get_mut("Work").unwrap();
+        work.add_task(Task::new("Write report".to_string()))
+            .unwrap();
+        work.add_task(Task::new("Order more MILK for the office".to_string()))
+            .unwrap();
+
+        let matches: Vec<_> = db
+            .search("  Milk ")
+            .into_iter()
+            .map(|(name, id, _)| (name, id))
+            .collect();
+
+        assert_eq!(matches, [("Home".to_string(), 0), ("Work".to_string(), 1)]);
+    }
+
+    #[test]
+    fn searching_without_matches_gives_nothing() {
+        let mut db = db_with_lists(&["Home"]);
+        db.task_lists
+            .get_mut("Home")
+            .unwrap()
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+
+        assert!(db.search("bread").is_empty());
+    }
+
+    #[test]
+    fn searching_for_empty_query_gives_nothing() {
+        let mut db = db_with_lists(&["Home"]);
+        db.task_lists
+            .get_mut("Home")
+            .unwrap()
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+
+        assert!(db.search("").is_empty());
+        assert!(db.search("   ").is_empty());
+    }
+
+    #[test]
+    fn stats_count_tasks_by_status_per_task_list_and_in_total() {
+        let mut db = db_with_lists(&["Home", "Work", "Someday"]);
+
+        let home = db.task_lists.get_mut("Home").unwrap();
+        home.add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        home.add_task(Task::new("Fix the fence".to_string()))
+            .unwrap();
+        home.complete_task(0).unwrap();
+
+        let work = db.task_lists.get_mut("Work").unwrap();
+        work.add_task(Task::new("Write report".to_string()))
+            .unwrap();
+        work.complete_task(0).unwrap();
+
+        assert_eq!(
+            db.stats(),
+            Stats {
+                task_lists: vec![
+                    (
+                        "Home".to_string(),
+                        Counts {
+                            complete: 1,
+                            incomplete: 1
+                        }
+                    ),
+                    (
+                        "Work".to_string(),
+                        Counts {
+                            complete: 1,
+                            incomplete: 0
+                        }
+                    ),
+                    ("Someday".to_string(), Counts::default()),
+                ],
+                total: Counts {
+                    complete: 2,
+                    incomplete: 1
+                },
+            }
+        );
+    }
+
+    fn db_with_due_dates() -> Db {
+        let mut db = db_with_lists(&["Home", "Work"]);
+
+        let home = db.task_lists.get_mut("Home").unwrap();
+        home.add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        home.add_task(Task::new("Fix the fence".to_string()))
+            .unwrap();
+        home.add_task(Task::new("Call mum".to_string())).unwrap();
+        home.add_task(Task::new("Post letter".to_string())).unwrap();
+        home.set_due(0, Some(NaiveDate::from_ymd(2024, 5, 1)))
+            .unwrap();
+        home.set_due(1, Some(NaiveDate::from_ymd(2024, 4, 30)))
+            .unwrap();
+        home.set_due(3, Some(NaiveDate::from_ymd(2024, 4, 1)))
+            .unwrap();
+        home.complete_task(3).unwrap();
+
+        let work = db.task_lists.get_mut("Work").unwrap();
+        work.add_task(Task::new("Write report".to_string()))
+            .unwrap();
+        work.add_task(Task::new("Book flights".to_string()))
+            .unwrap();
+        work.set_due(0, Some(NaiveDate::from_ymd(2024, 5, 1)))
+            .unwrap();
+        work.set_due(1, Some(NaiveDate::from_ymd(2024, 5, 2)))
+            .unwrap();
+
+        db
+    }
+
+    #[test]
+    fn tasks_due_on_date_are_found_across_all_task_lists() {
+        let db = db_with_due_dates();
+
+        let matches: Vec<_> = db
+            .due_on(NaiveDate::from_ymd(2024, 5, 1))
+            .into_iter()
+            .map(|(name, id, _)| (name, id))
+            .collect();
+
+        assert_eq!(matches, [("Home".to_string(), 0), ("Work".to_string(), 0)]);
+    }
+
+    #[test]
+    fn tasks_due_between_dates_include_both_endpoints() {
+        let db = db_with_due_dates();
+
+        let matches: Vec<_> = db
+            .due_between(
+                NaiveDate::from_ymd(2024, 4, 30),
+                NaiveDate::from_ymd(2024, 5, 1),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(name, id, _)| (name, id))
+            .collect();
+
+        assert_eq!(
+            matches,
+            [
+                ("Home".to_string(), 0),
+                ("Home".to_string(), 1),
+                ("Work".to_string(), 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn tasks_due_between_dates_in_wrong_order_gives_error() {
+        let db = db_with_due_dates();
+
+        assert_eq!(
+            db.due_between(
+                NaiveDate::from_ymd(2024, 5, 2),
+                NaiveDate::from_ymd(2024, 5, 1)
+            ),
+            Err(Error::InvalidDateRange {
+                start: NaiveDate::from_ymd(2024, 5, 2),
+                end: NaiveDate::from_ymd(2024, 5, 1)
+            })
+        );
+    }
+
+    #[test]
+    fn cancelled_tasks_are_left_out_of_stats_and_tag_counts() {
+        let mut db = Db::default();
+
+        let tasks = db.get_current_task_list_mut().unwrap();
+        let mut dropped = Task::new("Dropped idea".to_string());
+        dropped.add_tag("ideas".to_string());
+        tasks.add_task(dropped).unwrap();
+        tasks
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        tasks.cancel_task(0).unwrap();
+        tasks.complete_task(1).unwrap();
+
+        assert_eq!(
+            db.stats().total,
+            Counts {
+                complete: 1,
+                incomplete: 0
+            }
+        );
+        assert_eq!(db.tag_counts(), [("(untagged)".to_string(), 0, 1)]);
+    }
+
+    #[test]
+    fn cancelled_tasks_are_never_overdue() {
+        let mut db = Db::default();
+
+        let tasks = db.get_current_task_list_mut().unwrap();
+        tasks
+            .add_task(Task::new("Dropped idea".to_string()))
+            .unwrap();
+        tasks
+            .set_due(0, Some(NaiveDate::from_ymd(2024, 4, 1)))
+            .unwrap();
+        tasks.cancel_task(0).unwrap();
+
+        assert!(db.overdue(NaiveDate::from_ymd(2024, 5, 1)).is_empty());
+    }
+
+    #[test]
+    fn overdue_tasks_are_incomplete_and_due_strictly_before_today() {
+        let db = db_with_due_dates();
+
+        let matches: Vec<_> = db
+            .overdue(NaiveDate::from_ymd(2024, 5, 1))
+            .into_iter()
+            .map(|(name, id, _)| (name, id))
+            .collect();
+
+        assert_eq!(matches, [("Home".to_string(), 1)]);
+    }
+
+    #[test]
+    fn tasks_with_reminders_due_have_one_on_or_before_today() {
+        let mut db = db_with_lists(&["Home", "Work"]);
+
+        for (name, title, reminder) in &[
+            ("Home", "Water plants", (2024, 4, 30)),
+            ("Home", "Fix the fence", (2024, 5, 2)),
+            ("Work", "Write report", (2024, 5, 1)),
+        ] {
+            let task_list = db.get_task_list_mut(name).unwrap();
+            let id = task_list.add_task(Task::new(title.to_string())).unwrap();
+            let (year, month, day) = *reminder;
+            task_list
+                .add_reminder(id, NaiveDate::from_ymd(year, month, day))
+                .unwrap();
+        }
+
+        let matches: Vec<_> = db
+            .with_reminders_due(NaiveDate::from_ymd(2024, 5, 1))
+            .into_iter()
+            .map(|(name, id, _)| (name, id))
+            .collect();
+
+        assert_eq!(matches, [("Home".to_string(), 0), ("Work".to_string(), 0)]);
+    }
+
+    #[test]
+    fn tasks_completed_since_date_are_grouped_by_list_and_most_recent_first() {
+        let mut db = db_with_lists(&["Home", "Work"]);
+
+        let work = db.get_task_list_mut("Work").unwrap();
+        work.insert_task_with_id(
+            0,
+            Task::with_times(
+                "Write report",
+                Status::Complete,
+                None,
+                Some("2024-05-01T09:00:00Z"),
+            ),
+        )
+        .unwrap();
+        work.insert_task_with_id(
+            1,
+            Task::with_times(
+                "Book flights",
+                Status::Complete,
+                None,
+                Some("2024-05-02T09:00:00Z"),
+            ),
+        )
+        .unwrap();
+
+        let home = db.get_task_list_mut("Home").unwrap();
+        home.insert_task_with_id(
+            0,
+            Task::with_times(
+                "Buy some milk",
+                Status::Complete,
+                None,
+                Some("2024-04-30T23:00:00Z"),
+            ),
+        )
+        .unwrap();
+        home.insert_task_with_id(
+            1,
+            Task::with_times(
+                "Fix the fence",
+                Status::Complete,
+                None,
+                Some("2024-05-01T08:00:00Z"),
+            ),
+        )
+        .unwrap();
+        home.insert_task_with_id(
+            2,
+            Task::with_times("Call mum", Status::Complete, None, None),
+        )
+        .unwrap();
+        home.insert_task_with_id(3, Task::new("Water plants".to_string()))
+            .unwrap();
+        home.insert_task_with_id(
+            4,
+            Task::with_times(
+                "Post letter",
+                Status::Complete,
+                None,
+                Some("2024-05-03T10:00:00Z"),
+            ),
+        )
+        .unwrap();
+
+        let matches: Vec<_> = db
+            .completed_since(Utc.ymd(2024, 5, 1))
+            .into_iter()
+            .map(|(name, id, _)| (name, id))
+            .collect();
+
+        assert_eq!(
+            matches,
+            [
+                ("Home".to_string(), 4),
+                ("Home".to_string(), 1),
+                ("Work".to_string(), 1),
+                ("Work".to_string(), 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn tasks_completed_since_date_go_by_the_date_in_its_time_zone() {
+        let mut db = Db::default();
+        let task_list = db.get_current_task_list_mut().unwrap();
+
+        // Already 1 May ten hours ahead of UTC.
+        task_list
+            .insert_task_with_id(
+                0,
+                Task::with_times(
+                    "Buy some milk",
+                    Status::Complete,
+                    None,
+                    Some("2024-04-30T15:00:00Z"),
+                ),
+            )
+            .unwrap();
+        // Still 30 April ten hours ahead of UTC.
+        task_list
+            .insert_task_with_id(
+                1,
+                Task::with_times(
+                    "Fix the fence",
+                    Status::Complete,
+                    None,
+                    Some("2024-04-30T13:00:00Z"),
+                ),
+            )
+            .unwrap();
+
+        let ids = |since| -> Vec<_> {
+            db.completed_since(since)
+                .into_iter()
+                .map(|(_, id, _)| id)
+                .collect()
+        };
+
+        assert_eq!(ids(FixedOffset::east(10 * 60 * 60).ymd(2024, 5, 1)), [0]);
+        assert!(ids(FixedOffset::east(0).ymd(2024, 5, 1)).is_empty());
+    }
+
+    #[test]
+    fn completed_tasks_can_be_removed_from_every_task_list() {
+        let mut db = db_with_lists(&["Home", "Work", "Garden"]);
+
+        for (name, title, complete) in &[
+            ("Home", "Fix the fence", true),
+            ("Home", "Buy some milk", false),
+            ("Work", "Write report", true),
+            ("Work", "Book flights", true),
+            ("Garden", "Water plants", false),
+        ] {
+            let task_list = db.get_task_list_mut(name).unwrap();
+            let id = task_list.add_task(Task::new(title.to_string())).unwrap();
+
+            if *complete {
+                task_list.complete_task(id).unwrap();
+            }
+        }
+
+        assert_eq!(db.remove_completed_everywhere(), 3);
+
+        let titles: Vec<_> = db
+            .iter_tasks()
+            .map(|(name, _, task)| (name, task.title()))
+            .collect();
+
+        assert_eq!(
+            titles,
+            [("Home", "Buy some milk"), ("Garden", "Water plants")]
+        );
+    }
+
+    #[test]
+    fn archiving_moves_completed_tasks_out_of_every_task_list() {
+        let mut db = db_with_lists(&["Home", "Work"]);
+
+        for (name, title) in &[
+            ("Home", "Fix the fence"),
+            ("Home", "Water plants"),
+            ("Work", "Write report"),
+        ] {
+            db.get_task_list_mut(name)
+                .unwrap()
+                .add_task(Task::new(title.to_string()))
+                .unwrap();
+        }
+
+        db.get_task_list_mut("Home")
+            .unwrap()
+            .complete_task(0)
+            .unwrap();
+        db.get_task_list_mut("Work")
+            .unwrap()
+            .complete_task(0)
+            .unwrap();
+
+        let archived = db.archive_completed();
+
+        assert_eq!(
+            archived
+                .iter()
+                .map(|archived| (archived.list.as_str(), archived.title.as_str()))
+                .collect::<Vec<_>>(),
+            [("Home", "Fix the fence"), ("Work", "Write report")]
+        );
+        assert!(archived.iter().all(|archived| archived.completed.is_some()));
+
+        assert_eq!(
+            db.iter_tasks()
+                .map(|(name, id, task)| (name, id, task.title()))
+                .collect::<Vec<_>>(),
+            [("Home", 1, "Water plants")]
+        );
+    }
+
+    #[test]
+    fn next_actionable_task_has_highest_priority() {
+        let mut db = Db::default();
+        let today = NaiveDate::from_ymd(2024, 5, 1);
+
+        let tasks = db.get_current_task_list_mut().unwrap();
+        tasks
+            .add_task(Task::new("Water plants".to_string()))
+            .unwrap();
+        tasks.add_task(Task::new("File taxes".to_string())).unwrap();
+        tasks
+            .add_task(Task::new("Renew passport".to_string()))
+            .unwrap();
+        tasks.add_task(Task::new("Sort socks".to_string())).unwrap();
+        tasks.set_priority(1, Priority::High).unwrap();
+        tasks.set_priority(2, Priority::High).unwrap();
+        tasks.set_priority(3, Priority::Low).unwrap();
+
+        let (name, id, task) = db.next_actionable(today, false).unwrap();
+        assert_eq!(name, "Tasks");
+        assert_eq!(id, 1);
+        assert_eq!(task.to_string(), "• File taxes");
+    }
+
+    #[test]
+    fn next_actionable_task_is_due_soonest_among_equal_priorities() {
+        let mut db = Db::default();
+        let today = NaiveDate::from_ymd(2024, 5, 1);
+
+        let tasks = db.get_current_task_list_mut().unwrap();
+        tasks
+            .add_task(Task::new("Water plants".to_string()))
+            .unwrap();
+        tasks.add_task(Task::new("File taxes".to_string())).unwrap();
+        tasks
+            .add_task(Task::new("Renew passport".to_string()))
+            .unwrap();
+        tasks
+            .set_due(1, Some(NaiveDate::from_ymd(2024, 6, 1)))
+            .unwrap();
+        tasks
+            .set_due(2, Some(NaiveDate::from_ymd(2024, 5, 1)))
+            .unwrap();
+
+        assert_eq!(db.next_actionable(today, false).unwrap().1, 2);
+    }
+
+    #[test]
+    fn next_actionable_task_is_overdue_before_due_later() {
+        let mut db = Db::default();
+        let today = NaiveDate::from_ymd(2024, 5, 1);
+
+        let tasks = db.get_current_task_list_mut().unwrap();
+        tasks
+            .add_task(Task::new("Water plants".to_string()))
+            .unwrap();
+        tasks.add_task(Task::new("File taxes".to_string())).unwrap();
+        tasks.set_due(0, Some(today + Duration::days(3))).unwrap();
+        tasks.set_due(1, Some(today - Duration::days(2))).unwrap();
+
+        assert_eq!(db.next_actionable(today, false).unwrap().1, 1);
+    }
+
+    #[test]
+    fn next_actionable_task_is_never_complete() {
+        let mut db = Db::default();
+        let today = NaiveDate::from_ymd(2024, 5, 1);
+
+        let tasks = db.get_current_task_list_mut().unwrap();
+        tasks
+            .add_task(Task::new("Water plants".to_string()))
+            .unwrap();
+        tasks.add_task(Task::new("File taxes".to_string())).unwrap();
+        tasks.set_priority(0, Priority::High).unwrap();
+        tasks.complete_task(0).unwrap();
+
+        assert_eq!(db.next_actionable(today, false).unwrap().1, 1);
+
+        db.get_current_task_list_mut()
+            .unwrap()
+            .complete_task(1)
+            .unwrap();
+
+        assert_eq!(db.next_actionable(today, false), None);
+    }
+
+    #[test]
+    fn next_actionable_task_is_never_blocked() {
+        let mut db = Db::default();
+        let today = NaiveDate::from_ymd(2024, 5, 1);
+
+        let tasks = db.get_current_task_list_mut().unwrap();
+        tasks.add_task(Task::new("Buy paint".to_string())).unwrap();
+        tasks
+            .add_task(Task::new("Paint the fence".to_string()))
+            .unwrap();
+        tasks
+            .add_task(Task::new("Sand the fence".to_string()))
+            .unwrap();
+        tasks.set_priority(1, Priority::High).unwrap();
+        tasks.set_due(1, Some(today)).unwrap();
+        tasks.set_due(2, Some(today + Duration::days(1))).unwrap();
+        tasks.add_dependency(1, 0).unwrap();
+
+        assert_eq!(db.next_actionable(today, false).unwrap().1, 2);
+
+        db.get_current_task_list_mut()
+            .unwrap()
+            .complete_task(0)
+            .unwrap();
+
+        assert_eq!(db.next_actionable(today, false).unwrap().1, 1);
+    }
+
+    #[test]
+    fn next_actionable_task_is_never_cancelled() {
+        let mut db = Db::default();
+        let today = NaiveDate::from_ymd(2024, 5, 1);
+
+        let tasks = db.get_current_task_list_mut().unwrap();
+        tasks
+            .add_task(Task::new("Dropped idea".to_string()))
+            .unwrap();
+        tasks.cancel_task(0).unwrap();
+
+        assert_eq!(db.next_actionable(today, false), None);
+    }
+
+    #[test]
+    fn next_actionable_task_can_come_from_any_task_list() {
+        let mut db = Db::default();
+        let today = NaiveDate::from_ymd(2024, 5, 1);
+
+        db.get_current_task_list_mut()
+            .unwrap()
+            .add_task(Task::new("Water plants".to_string()))
+            .unwrap();
+
+        db.add_task_list("Work".to_string(), {
+            let mut tl = TaskList::default();
+            tl.add_task(Task::new("Write report".to_string())).unwrap();
+            tl.set_priority(0, Priority::High).unwrap();
+
+            tl
+        });
+
+        assert_eq!(db.next_actionable(today, false).unwrap().0, "Tasks");
+        assert_eq!(db.next_actionable(today, true).unwrap().0, "Work");
+    }
+
+    #[test]
+    fn db_can_be_restored_from_backup() {
+        let mut db = Db::default();
+        db.get_current_task_list_mut()
+            .unwrap()
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        db.add_task_list("Work".to_string(), TaskList::default());
+
+        let mut backup = db_with_lists(&["Home", "Garden"]);
+        backup.set_current("Garden".to_string()).unwrap();
+        backup
+            .get_current_task_list_mut()
+            .unwrap()
+            .add_task(Task::new("Mow the lawn".to_string()))
+            .unwrap();
+
+        let old_db = db.clone();
+
+        assert_eq!(db.restore(backup.clone()), Ok(old_db));
+        assert_eq!(db, backup);
+    }
+
+    #[test]
+    fn restoring_backup_without_current_task_list_gives_error() {
+        let mut db = Db::default();
+
+        assert_eq!(
+            db.restore(db_with_lists(&["Home"])),
+            Err(Error::NoCurrentTaskList)
+        );
+        assert_eq!(db, Db::default());
+    }
+
+    #[test]
+    fn db_can_be_exported_as_markdown() {
+        let mut db = db_with_lists(&["Home", "Work", "Someday"]);
+
+        let home = db.task_lists.get_mut("Home").unwrap();
+        home.add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        home.add_task(Task::new("Fix the fence".to_string()))
+            .unwrap();
+        home.complete_task(0).unwrap();
+
+        let work = db.task_lists.get_mut("Work").unwrap();
+        work.add_task(Task::new("Write report".to_string()))
+            .unwrap();
+
+        db.set_current("Work".to_string()).unwrap();
+
+        assert_eq!(
+            db.to_markdown(),
+            "\
+## Home
+
+- [x] Buy some milk
+- [ ] Fix the fence
+
+## Work _(current)_
+
+- [ ] Write report
+
+## Someday
+"
+        );
+    }
+
+    #[test]
+    fn db_round_trips_through_csv() {
+        let mut db = db_with_lists(&["Home", "Work"]);
+        db.set_current("Home".to_string()).unwrap();
+
+        let home = db.task_lists.get_mut("Home").unwrap();
+        home.add_task(Task::new("Buy milk, eggs and \"fancy\" bread".to_string()))
+            .unwrap();
+        home.add_task(Task::new("Fix the fence".to_string()))
+            .unwrap();
+        home.add_task(Task::new("Call mum".to_string())).unwrap();
+        home.remove_task(1).unwrap();
+        home.complete_task(0).unwrap();
+
+        let work = db.task_lists.get_mut("Work").unwrap();
+        work.add_task(Task::new("Write report".to_string()))
+            .unwrap();
+
+        let csv = db.to_csv();
+        let imported = Db::from_csv(csv.as_bytes()).unwrap();
+
+        // Timestamps aren’t part of the CSV, so we compare everything else.
+        assert_eq!(imported.to_csv(), csv);
+        assert_eq!(imported.to_string(), db.to_string());
+        assert_eq!(imported.current_list, db.current_list);
+    }
+
+    #[test]
+    fn csv_without_tasks_gives_default_db() {
+        assert_eq!(
+            Db::from_csv("list,id,status,title\n".as_bytes()).unwrap(),
+            Db::default()
+        );
+    }
+
+    #[test]
+    fn csv_with_duplicate_task_ids_gives_error() {
+        let csv = "\
+list,id,status,title
+Tasks,0,Incomplete,Buy some milk
+Tasks,0,Complete,Learn Haskell
+";
+
+        assert!(matches!(
+            Db::from_csv(csv.as_bytes()),
+            Err(spreadsheet::Error::DuplicateTaskId { list, id: 0 }) if list == "Tasks"
+        ));
+    }
+
+    #[test]
+    fn csv_with_unknown_status_gives_error() {
+        let csv = "\
+list,id,status,title
+Tasks,0,Done,Buy some milk
+";
+
+        assert!(matches!(
+            Db::from_csv(csv.as_bytes()),
+            Err(spreadsheet::Error::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn task_titles_line_up_across_task_lists() {
+        let mut db = db_with_lists(&["Home", "Work"]);
+        db.set_current("Home".to_string()).unwrap();
+
+        let home = db.task_lists.get_mut("Home").unwrap();
+        home.add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+
+        let work = db.task_lists.get_mut("Work").unwrap();
+        for i in 0..1001 {
+            work.add_task(Task::new(format!("Task {}", i))).unwrap();
+        }
+        work.remove_range(1..=999);
+        work.set_priority(1000, Priority::High).unwrap();
+
+        assert_eq!(
+            db.to_string(),
+            "\
+Home (current) [0/1]
+  [   0] !  • Buy some milk
+
+Work [0/2]
+  [1000] !! • Task 1000
+  [   0] !  • Task 0"
+        );
+    }
+
+    #[test]
+    fn task_titles_are_wrapped_within_their_task_list() {
+        let mut db = Db::default();
+        db.get_current_task_list_mut()
+            .unwrap()
+            .add_task(Task::new(
+                "Write the quarterly report for the board meeting".to_string(),
+            ))
+            .unwrap();
+
+        assert_eq!(
+            db.render_wrapped(32),
+            "\
+Tasks (current) [0/1]
+  [  0] ! • Write the quarterly
+            report for the board
+            meeting"
+        );
+    }
+
+    #[test]
+    fn db_round_trips_through_toml() {
+        let mut db = db_with_lists(&["Work", "Home"]);
+        db.set_current("Home".to_string()).unwrap();
+
+        let home = db.task_lists.get_mut("Home").unwrap();
+        let mut task = Task::new("Fix the fence".to_string());
+        task.set_due(Some(NaiveDate::from_ymd(2024, 5, 1)));
+        task.add_tag("weekend".to_string());
+        home.add_task(Task::new("Water plants".to_string()))
+            .unwrap();
+        home.add_task(task).unwrap();
+        home.complete_task(0).unwrap();
+        home.move_up(1).unwrap();
+
+        let toml = db.to_toml();
+        let imported = Db::from_toml(&toml).unwrap();
+
+        assert_eq!(imported, db);
+        assert_eq!(imported.list_names(), ["Work", "Home"]);
+        assert_eq!(
+            imported.task_lists["Home"]
+                .iter()
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>(),
+            [1, 0]
+        );
+    }
+
+    #[test]
+    fn json_keeps_the_order_of_task_lists() {
+        let db = db_with_lists(&["Work", "Home", "Someday"]);
+
+        assert_eq!(
+            Db::from_json(&db.to_json()).unwrap().list_names(),
+            ["Work", "Home", "Someday"]
+        );
+    }
+
+    #[test]
+    fn exported_json_contains_current_schema_version() {
+        let json: serde_json::Value = serde_json::from_str(&Db::default().to_json()).unwrap();
+
+        assert_eq!(json["version"], schema::CURRENT_VERSION);
+    }
+
+    #[test]
+    fn json_with_current_schema_version_is_imported_as_is() {
+        let mut db = Db::default();
+        db.get_current_task_list_mut()
+            .unwrap()
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+
+        assert_eq!(Db::from_json(&db.to_json()).unwrap(), db);
+    }
+
+    #[test]
+    fn json_from_before_schema_versions_is_migrated() {
+        let json = r#"{
+            "task_lists": {
+                "Tasks": {
+                    "tasks": {
+                        "0": { "title": "Buy some milk", "status": "Incomplete" }
+                    }
+                }
+            },
+            "current_list": "Tasks"
+        }"#;
+
+        let mut expected = Db::default();
+        expected
+            .get_current_task_list_mut()
+            .unwrap()
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+
+        // Compare the rendered databases, since the migrated task has no creation time.
+        assert_eq!(
+            Db::from_json(json).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    fn json_with_newer_schema_version_is_rejected() {
+        let json = r#"{ "version": 1000, "task_lists": {}, "current_list": "" }"#;
+
+        let error = Db::from_json(json).unwrap_err();
+
+        assert!(matches!(
+            error,
+            schema::Error::UnsupportedVersion {
+                found: 1000,
+                supported: schema::CURRENT_VERSION,
+            }
+        ));
+        assert!(error.to_string().contains("please upgrade t"));
+    }
 
     #[test]
-    fn task_lists_can_be_added() {
+    fn display_implementation_shows_all_task_lists_and_current_task_list() {
         let mut db = Db::default();
 
-        let shopping_list = {
+        let default_task_list = db.get_current_task_list_mut().unwrap();
+
+        default_task_list
+            .add_task(Task::new("Buy laptop sleeve".to_string()))
+            .unwrap();
+        default_task_list
+            .add_task(Task::new("Vacuum".to_string()))
+            .unwrap();
+
+        let novel_tasks = {
             let mut tl = TaskList::default();
-            tl.add_task(Task::new("Milk".to_string()));
-            tl.add_task(Task::new("Frozen pizza".to_string()));
-            tl.add_task(Task::new("Yoghurt".to_string()));
+            tl.add_task(Task::new("Write acknowledgements".to_string()))
+                .unwrap();
+            tl.add_task(Task::new("Follow up publisher".to_string()))
+                .unwrap();
+            tl.add_task(Task::new("Do full read-through".to_string()))
+                .unwrap();
 
             tl
         };
 
-        let school_tasks = {
+        let useless_skills_tasks = {
             let mut tl = TaskList::default();
-            tl.add_task(Task::new("Finish history homework".to_string()));
-            tl.add_task(Task::new("Write english essay".to_string()));
-            tl.add_task(Task::new("Study for chemistry test".to_string()));
+            tl.add_task(Task::new("Study next 100 digits of π".to_string()))
+                .unwrap();
+            tl.add_task(Task::new("Memorise 100 biggest cities".to_string()))
+                .unwrap();
+            tl.add_task(Task::new("Learn to speak backwards".to_string()))
+                .unwrap();
 
             tl
         };
 
-        db.add_task_list("Shopping List".to_string(), shopping_list.clone());
-        db.add_task_list("School".to_string(), school_tasks.clone());
+        db.add_task_list("Novel".to_string(), novel_tasks);
+        db.add_task_list("Useless skills".to_string(), useless_skills_tasks);
+
+        db.set_current("Novel".to_string()).unwrap();
 
         assert_eq!(
-            db,
-            Db {
-                task_lists: {
-                    let mut task_lists = IndexMap::new();
-                    task_lists.insert("Tasks".to_string(), TaskList::default());
-                    task_lists.insert("Shopping List".to_string(), shopping_list);
-                    task_lists.insert("School".to_string(), school_tasks);
+            format!("{}", db),
+            "\
+Tasks [0/2]
+  [  0] ! • Buy laptop sleeve
+  [  1] ! • Vacuum
+
+Novel (current) [0/3]
+  [  0] ! • Write acknowledgements
+  [  1] ! • Follow up publisher
+  [  2] ! • Do full read-through
+
+Useless skills [0/3]
+  [  0] ! • Study next 100 digits of π
+  [  1] ! • Memorise 100 biggest cities
+  [  2] ! • Learn to speak backwards"
+        );
+    }
 
-                    task_lists
-                },
-                current_list: "Tasks".to_string(),
-            }
+    #[test]
+    fn display_implementation_shows_note_for_empty_task_lists() {
+        let db = Db::default();
+
+        assert_eq!(
+            format!("{}", db),
+            "\
+Tasks (current) [0/0]
+  No tasks have been added to this task list yet"
         );
     }
 
     #[test]
-    fn task_lists_can_be_removed() {
+    fn display_implementation_shows_note_for_empty_db() {
+        let db = Db::empty();
+
+        assert_eq!(format!("{}", db), "No task lists have been added yet");
+    }
+
+    #[test]
+    fn tags_are_rendered_in_their_color() {
         let mut db = Db::default();
+        let mut task = Task::new("Fix the fence".to_string());
+        task.add_tag("home".to_string());
+        task.add_tag("weekend".to_string());
+        db.get_current_task_list_mut()
+            .unwrap()
+            .add_task(task)
+            .unwrap();
+        db.set_tag_color("home".to_string(), Color::Blue);
 
-        db.add_task_list("Errands".to_string(), TaskList::default());
-        assert_eq!(db.task_lists.len(), 2); // 2 because there is also the default task list.
+        assert_eq!(db.tag_color("home"), Some(Color::Blue));
+        assert_eq!(db.tag_color("weekend"), None);
 
-        db.remove_task_list("Errands".to_string()).unwrap();
-        assert_eq!(db.task_lists.len(), 1);
+        let rendered = db.render(&RenderOptions {
+            use_color: true,
+            ..RenderOptions::default()
+        });
+
+        assert!(rendered.contains("\x1b[34m#home\x1b[0m #weekend"));
+        assert!(db.to_string().contains("Fix the fence #home #weekend"));
     }
 
     #[test]
-    fn trying_to_remove_non_existent_task_list_gives_error() {
+    fn tag_colors_given_when_rendering_take_precedence_over_saved_ones() {
         let mut db = Db::default();
+        let mut task = Task::new("Fix the fence".to_string());
+        task.add_tag("home".to_string());
+        task.add_tag("weekend".to_string());
+        db.get_current_task_list_mut()
+            .unwrap()
+            .add_task(task)
+            .unwrap();
+        db.set_tag_color("home".to_string(), Color::Blue);
+        db.set_tag_color("weekend".to_string(), Color::Green);
+
+        let rendered = db.render(&RenderOptions {
+            use_color: true,
+            tag_colors: vec![("weekend".to_string(), Color::Red)]
+                .into_iter()
+                .collect(),
+            ..RenderOptions::default()
+        });
+
+        assert!(rendered.contains("\x1b[34m#home\x1b[0m \x1b[31m#weekend\x1b[0m"));
+    }
+
+    #[test]
+    fn renamed_tag_keeps_its_color() {
+        let mut db = Db::default();
+        let mut task = Task::new("Fix the fence".to_string());
+        task.add_tag("home".to_string());
+        db.get_current_task_list_mut()
+            .unwrap()
+            .add_task(task)
+            .unwrap();
+        db.set_tag_color("home".to_string(), Color::Blue);
 
+        db.rename_tag("home", "house");
+
+        assert_eq!(db.tag_color("home"), None);
+        assert_eq!(db.tag_color("house"), Some(Color::Blue));
+    }
+
+    #[test]
+    fn summary_counts_tasks_across_every_task_list() {
         assert_eq!(
-            db.remove_task_list("Foo Bar Baz".to_string()),
-            Err(Error::NonExistentTaskList("Foo Bar Baz".to_string()))
+            db_with_due_dates().summary(),
+            "6 tasks across 2 lists, 1 complete"
         );
     }
 
     #[test]
-    fn trying_to_remove_current_task_list_gives_error() {
+    fn summary_of_db_without_tasks_says_so() {
+        assert_eq!(db_with_lists(&["Home", "Work"]).summary(), "No tasks");
+        assert_eq!(Db::empty().summary(), "No tasks");
+    }
+
+    #[test]
+    fn summary_is_singular_for_one_task_in_one_list() {
         let mut db = Db::default();
+        db.get_current_task_list_mut()
+            .unwrap()
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
 
-        assert_eq!(db.current_list, "Tasks".to_string());
+        assert_eq!(db.summary(), "1 task across 1 list, 0 complete");
+    }
+
+    #[test]
+    fn summary_is_rendered_last_after_a_blank_line() {
+        let db = db_with_due_dates();
+
+        let options = RenderOptions {
+            summary: true,
+            ..RenderOptions::default()
+        };
 
         assert_eq!(
-            db.remove_task_list("Tasks".to_string()),
-            Err(Error::CannotRemoveCurrentTaskList("Tasks".to_string()))
+            db.render(&options),
+            format!("{}\n\n6 tasks across 2 lists, 1 complete", db)
         );
     }
 
     #[test]
-    fn task_lists_can_be_renamed() {
-        let mut db = Db::default();
+    fn rendering_with_date_marks_overdue_and_due_today_tasks() {
+        let db = db_with_due_dates();
 
-        let work_tasks = {
-            let mut tl = TaskList::default();
-            tl.add_task(Task::new("Finish spreadsheet".to_string()));
-            tl.add_task(Task::new("Write report".to_string()));
+        assert_eq!(
+            db.render_with_date(NaiveDate::from_ymd(2024, 5, 1), false),
+            "\
+Home [1/4]
+  [  0] ! • Buy some milk (due 2024-05-01) (due today)
+  [  1] ! • Fix the fence (due 2024-04-30) (overdue)
+  [  2] ! • Call mum
+  [  3] ! – Post letter (due 2024-04-01)
+
+Work [0/2]
+  [  0] ! • Write report (due 2024-05-01) (due today)
+  [  1] ! • Book flights (due 2024-05-02)"
+        );
+    }
 
-            tl
+    #[test]
+    fn overdue_markers_are_colored_when_using_color() {
+        let db = db_with_due_dates();
+        let rendered = db.render_with_date(NaiveDate::from_ymd(2024, 5, 1), true);
+
+        assert!(
+            rendered.contains("\x1b[31m(overdue)\x1b[0m"),
+            "{}",
+            rendered
+        );
+        assert!(
+            rendered.contains("\x1b[33m(due today)\x1b[0m"),
+            "{}",
+            rendered
+        );
+    }
+
+    #[test]
+    fn rendering_without_date_marks_nothing() {
+        let rendered = db_with_due_dates().to_string();
+
+        assert!(!rendered.contains("overdue"), "{}", rendered);
+        assert!(!rendered.contains("due today"), "{}", rendered);
+    }
+
+    #[test]
+    fn rendering_fewer_task_lists_than_the_maximum_shows_all_of_them() {
+        let db = db_with_lists(&["Home", "Work"]);
+
+        let options = RenderOptions {
+            max_lists: Some(3),
+            ..RenderOptions::default()
         };
 
-        db.add_task_list("Wokr".to_string(), work_tasks.clone());
+        assert_eq!(db.render(&options), db.to_string());
+    }
 
-        db.rename_task_list("Wokr".to_string(), "Work".to_string())
-            .unwrap();
+    #[test]
+    fn rendering_as_many_task_lists_as_the_maximum_shows_all_of_them() {
+        let db = db_with_lists(&["Home", "Work", "Garden"]);
 
-        assert!(db.task_lists.get("Wokr").is_none());
-        assert_eq!(db.task_lists["Work"], work_tasks);
+        let options = RenderOptions {
+            max_lists: Some(3),
+            ..RenderOptions::default()
+        };
+
+        assert_eq!(db.render(&options), db.to_string());
     }
 
     #[test]
-    fn renaming_non_existent_task_list_gives_error() {
-        let mut db = Db::default();
+    fn rendering_more_task_lists_than_the_maximum_notes_how_many_are_hidden() {
+        let mut db = db_with_lists(&["Home", "Work", "Garden", "Errands"]);
+        db.set_current("Home".to_string()).unwrap();
+
+        let options = RenderOptions {
+            max_lists: Some(2),
+            ..RenderOptions::default()
+        };
 
         assert_eq!(
-            db.rename_task_list("Foo".to_string(), "Bar".to_string()),
-            Err(Error::NonExistentTaskList("Foo".to_string()))
+            db.render(&options),
+            "\
+Home (current) [0/0]
+  No tasks have been added to this task list yet
+
+Work [0/0]
+  No tasks have been added to this task list yet
+
+… and 2 more lists"
         );
     }
 
     #[test]
-    fn renaming_the_current_task_list_switches_to_it() {
-        let mut db = Db::default();
+    fn task_list_headers_show_how_many_tasks_are_complete() {
+        let mut db = db_with_lists(&["Home", "Work", "Someday"]);
+        db.set_current("Work".to_string()).unwrap();
 
-        db.rename_task_list("Tasks".to_string(), "Todo".to_string())
+        let home = db.task_lists.get_mut("Home").unwrap();
+        home.add_task(Task::new("Fix the fence".to_string()))
             .unwrap();
-        assert_eq!(db.current_list, "Todo".to_string());
-
-        db.add_task_list("Personal tasks".to_string(), TaskList::default());
-        assert_eq!(db.current_list, "Todo".to_string());
+        home.complete_task(0).unwrap();
 
-        db.rename_task_list("Personal tasks".to_string(), "Personal".to_string())
-            .unwrap();
-        assert_eq!(db.current_list, "Todo".to_string());
+        let work = db.task_lists.get_mut("Work").unwrap();
+        for title in &["Write report", "Reply to emails", "Book flights"] {
+            work.add_task(Task::new(title.to_string())).unwrap();
+        }
+        work.complete_task(0).unwrap();
+        work.complete_task(2).unwrap();
 
-        db.set_current("Personal".to_string()).unwrap();
-        assert_eq!(db.current_list, "Personal".to_string());
+        let headers: Vec<_> = db
+            .to_string()
+            .lines()
+            .filter(|line| !line.starts_with(' ') && !line.is_empty())
+            .map(str::to_string)
+            .collect();
 
-        db.rename_task_list("Personal".to_string(), "Personal tasks".to_string())
-            .unwrap();
-        assert_eq!(db.current_list, "Personal tasks".to_string());
+        assert_eq!(
+            headers,
+            ["Home [1/1]", "Work (current) [2/3]", "Someday [0/0]"]
+        );
     }
 
     #[test]
-    fn display_implementation_shows_all_task_lists_and_current_task_list() {
-        let mut db = Db::default();
+    fn subset_of_task_lists_is_displayed_in_the_order_given() {
+        let mut db = db_with_lists(&["Home", "Work", "Someday"]);
+        db.set_current("Work".to_string()).unwrap();
 
-        let default_task_list = db.get_current_task_list_mut();
+        assert_eq!(
+            db.display_subset(&["Work", "Home"]).unwrap(),
+            "\
+Work (current) [0/0]
+  No tasks have been added to this task list yet
 
-        default_task_list.add_task(Task::new("Buy laptop sleeve".to_string()));
-        default_task_list.add_task(Task::new("Vacuum".to_string()));
+Home [0/0]
+  No tasks have been added to this task list yet"
+        );
+    }
 
-        let novel_tasks = {
-            let mut tl = TaskList::default();
-            tl.add_task(Task::new("Write acknowledgements".to_string()));
-            tl.add_task(Task::new("Follow up publisher".to_string()));
-            tl.add_task(Task::new("Do full read-through".to_string()));
+    #[test]
+    fn displaying_subset_with_unknown_task_lists_names_them() {
+        let db = db_with_lists(&["Home", "Work"]);
 
-            tl
-        };
+        assert_eq!(
+            db.display_subset(&["Garden", "Home", "Errands"]),
+            Err(Error::NonExistentTaskLists(vec![
+                "Garden".to_string(),
+                "Errands".to_string()
+            ]))
+        );
+    }
 
-        let useless_skills_tasks = {
-            let mut tl = TaskList::default();
-            tl.add_task(Task::new("Study next 100 digits of π".to_string()));
-            tl.add_task(Task::new("Memorise 100 biggest cities".to_string()));
-            tl.add_task(Task::new("Learn to speak backwards".to_string()));
+    #[test]
+    fn rendering_limited_task_lists_always_shows_the_current_one() {
+        let mut db = db_with_lists(&["Home", "Work", "Garden", "Errands"]);
+        db.set_current("Garden".to_string()).unwrap();
 
-            tl
+        let options = RenderOptions {
+            max_lists: Some(1),
+            ..RenderOptions::default()
         };
 
-        db.add_task_list("Novel".to_string(), novel_tasks);
-        db.add_task_list("Useless skills".to_string(), useless_skills_tasks);
-
-        db.set_current("Novel".to_string()).unwrap();
-
         assert_eq!(
-            format!("{}", db),
+            db.render(&options),
             "\
-Tasks
-  [  0] • Buy laptop sleeve
-  [  1] • Vacuum
+Home [0/0]
+  No tasks have been added to this task list yet
 
-Novel (current)
-  [  0] • Write acknowledgements
-  [  1] • Follow up publisher
-  [  2] • Do full read-through
+Garden (current) [0/0]
+  No tasks have been added to this task list yet
 
-Useless skills
-  [  0] • Study next 100 digits of π
-  [  1] • Memorise 100 biggest cities
-  [  2] • Learn to speak backwards"
+… and 2 more lists"
         );
     }
 
     #[test]
-    fn display_implementation_shows_note_for_empty_task_lists() {
-        let db = Db::default();
+    fn current_task_list_is_highlighted_when_using_color() {
+        let mut db = db_with_lists(&["Home", "Work"]);
+        db.set_current("Work".to_string()).unwrap();
+
+        let options = RenderOptions {
+            use_color: true,
+            ..RenderOptions::default()
+        };
 
         assert_eq!(
-            format!("{}", db),
+            db.render(&options),
             "\
-Tasks (current)
+Home [0/0]
+  No tasks have been added to this task list yet
+
+Work \x1b[1;32m(current)\x1b[0m [0/0]
   No tasks have been added to this task list yet"
         );
     }
@@ -332,6 +3256,86 @@ Tasks (current)
         assert_eq!(db.current_list, "Guitar".to_string());
     }
 
+    #[test]
+    fn current_task_list_can_be_set_with_a_misspelled_name() {
+        let mut db = db_with_lists(&["Home", "Work", "Someday"]);
+
+        db.set_current("Wrk".to_string()).unwrap();
+        assert_eq!(db.current_list, "Work");
+
+        db.set_current("some".to_string()).unwrap();
+        assert_eq!(db.current_list, "Someday");
+    }
+
+    #[test]
+    fn list_names_far_from_every_task_list_do_not_match() {
+        let db = db_with_lists(&["Home", "Work"]);
+
+        assert_eq!(
+            db.resolve_list_name("Groceries"),
+            Err(Error::NonExistentTaskList("Groceries".to_string()))
+        );
+    }
+
+    #[test]
+    fn list_names_matching_several_task_lists_are_ambiguous() {
+        let db = db_with_lists(&["Work email", "Work meetings", "Home"]);
+
+        assert_eq!(
+            db.resolve_list_name("work"),
+            Err(Error::AmbiguousTaskList {
+                name: "work".to_string(),
+                candidates: vec!["Work email".to_string(), "Work meetings".to_string()],
+            })
+        );
+        assert_eq!(
+            Error::AmbiguousTaskList {
+                name: "work".to_string(),
+                candidates: vec!["Work email".to_string(), "Work meetings".to_string()],
+            }
+            .to_string(),
+            "‘work’ could refer to several task lists: Work email, Work meetings"
+        );
+    }
+
+    #[test]
+    fn exact_list_names_are_preferred_over_loose_matches() {
+        let db = db_with_lists(&["Work", "Work meetings"]);
+
+        assert_eq!(db.resolve_list_name("Work"), Ok("Work"));
+    }
+
+    #[test]
+    fn levenshtein_distance_counts_edits() {
+        assert_eq!(levenshtein("work", "wrk"), 1);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("same", "same"), 0);
+    }
+
+    #[test]
+    fn current_task_list_can_be_set_by_position() {
+        let mut db = db_with_lists(&["Home", "Work", "Someday"]);
+
+        db.set_current_by_index(2).unwrap();
+        assert_eq!(db.current_list, "Someday");
+
+        db.set_current_by_index(0).unwrap();
+        assert_eq!(db.current_list, "Home");
+    }
+
+    #[test]
+    fn setting_current_task_list_past_the_last_position_gives_error() {
+        let mut db = db_with_lists(&["Home", "Work"]);
+        db.set_current("Home".to_string()).unwrap();
+
+        assert_eq!(
+            db.set_current_by_index(2),
+            Err(Error::TaskListIndexOutOfRange { index: 2, len: 2 })
+        );
+        assert_eq!(db.current_list, "Home");
+    }
+
     #[test]
     fn setting_current_task_list_to_one_that_does_not_exist_gives_error() {
         let mut db = Db::default();
@@ -342,13 +3346,131 @@ Tasks (current)
         );
     }
 
+    #[test]
+    fn dangling_current_task_list_is_repaired_to_first_task_list() {
+        let mut db = db_with_lists(&["Home", "Work"]);
+        db.current_list = "Garden".to_string();
+
+        assert!(db.repair_current_list(DEFAULT_LIST_NAME));
+        assert_eq!(db.current_list, "Home");
+        assert!(!db.repair_current_list(DEFAULT_LIST_NAME));
+    }
+
+    #[test]
+    fn dangling_current_task_list_without_any_task_lists_is_repaired_to_default() {
+        let mut db = Db::empty();
+        db.current_list = "Garden".to_string();
+
+        assert!(db.repair_current_list(DEFAULT_LIST_NAME));
+        assert_eq!(db, Db::default());
+    }
+
+    #[test]
+    fn broken_db_reports_its_issues() {
+        let mut db: Db = serde_json::from_value(serde_json::json!({
+            "task_lists": {
+                "Home": {
+                    "tasks": {
+                        "0": {
+                            "title": " ",
+                            "status": "Incomplete",
+                            "reminders": ["2024-05-02", "2024-05-01"],
+                            "tags": ["garden", "weekend", "garden"],
+                        },
+                        "1": {
+                            "title": "Fix the fence",
+                            "status": "Incomplete",
+                            "reminders": ["2024-05-01"],
+                        },
+                    },
+                },
+            },
+            "current_list": "Garden",
+        }))
+        .unwrap();
+
+        let home = |id| ("Home".to_string(), id);
+        let (list, id) = home(0);
+
+        assert_eq!(
+            db.validate(),
+            [
+                ValidationIssue::DanglingCurrentList("Garden".to_string()),
+                ValidationIssue::BlankTitle {
+                    list: list.clone(),
+                    id,
+                },
+                ValidationIssue::UntidyReminders {
+                    list: list.clone(),
+                    id,
+                },
+                ValidationIssue::DuplicateTags {
+                    list: list.clone(),
+                    id,
+                },
+            ]
+        );
+
+        assert_eq!(
+            db.fix(DEFAULT_LIST_NAME),
+            [ValidationIssue::BlankTitle { list, id }]
+        );
+        assert_eq!(db.current_list, "Home");
+        assert_eq!(
+            db.task_lists["Home"].get(0).unwrap().tags(),
+            ["garden", "weekend"]
+        );
+    }
+
+    #[test]
+    fn db_without_task_lists_reports_it() {
+        assert_eq!(
+            Db::empty().validate(),
+            [
+                ValidationIssue::NoTaskLists,
+                ValidationIssue::DanglingCurrentList(String::new())
+            ]
+        );
+    }
+
+    #[test]
+    fn default_db_has_no_issues() {
+        assert_eq!(Db::default().validate(), []);
+    }
+
+    #[test]
+    fn list_names_are_in_display_order() {
+        let mut db = db_with_lists(&["Work", "Home", "Garden"]);
+        db.move_list_to("Garden".to_string(), 1).unwrap();
+
+        assert_eq!(db.list_names(), ["Work", "Garden", "Home"]);
+    }
+
+    #[test]
+    fn task_lists_can_be_obtained_by_name() {
+        let mut db = db_with_lists(&["Home", "Work"]);
+
+        db.get_task_list_mut("Work")
+            .unwrap()
+            .add_task(Task::new("Write report".to_string()))
+            .unwrap();
+
+        assert!(db.task_lists["Home"].is_empty());
+        assert!(!db.task_lists["Work"].is_empty());
+        assert_eq!(
+            db.get_task_list_mut("Garden"),
+            Err(Error::NonExistentTaskList("Garden".to_string()))
+        );
+    }
+
     #[test]
     fn current_task_list_can_be_obtained_and_mutated() {
         let mut db = Db::default();
 
         let mut refactoring_tasks = {
             let mut tl = TaskList::default();
-            tl.add_task(Task::new("Clean up FooBar’s Display impl".to_string()));
+            tl.add_task(Task::new("Clean up FooBar’s Display impl".to_string()))
+                .unwrap();
 
             tl
         };
@@ -359,19 +3481,15 @@ Tasks (current)
 
         db.set_current("Refactoring".to_string()).unwrap();
 
-        let current_task_list = db.get_current_task_list_mut();
+        let current_task_list = db.get_current_task_list_mut().unwrap();
         assert_eq!(current_task_list, &mut refactoring_tasks);
 
-        let current_task_list = current_task_list;
-
-        current_task_list.add_task(Task::new("Refactor foo.rs".to_string()));
+        let new_task = Task::new("Refactor foo.rs".to_string());
+        current_task_list.add_task(new_task.clone()).unwrap();
 
         assert_eq!(db.task_lists["Refactoring"], {
-            let mut tl = TaskList::default();
-            tl.add_task(Task::new("Clean up FooBar’s Display impl".to_string()));
-            tl.add_task(Task::new("Refactor foo.rs".to_string()));
-
-            tl
+            refactoring_tasks.add_task(new_task).unwrap();
+            refactoring_tasks
         });
     }
 }
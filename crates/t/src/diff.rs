@@ -0,0 +1,223 @@
+use crate::db::Db;
+use crate::task::Task;
+use indexmap::IndexMap;
+use std::fmt;
+
+/// Something that differs between two states of a database.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    TaskListAdded(String),
+    TaskListRemoved(String),
+    CurrentListChanged {
+        from: String,
+        to: String,
+    },
+    TaskAdded {
+        list: String,
+        id: u32,
+        title: String,
+    },
+    TaskRemoved {
+        list: String,
+        id: u32,
+        title: String,
+    },
+    TaskCompleted {
+        list: String,
+        id: u32,
+        title: String,
+    },
+    /// The task changed in some other way, such as being renamed, tagged or uncompleted.
+    TaskChanged {
+        list: String,
+        id: u32,
+        title: String,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TaskListAdded(name) => write!(f, "+ task list ‘{}’", name),
+            Self::TaskListRemoved(name) => write!(f, "- task list ‘{}’", name),
+            Self::CurrentListChanged { from, to } => {
+                write!(f, "~ current task list ‘{}’ → ‘{}’", from, to)
+            }
+            Self::TaskAdded { list, id, title } => write!(f, "+ {}: [{}] {}", list, id, title),
+            Self::TaskRemoved { list, id, title } => write!(f, "- {}: [{}] {}", list, id, title),
+            Self::TaskCompleted { list, id, title } => {
+                write!(f, "✓ {}: [{}] {}", list, id, title)
+            }
+            Self::TaskChanged { list, id, title } => write!(f, "~ {}: [{}] {}", list, id, title),
+        }
+    }
+}
+
+/// Works out what changed to turn one state of a database into another, so that changes can be
+/// previewed. Task lists come first, followed by tasks in the order they appear in `after`, with
+/// tasks that were removed without their ID being reused last.
+pub fn diff(before: &Db, after: &Db) -> Vec<Change> {
+    let mut changes = Vec::new();
+
+    let names_before = before.list_names();
+    let names_after = after.list_names();
+
+    for name in &names_after {
+        if !names_before.contains(name) {
+            changes.push(Change::TaskListAdded(name.to_string()));
+        }
+    }
+
+    for name in &names_before {
+        if !names_after.contains(name) {
+            changes.push(Change::TaskListRemoved(name.to_string()));
+        }
+    }
+
+    if before.current_list() != after.current_list() {
+        changes.push(Change::CurrentListChanged {
+            from: before.current_list().to_string(),
+            to: after.current_list().to_string(),
+        });
+    }
+
+    let mut tasks_before: IndexMap<(&str, u32), &Task> = before
+        .iter_tasks()
+        .map(|(name, id, task)| ((name, id), task))
+        .collect();
+
+    for (name, id, task) in after.iter_tasks() {
+        let (list, title) = (name.to_string(), task.title().to_string());
+
+        match tasks_before.shift_remove(&(name, id)) {
+            None => changes.push(Change::TaskAdded { list, id, title }),
+            // IDs are reused, so a task created at a different time is a different task that was
+            // given the ID of one that was removed.
+            Some(task_before) if task_before.created() != task.created() => {
+                changes.push(Change::TaskRemoved {
+                    list: list.clone(),
+                    id,
+                    title: task_before.title().to_string(),
+                });
+                changes.push(Change::TaskAdded { list, id, title });
+            }
+            Some(task_before) if task_before == task => {}
+            Some(task_before) if !task_before.is_complete() && task.is_complete() => {
+                changes.push(Change::TaskCompleted { list, id, title })
+            }
+            Some(_) => changes.push(Change::TaskChanged { list, id, title }),
+        }
+    }
+
+    for ((name, id), task) in tasks_before {
+        changes.push(Change::TaskRemoved {
+            list: name.to_string(),
+            id,
+            title: task.title().to_string(),
+        });
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task_list::TaskList;
+
+    fn db_with_tasks(titles: &[&str]) -> Db {
+        let mut db = Db::default();
+
+        for title in titles {
+            db.get_current_task_list_mut()
+                .unwrap()
+                .add_task(Task::new(title.to_string()))
+                .unwrap();
+        }
+
+        db
+    }
+
+    #[test]
+    fn identical_databases_have_no_changes() {
+        let db = db_with_tasks(&["Buy some milk"]);
+
+        assert_eq!(diff(&db, &db.clone()), []);
+    }
+
+    #[test]
+    fn added_removed_and_completed_tasks_are_found() {
+        let before = db_with_tasks(&["Buy some milk", "Fix the fence", "Water plants"]);
+
+        let mut after = before.clone();
+        let tasks = after.get_current_task_list_mut().unwrap();
+        tasks.remove_task(0).unwrap();
+        tasks.complete_task(1).unwrap();
+        tasks
+            .rename_task(2, "Water the plants".to_string())
+            .unwrap();
+        tasks.add_task(Task::new("Call mum".to_string())).unwrap();
+
+        assert_eq!(
+            diff(&before, &after),
+            [
+                Change::TaskCompleted {
+                    list: "Tasks".to_string(),
+                    id: 1,
+                    title: "Fix the fence".to_string(),
+                },
+                Change::TaskChanged {
+                    list: "Tasks".to_string(),
+                    id: 2,
+                    title: "Water the plants".to_string(),
+                },
+                Change::TaskRemoved {
+                    list: "Tasks".to_string(),
+                    id: 0,
+                    title: "Buy some milk".to_string(),
+                },
+                Change::TaskAdded {
+                    list: "Tasks".to_string(),
+                    id: 0,
+                    title: "Call mum".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn added_and_removed_task_lists_are_found() {
+        let mut before = Db::default();
+        before.add_task_list("Work".to_string(), TaskList::default());
+
+        let mut after = before.clone();
+        after.remove_task_list("Work".to_string(), "Tasks").unwrap();
+        after.add_task_list("Home".to_string(), TaskList::default());
+        after.set_current("Home".to_string()).unwrap();
+
+        assert_eq!(
+            diff(&before, &after),
+            [
+                Change::TaskListAdded("Home".to_string()),
+                Change::TaskListRemoved("Work".to_string()),
+                Change::CurrentListChanged {
+                    from: "Tasks".to_string(),
+                    to: "Home".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn changes_are_displayed_with_a_sign() {
+        assert_eq!(
+            Change::TaskRemoved {
+                list: "Tasks".to_string(),
+                id: 3,
+                title: "Buy some milk".to_string(),
+            }
+            .to_string(),
+            "- Tasks: [3] Buy some milk"
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use crate::db::Db;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// How many snapshots are kept before the oldest ones are discarded.
+pub const MAX_SNAPSHOTS: usize = 10;
+
+/// Previous states of the database, most recent last, so that changes can be undone.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct History {
+    snapshots: VecDeque<Db>,
+}
+
+impl History {
+    /// Records a state of the database, discarding the oldest snapshot if there are already
+    /// [`MAX_SNAPSHOTS`] of them.
+    pub fn push(&mut self, db: Db) {
+        if self.snapshots.len() == MAX_SNAPSHOTS {
+            self.snapshots.pop_front();
+        }
+
+        self.snapshots.push_back(db);
+    }
+
+    /// Removes and returns the most recently recorded state of the database.
+    pub fn pop(&mut self) -> Option<Db> {
+        self.snapshots.pop_back()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::Task;
+
+    fn db_with_task(title: &str) -> Db {
+        let mut db = Db::default();
+        db.get_current_task_list_mut()
+            .unwrap()
+            .add_task(Task::new(title.to_string()))
+            .unwrap();
+
+        db
+    }
+
+    #[test]
+    fn history_starts_empty() {
+        let mut history = History::default();
+
+        assert!(history.is_empty());
+        assert_eq!(history.pop(), None);
+    }
+
+    #[test]
+    fn snapshots_are_popped_most_recent_first() {
+        let mut history = History::default();
+        let db = db_with_task("Buy some milk");
+        history.push(Db::default());
+        history.push(db.clone());
+
+        assert_eq!(history.pop(), Some(db));
+        assert_eq!(history.pop(), Some(Db::default()));
+        assert!(history.is_empty());
+    }
+
+    #[test]
+    fn oldest_snapshots_are_discarded_past_limit() {
+        let mut history = History::default();
+        let oldest = db_with_task("Oldest");
+        history.push(oldest.clone());
+
+        for _ in 0..MAX_SNAPSHOTS {
+            history.push(Db::default());
+        }
+
+        assert_eq!(history.snapshots.len(), MAX_SNAPSHOTS);
+        assert!(!history.snapshots.contains(&oldest));
+    }
+}
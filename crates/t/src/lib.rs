@@ -1,4 +1,15 @@
+pub mod archive;
+pub mod date;
 pub mod db;
-mod status;
+pub mod diff;
+pub mod history;
+pub mod priority;
+pub mod recurrence;
+pub mod render;
+pub mod schema;
+pub mod spreadsheet;
+pub mod stats;
+pub mod status;
+pub mod storage;
 pub mod task;
 pub mod task_list;
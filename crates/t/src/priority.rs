@@ -0,0 +1,74 @@
+use serde::Deserialize;
+use serde::Serialize;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum Error {
+    #[error("unknown priority ‘{0}’ (expected ‘low’, ‘medium’ or ‘high’)")]
+    UnknownPriority(String),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Priority {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl Priority {
+    /// The marker shown in front of tasks with this priority. Markers are plain ASCII so that they
+    /// line up in any terminal.
+    pub(crate) fn marker(self) -> &'static str {
+        match self {
+            Self::Low => "",
+            Self::Medium => "!",
+            Self::High => "!!",
+        }
+    }
+}
+
+impl FromStr for Priority {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(Self::Low),
+            "medium" => Ok(Self::Medium),
+            "high" => Ok(Self::High),
+            _ => Err(Error::UnknownPriority(s.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_priority_is_medium() {
+        assert_eq!(Priority::default(), Priority::Medium);
+    }
+
+    #[test]
+    fn priorities_can_be_parsed_case_insensitively() {
+        assert_eq!("low".parse(), Ok(Priority::Low));
+        assert_eq!("Medium".parse(), Ok(Priority::Medium));
+        assert_eq!("HIGH".parse(), Ok(Priority::High));
+    }
+
+    #[test]
+    fn parsing_unknown_priority_gives_error() {
+        assert_eq!(
+            "urgent".parse::<Priority>(),
+            Err(Error::UnknownPriority("urgent".to_string()))
+        );
+    }
+
+    #[test]
+    fn priorities_are_ordered_from_low_to_high() {
+        assert!(Priority::Low < Priority::Medium);
+        assert!(Priority::Medium < Priority::High);
+    }
+}
@@ -0,0 +1,138 @@
+use chrono::Datelike;
+use chrono::Duration;
+use chrono::NaiveDate;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum Error {
+    #[error("unknown recurrence ‘{0}’ (expected ‘day’, ‘week’ or ‘month’)")]
+    UnknownRecurrence(String),
+}
+
+/// How often a recurring task comes due again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Recurrence {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl Recurrence {
+    /// Returns the date one interval after `date`. Monthly recurrences that land past the end of
+    /// the next month fall on its last day instead, so January 31 is followed by February 28 (or
+    /// 29 in leap years).
+    pub fn advance(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Daily => date + Duration::days(1),
+            Self::Weekly => date + Duration::weeks(1),
+            Self::Monthly => {
+                let (year, month) = if date.month() == 12 {
+                    (date.year() + 1, 1)
+                } else {
+                    (date.year(), date.month() + 1)
+                };
+
+                // Every month has at least 28 days, so this always finds a valid date.
+                (1..=date.day())
+                    .rev()
+                    .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+                    .unwrap()
+            }
+        }
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "day" | "daily" => Ok(Self::Daily),
+            "week" | "weekly" => Ok(Self::Weekly),
+            "month" | "monthly" => Ok(Self::Monthly),
+            _ => Err(Error::UnknownRecurrence(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Daily => f.write_str("every day"),
+            Self::Weekly => f.write_str("every week"),
+            Self::Monthly => f.write_str("every month"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn daily_recurrence_advances_by_a_day() {
+        assert_eq!(
+            Recurrence::Daily.advance(NaiveDate::from_ymd(2024, 2, 28)),
+            NaiveDate::from_ymd(2024, 2, 29)
+        );
+        assert_eq!(
+            Recurrence::Daily.advance(NaiveDate::from_ymd(2024, 12, 31)),
+            NaiveDate::from_ymd(2025, 1, 1)
+        );
+    }
+
+    #[test]
+    fn weekly_recurrence_advances_by_a_week() {
+        assert_eq!(
+            Recurrence::Weekly.advance(NaiveDate::from_ymd(2024, 5, 28)),
+            NaiveDate::from_ymd(2024, 6, 4)
+        );
+    }
+
+    #[test]
+    fn monthly_recurrence_advances_by_a_month() {
+        assert_eq!(
+            Recurrence::Monthly.advance(NaiveDate::from_ymd(2024, 5, 15)),
+            NaiveDate::from_ymd(2024, 6, 15)
+        );
+        assert_eq!(
+            Recurrence::Monthly.advance(NaiveDate::from_ymd(2024, 12, 15)),
+            NaiveDate::from_ymd(2025, 1, 15)
+        );
+    }
+
+    #[test]
+    fn monthly_recurrence_past_end_of_month_falls_on_last_day() {
+        assert_eq!(
+            Recurrence::Monthly.advance(NaiveDate::from_ymd(2023, 1, 31)),
+            NaiveDate::from_ymd(2023, 2, 28)
+        );
+        assert_eq!(
+            Recurrence::Monthly.advance(NaiveDate::from_ymd(2024, 1, 31)),
+            NaiveDate::from_ymd(2024, 2, 29)
+        );
+        assert_eq!(
+            Recurrence::Monthly.advance(NaiveDate::from_ymd(2024, 3, 31)),
+            NaiveDate::from_ymd(2024, 4, 30)
+        );
+    }
+
+    #[test]
+    fn recurrences_can_be_parsed() {
+        assert_eq!("day".parse(), Ok(Recurrence::Daily));
+        assert_eq!("Weekly".parse(), Ok(Recurrence::Weekly));
+        assert_eq!("month".parse(), Ok(Recurrence::Monthly));
+    }
+
+    #[test]
+    fn parsing_unknown_recurrence_gives_error() {
+        assert_eq!(
+            "fortnight".parse::<Recurrence>(),
+            Err(Error::UnknownRecurrence("fortnight".to_string()))
+        );
+    }
+}
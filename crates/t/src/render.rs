@@ -0,0 +1,579 @@
+use crate::status::Status;
+use chrono::NaiveDate;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+use unicode_width::UnicodeWidthStr;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum Error {
+    #[error("cannot group tasks by ‘{0}’ (expected ‘tag’)")]
+    UnknownGrouping(String),
+    #[error("cannot order tasks by ‘{0}’ (expected ‘priority’ or ‘age’)")]
+    UnknownTaskOrder(String),
+    #[error("the glyph for ‘{0}’ tasks is empty")]
+    EmptyGlyph(&'static str),
+    #[error("cannot show dates as ‘{0}’ (expected ‘absolute’ or ‘relative’)")]
+    UnknownDateStyle(String),
+    #[error("unknown color ‘{0}’ (expected one of {})", Color::NAMES.join(", "))]
+    UnknownColor(String),
+}
+
+/// Options controlling how a database or task list is rendered for display.
+///
+/// The `Display` implementations use the default options, which produce plain, uncolored output.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RenderOptions {
+    /// Whether ANSI escape codes may be used to style the output.
+    pub use_color: bool,
+    /// Whether completed tasks are rendered faintly so incomplete tasks stand out.
+    pub dim_completed: bool,
+    /// How many task lists to show. The current task list is shown even if it falls past this
+    /// limit.
+    pub max_lists: Option<usize>,
+    /// How the tasks within each task list are grouped, if at all.
+    pub group_by: Option<GroupBy>,
+    /// The order tasks are shown in within each task list.
+    pub order: TaskOrder,
+    /// Whether the output is transliterated to ASCII for terminals that can’t render anything
+    /// else. This is best-effort: accented letters lose their accents, symbols and emoji are
+    /// spelled out, and status glyphs are swapped for their closest ASCII equivalents.
+    pub ascii: bool,
+    /// How task statuses are drawn.
+    pub status_style: StatusStyle,
+    /// Whether tasks are shown with when they were created and completed.
+    pub verbose: bool,
+    /// The number of columns to wrap task titles at, if any. Wrapped lines are indented to line up
+    /// with the start of the title.
+    pub width: Option<usize>,
+    /// The date to judge whether tasks are overdue or due today by, if any. Incomplete tasks due
+    /// before this date are marked as overdue. Relative dates are counted from this date too.
+    pub today: Option<NaiveDate>,
+    /// How dates such as due dates are shown.
+    pub dates: DateStyle,
+    /// Glyphs to draw statuses with instead of the usual Unicode ones.
+    pub glyphs: Glyphs,
+    /// Whether a database is followed by a line summing up how many tasks it has.
+    pub summary: bool,
+    /// The colors tags are drawn in when color is used. Tags without a color are drawn plainly.
+    /// When a database is rendered, these are added to the colors saved in it and take precedence
+    /// over them.
+    pub tag_colors: HashMap<String, Color>,
+}
+
+impl RenderOptions {
+    /// Returns the glyph a status is drawn with. Custom glyphs replace only the Unicode glyphs, so
+    /// asking for ASCII output still gives ASCII.
+    pub(crate) fn glyph(&self, status: &Status) -> &str {
+        let custom = match self.status_style {
+            StatusStyle::Unicode => self.glyphs.get(status),
+            StatusStyle::Ascii => None,
+        };
+
+        custom.unwrap_or_else(|| status.glyph(self.status_style))
+    }
+
+    /// Formats a date in the chosen style.
+    pub(crate) fn format_date(&self, date: NaiveDate) -> String {
+        self.dates.format(date, self.today)
+    }
+
+    /// Returns the color a tag is drawn in, if it has one.
+    pub(crate) fn tag_color(&self, tag: &str) -> Option<Color> {
+        self.tag_colors.get(tag).copied()
+    }
+
+    /// Renders a tag as a `#tag` chip, colored if it has a color and color is used.
+    pub(crate) fn render_tag(&self, tag: &str) -> String {
+        let chip = format!("#{}", tag);
+
+        match self.tag_color(tag) {
+            Some(color) if self.use_color => color.paint(&chip),
+            _ => chip,
+        }
+    }
+
+    /// Returns the options for rendering something that will be indented by `columns`, so that it
+    /// still fits within the width once indented.
+    pub(crate) fn indented(&self, columns: usize) -> Self {
+        Self {
+            width: self.width.map(|width| width.saturating_sub(columns)),
+            ..self.clone()
+        }
+    }
+}
+
+/// The glyphs used to show whether a task is complete.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum StatusStyle {
+    /// A bullet (`•`) for incomplete tasks and an en dash (`–`) for complete ones.
+    #[default]
+    Unicode,
+    /// Checkboxes (`[ ]` and `[x]`) for terminals that can’t render anything but ASCII.
+    Ascii,
+}
+
+/// Glyphs for drawing statuses, such as emoji, read from the `[glyphs]` table of the config file.
+/// Statuses without a glyph here are drawn as usual.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Glyphs {
+    pub incomplete: Option<String>,
+    pub in_progress: Option<String>,
+    pub complete: Option<String>,
+    pub cancelled: Option<String>,
+}
+
+impl Glyphs {
+    fn get(&self, status: &Status) -> Option<&str> {
+        let glyph = match status {
+            Status::Incomplete => &self.incomplete,
+            Status::InProgress => &self.in_progress,
+            Status::Complete => &self.complete,
+            Status::Cancelled => &self.cancelled,
+        };
+
+        glyph.as_deref()
+    }
+
+    /// Checks that none of the glyphs are empty, since a task without a visible status would be
+    /// easy to misread.
+    pub fn validate(&self) -> Result<(), Error> {
+        let glyphs = [
+            ("incomplete", &self.incomplete),
+            ("in-progress", &self.in_progress),
+            ("complete", &self.complete),
+            ("cancelled", &self.cancelled),
+        ];
+
+        match glyphs
+            .iter()
+            .find(|(_, glyph)| glyph.as_ref().is_some_and(|glyph| glyph.trim().is_empty()))
+        {
+            Some((name, _)) => Err(Error::EmptyGlyph(name)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// How dates such as due dates and reminders are shown. This is purely a matter of display; dates
+/// are always stored as they are.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum DateStyle {
+    /// As the date itself, such as `2024-05-01`.
+    #[default]
+    Absolute,
+    /// As how far the date is from today, such as `in 3 days`.
+    Relative,
+}
+
+impl DateStyle {
+    /// Formats a date in this style. Relative dates can only be worked out knowing what day it is,
+    /// so they are shown as absolute dates if `today` isn’t given.
+    pub fn format(self, date: NaiveDate, today: Option<NaiveDate>) -> String {
+        match (self, today) {
+            (Self::Relative, Some(today)) => relative_date(date, today),
+            _ => date.to_string(),
+        }
+    }
+}
+
+impl FromStr for DateStyle {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "absolute" => Ok(Self::Absolute),
+            "relative" => Ok(Self::Relative),
+            _ => Err(Error::UnknownDateStyle(s.to_string())),
+        }
+    }
+}
+
+/// A terminal color that tags can be drawn in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+impl Color {
+    const NAMES: [&'static str; 6] = ["red", "green", "yellow", "blue", "magenta", "cyan"];
+
+    fn paint(self, s: &str) -> String {
+        let code = match self {
+            Self::Red => 31,
+            Self::Green => 32,
+            Self::Yellow => 33,
+            Self::Blue => 34,
+            Self::Magenta => 35,
+            Self::Cyan => 36,
+        };
+
+        format!("\x1b[{}m{}\x1b[0m", code, s)
+    }
+}
+
+impl FromStr for Color {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "red" => Ok(Self::Red),
+            "green" => Ok(Self::Green),
+            "yellow" => Ok(Self::Yellow),
+            "blue" => Ok(Self::Blue),
+            "magenta" => Ok(Self::Magenta),
+            "cyan" => Ok(Self::Cyan),
+            _ => Err(Error::UnknownColor(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Red => "red",
+            Self::Green => "green",
+            Self::Yellow => "yellow",
+            Self::Blue => "blue",
+            Self::Magenta => "magenta",
+            Self::Cyan => "cyan",
+        })
+    }
+}
+
+/// The order tasks are shown in. This only affects how tasks are displayed, not the order they are
+/// stored in.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum TaskOrder {
+    /// From highest to lowest priority, keeping tasks of equal priority in their usual order.
+    #[default]
+    Priority,
+    /// From oldest to newest, since IDs are reused and so don’t say how old a task is.
+    Age,
+}
+
+impl FromStr for TaskOrder {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "priority" => Ok(Self::Priority),
+            "age" => Ok(Self::Age),
+            _ => Err(Error::UnknownTaskOrder(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GroupBy {
+    /// Lists each tag as a subheading with the tasks carrying it beneath. Tasks with several tags
+    /// appear under each of them, and tasks without any tags are grouped under `(untagged)`.
+    Tag,
+}
+
+impl FromStr for GroupBy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tag" => Ok(Self::Tag),
+            _ => Err(Error::UnknownGrouping(s.to_string())),
+        }
+    }
+}
+
+pub(crate) fn transliterate_if_ascii(s: String, options: &RenderOptions) -> String {
+    if !options.ascii {
+        return s;
+    }
+
+    // We transliterate character by character rather than using `deunicode::deunicode`, since that
+    // also replaces ASCII control characters such as newlines and escape codes.
+    s.chars()
+        .map(|c| {
+            if c.is_ascii() {
+                c.to_string()
+            } else {
+                // Symbols are padded with spaces to separate them from surrounding words, but our
+                // output already has spaces in the right places.
+                deunicode::deunicode_char(c)
+                    .map_or("?", str::trim)
+                    .to_string()
+            }
+        })
+        .collect()
+}
+
+/// Splits text into lines at most `width` columns wide, breaking between words. Words wider than
+/// `width` get a line of their own rather than being split.
+pub(crate) fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split(' ') {
+        if !line.is_empty() && line.width() + 1 + word.width() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+
+        if !line.is_empty() {
+            line.push(' ');
+        }
+
+        line.push_str(word);
+    }
+
+    lines.push(line);
+
+    lines
+}
+
+/// Describes how far `date` is from `today`, such as “in 3 days” or “yesterday”. `today` should be
+/// the local date, so that the description matches the user’s calendar rather than UTC’s.
+pub fn relative_date(date: NaiveDate, today: NaiveDate) -> String {
+    match (date - today).num_days() {
+        0 => "today".to_string(),
+        1 => "tomorrow".to_string(),
+        -1 => "yesterday".to_string(),
+        days if days > 0 => format!("in {} days", days),
+        days => format!("{} days ago", -days),
+    }
+}
+
+pub(crate) fn dim(s: &str) -> String {
+    format!("\x1b[2m{}\x1b[0m", s)
+}
+
+pub(crate) fn cyan(s: &str) -> String {
+    format!("\x1b[36m{}\x1b[0m", s)
+}
+
+pub(crate) fn red(s: &str) -> String {
+    format!("\x1b[31m{}\x1b[0m", s)
+}
+
+pub(crate) fn yellow(s: &str) -> String {
+    format!("\x1b[33m{}\x1b[0m", s)
+}
+
+pub(crate) fn bold_green(s: &str) -> String {
+    format!("\x1b[1;32m{}\x1b[0m", s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_glyphs_are_refused() {
+        let glyphs = Glyphs {
+            complete: Some(" ".to_string()),
+            ..Glyphs::default()
+        };
+
+        assert_eq!(glyphs.validate(), Err(Error::EmptyGlyph("complete")));
+        assert_eq!(Glyphs::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn date_style_can_be_parsed() {
+        assert_eq!("absolute".parse(), Ok(DateStyle::Absolute));
+        assert_eq!("relative".parse(), Ok(DateStyle::Relative));
+        assert_eq!(
+            "fuzzy".parse::<DateStyle>(),
+            Err(Error::UnknownDateStyle("fuzzy".to_string()))
+        );
+    }
+
+    #[test]
+    fn relative_dates_need_today() {
+        let date = NaiveDate::from_ymd(2024, 5, 4);
+
+        assert_eq!(
+            DateStyle::Relative.format(date, Some(NaiveDate::from_ymd(2024, 5, 1))),
+            "in 3 days"
+        );
+        assert_eq!(DateStyle::Relative.format(date, None), "2024-05-04");
+        assert_eq!(
+            DateStyle::Absolute.format(date, Some(NaiveDate::from_ymd(2024, 5, 1))),
+            "2024-05-04"
+        );
+    }
+
+    #[test]
+    fn colors_can_be_parsed() {
+        assert_eq!("magenta".parse(), Ok(Color::Magenta));
+        assert_eq!(
+            "mauve".parse::<Color>(),
+            Err(Error::UnknownColor("mauve".to_string()))
+        );
+    }
+
+    #[test]
+    fn tags_are_drawn_in_their_color() {
+        let options = RenderOptions {
+            use_color: true,
+            tag_colors: vec![("urgent".to_string(), Color::Red)]
+                .into_iter()
+                .collect(),
+            ..RenderOptions::default()
+        };
+
+        assert_eq!(options.tag_color("urgent"), Some(Color::Red));
+        assert_eq!(options.render_tag("urgent"), "\x1b[31m#urgent\x1b[0m");
+    }
+
+    #[test]
+    fn tags_without_a_color_are_drawn_plainly() {
+        let options = RenderOptions {
+            use_color: true,
+            tag_colors: vec![("urgent".to_string(), Color::Red)]
+                .into_iter()
+                .collect(),
+            ..RenderOptions::default()
+        };
+
+        assert_eq!(options.tag_color("errands"), None);
+        assert_eq!(options.render_tag("errands"), "#errands");
+    }
+
+    #[test]
+    fn tag_colors_are_ignored_without_color() {
+        let options = RenderOptions {
+            tag_colors: vec![("urgent".to_string(), Color::Red)]
+                .into_iter()
+                .collect(),
+            ..RenderOptions::default()
+        };
+
+        assert_eq!(options.render_tag("urgent"), "#urgent");
+    }
+
+    #[test]
+    fn relative_date_of_today_is_today() {
+        let today = NaiveDate::from_ymd(2024, 5, 1);
+
+        assert_eq!(relative_date(today, today), "today");
+    }
+
+    #[test]
+    fn relative_date_of_tomorrow_is_tomorrow() {
+        assert_eq!(
+            relative_date(
+                NaiveDate::from_ymd(2024, 5, 1),
+                NaiveDate::from_ymd(2024, 4, 30)
+            ),
+            "tomorrow"
+        );
+    }
+
+    #[test]
+    fn relative_date_of_yesterday_is_yesterday() {
+        assert_eq!(
+            relative_date(
+                NaiveDate::from_ymd(2024, 4, 30),
+                NaiveDate::from_ymd(2024, 5, 1)
+            ),
+            "yesterday"
+        );
+    }
+
+    #[test]
+    fn relative_date_counts_days_in_either_direction() {
+        let today = NaiveDate::from_ymd(2024, 5, 1);
+
+        assert_eq!(
+            relative_date(NaiveDate::from_ymd(2024, 5, 4), today),
+            "in 3 days"
+        );
+        assert_eq!(
+            relative_date(NaiveDate::from_ymd(2024, 4, 29), today),
+            "2 days ago"
+        );
+    }
+
+    #[test]
+    fn ascii_output_transliterates_non_ascii_characters() {
+        let options = RenderOptions {
+            ascii: true,
+            ..RenderOptions::default()
+        };
+
+        assert_eq!(
+            transliterate_if_ascii("• Café ☕ – Crème brûlée 🎉".to_string(), &options),
+            "* Cafe coffee - Creme brulee tada"
+        );
+    }
+
+    #[test]
+    fn output_is_left_alone_without_ascii_option() {
+        assert_eq!(
+            transliterate_if_ascii("• Café".to_string(), &RenderOptions::default()),
+            "• Café"
+        );
+    }
+
+    #[test]
+    fn text_is_wrapped_between_words() {
+        assert_eq!(
+            wrap("the quick brown fox jumps over the lazy dog", 10),
+            ["the quick", "brown fox", "jumps over", "the lazy", "dog"]
+        );
+    }
+
+    #[test]
+    fn words_longer_than_width_get_their_own_line() {
+        assert_eq!(
+            wrap("a supercalifragilistic word", 5),
+            ["a", "supercalifragilistic", "word"]
+        );
+    }
+
+    #[test]
+    fn text_shorter_than_width_is_left_alone() {
+        assert_eq!(wrap("Buy some milk", 80), ["Buy some milk"]);
+    }
+
+    #[test]
+    fn indenting_narrows_width() {
+        let options = RenderOptions {
+            width: Some(40),
+            ..RenderOptions::default()
+        };
+
+        assert_eq!(options.indented(2).width, Some(38));
+        assert_eq!(RenderOptions::default().indented(2).width, None);
+    }
+
+    #[test]
+    fn grouping_can_be_parsed() {
+        assert_eq!("tag".parse(), Ok(GroupBy::Tag));
+    }
+
+    #[test]
+    fn task_order_can_be_parsed() {
+        assert_eq!("priority".parse(), Ok(TaskOrder::Priority));
+        assert_eq!("age".parse(), Ok(TaskOrder::Age));
+        assert_eq!(
+            "size".parse::<TaskOrder>(),
+            Err(Error::UnknownTaskOrder("size".to_string()))
+        );
+    }
+
+    #[test]
+    fn parsing_unknown_grouping_gives_error() {
+        assert_eq!(
+            "colour".parse::<GroupBy>(),
+            Err(Error::UnknownGrouping("colour".to_string()))
+        );
+    }
+}
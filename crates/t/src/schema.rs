@@ -0,0 +1,73 @@
+use serde_json::Value;
+use thiserror::Error;
+
+/// The schema version written into exported databases by this version of t.
+pub const CURRENT_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("database is not valid: {0}")]
+    Malformed(#[from] serde_json::Error),
+    #[error("database is not valid TOML: {0}")]
+    MalformedToml(#[from] toml::de::Error),
+    #[error("database schema version is not a non-negative integer")]
+    InvalidVersion,
+    #[error("database has schema version {found}, but this version of t only understands versions up to {supported}; please upgrade t to import it")]
+    UnsupportedVersion { found: u32, supported: u32 },
+}
+
+/// Upgrades a serialized database of any supported schema version to the current one.
+///
+/// Databases from before schema versions were introduced carry no `version` field and are treated
+/// as version 0.
+pub(crate) fn migrate(mut db: Value) -> Result<Value, Error> {
+    let mut version = match db.get("version") {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .filter(|&version| version <= u64::from(u32::MAX))
+            .ok_or(Error::InvalidVersion)? as u32,
+    };
+
+    if version > CURRENT_VERSION {
+        return Err(Error::UnsupportedVersion {
+            found: version,
+            supported: CURRENT_VERSION,
+        });
+    }
+
+    while version < CURRENT_VERSION {
+        match version {
+            0 => migrate_v0_to_v1(&mut db),
+            _ => unreachable!(),
+        }
+
+        version += 1;
+    }
+
+    if let Value::Object(fields) = &mut db {
+        fields.insert("version".to_string(), Value::from(CURRENT_VERSION));
+    }
+
+    Ok(db)
+}
+
+/// Tasks in unversioned databases may predate reminders, so we give those tasks an empty list of
+/// them.
+fn migrate_v0_to_v1(db: &mut Value) {
+    let task_lists = match db.get_mut("task_lists").and_then(Value::as_object_mut) {
+        Some(task_lists) => task_lists,
+        None => return,
+    };
+
+    let tasks = task_lists
+        .values_mut()
+        .filter_map(|task_list| task_list.get_mut("tasks").and_then(Value::as_object_mut))
+        .flat_map(|tasks| tasks.values_mut())
+        .filter_map(Value::as_object_mut);
+
+    for task in tasks {
+        task.entry("reminders")
+            .or_insert_with(|| Value::Array(Vec::new()));
+    }
+}
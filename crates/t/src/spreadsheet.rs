@@ -0,0 +1,44 @@
+use crate::status::Status;
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("CSV is not valid: {0}")]
+    Malformed(#[from] csv::Error),
+    #[error("task list ‘{list}’ has more than one task with ID {id}")]
+    DuplicateTaskId { list: String, id: u32 },
+}
+
+/// A task as it appears in a row of a CSV file.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Row {
+    pub(crate) list: String,
+    pub(crate) id: u32,
+    pub(crate) status: Status,
+    pub(crate) title: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_are_written_with_header_and_quoting() {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer
+            .serialize(Row {
+                list: "Tasks".to_string(),
+                id: 0,
+                status: Status::Incomplete,
+                title: "Buy milk, eggs and \"fancy\" bread".to_string(),
+            })
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(writer.into_inner().unwrap()).unwrap(),
+            "list,id,status,title\nTasks,0,Incomplete,\"Buy milk, eggs and \"\"fancy\"\" bread\"\n"
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use std::fmt;
+
+/// How far along the tasks in each task list, and in the database as a whole, are.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Stats {
+    /// The counts for each task list, in the order the task lists are displayed.
+    pub task_lists: Vec<(String, Counts)>,
+    pub total: Counts,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Counts {
+    pub complete: usize,
+    pub incomplete: usize,
+}
+
+impl Counts {
+    pub fn total(self) -> usize {
+        self.complete + self.incomplete
+    }
+}
+
+impl fmt::Display for Counts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{} complete", self.complete, self.total())?;
+
+        // There’s no meaningful percentage without any tasks.
+        if self.total() > 0 {
+            write!(f, " ({}%)", self.complete * 100 / self.total())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_are_displayed_with_percentage_complete() {
+        let counts = Counts {
+            complete: 3,
+            incomplete: 2,
+        };
+
+        assert_eq!(counts.to_string(), "3/5 complete (60%)");
+    }
+
+    #[test]
+    fn counts_without_tasks_have_no_percentage() {
+        assert_eq!(Counts::default().to_string(), "0/0 complete");
+    }
+}
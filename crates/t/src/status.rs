@@ -1,26 +1,89 @@
+use crate::render::StatusStyle;
 use serde::Deserialize;
 use serde::Serialize;
 use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum Error {
+    #[error(
+        "unknown status ‘{0}’ (expected ‘incomplete’, ‘in-progress’, ‘complete’ or ‘cancelled’)"
+    )]
+    UnknownStatus(String),
+}
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
-pub(crate) enum Status {
+pub enum Status {
     Incomplete,
+    /// Being worked on, but not yet complete.
+    InProgress,
     Complete,
+    /// Dropped rather than done, which is kept apart from completion so it isn’t counted as such.
+    Cancelled,
 }
 
-impl fmt::Display for Status {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl Status {
+    /// The name the status is given on the command line, which it is also parsed from.
+    pub fn name(&self) -> &'static str {
         match self {
-            Self::Incomplete => f.write_str("•"),
-            Self::Complete => f.write_str("–"),
+            Self::Incomplete => "incomplete",
+            Self::InProgress => "in-progress",
+            Self::Complete => "complete",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    pub(crate) fn glyph(&self, style: StatusStyle) -> &'static str {
+        match (self, style) {
+            (Self::Incomplete, StatusStyle::Unicode) => "•",
+            (Self::InProgress, StatusStyle::Unicode) => "◐",
+            (Self::Complete, StatusStyle::Unicode) => "–",
+            (Self::Cancelled, StatusStyle::Unicode) => "✗",
+            (Self::Incomplete, StatusStyle::Ascii) => "[ ]",
+            (Self::InProgress, StatusStyle::Ascii) => "[~]",
+            (Self::Complete, StatusStyle::Ascii) => "[x]",
+            (Self::Cancelled, StatusStyle::Ascii) => "[-]",
+        }
+    }
+}
+
+impl FromStr for Status {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "incomplete" => Ok(Self::Incomplete),
+            "in-progress" => Ok(Self::InProgress),
+            "complete" => Ok(Self::Complete),
+            "cancelled" => Ok(Self::Cancelled),
+            _ => Err(Error::UnknownStatus(s.to_string())),
         }
     }
 }
 
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.glyph(StatusStyle::default()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn statuses_are_parsed_from_their_names() {
+        for status in [
+            Status::Incomplete,
+            Status::InProgress,
+            Status::Complete,
+            Status::Cancelled,
+        ] {
+            assert_eq!(status.name().parse(), Ok(status));
+        }
+    }
+
     #[test]
     fn incomplete_status_is_displayed_as_bullet() {
         assert_eq!(format!("{}", Status::Incomplete), "•");
@@ -30,4 +93,38 @@ mod tests {
     fn complete_status_is_displayed_as_en_dash() {
         assert_eq!(format!("{}", Status::Complete), "–");
     }
+
+    #[test]
+    fn in_progress_status_is_displayed_as_half_circle() {
+        assert_eq!(format!("{}", Status::InProgress), "◐");
+    }
+
+    #[test]
+    fn cancelled_status_is_displayed_as_cross() {
+        assert_eq!(format!("{}", Status::Cancelled), "✗");
+    }
+
+    #[test]
+    fn statuses_can_be_parsed_case_insensitively() {
+        assert_eq!("incomplete".parse(), Ok(Status::Incomplete));
+        assert_eq!("In-Progress".parse(), Ok(Status::InProgress));
+        assert_eq!("COMPLETE".parse(), Ok(Status::Complete));
+        assert_eq!("cancelled".parse(), Ok(Status::Cancelled));
+    }
+
+    #[test]
+    fn parsing_unknown_status_gives_error() {
+        assert_eq!(
+            "done".parse::<Status>(),
+            Err(Error::UnknownStatus("done".to_string()))
+        );
+    }
+
+    #[test]
+    fn ascii_statuses_are_checkboxes() {
+        assert_eq!(Status::Incomplete.glyph(StatusStyle::Ascii), "[ ]");
+        assert_eq!(Status::InProgress.glyph(StatusStyle::Ascii), "[~]");
+        assert_eq!(Status::Complete.glyph(StatusStyle::Ascii), "[x]");
+        assert_eq!(Status::Cancelled.glyph(StatusStyle::Ascii), "[-]");
+    }
 }
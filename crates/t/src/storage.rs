@@ -0,0 +1,183 @@
+use crate::db::Db;
+use crate::schema;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("cannot store the database as ‘{0}’ (expected ‘json’ or ‘toml’)")]
+    UnknownFormat(String),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Schema(#[from] schema::Error),
+}
+
+/// Somewhere a database can be loaded from and saved to, so that `t` can be embedded in programs
+/// that keep their data somewhere other than a file.
+pub trait Storage {
+    type Error;
+
+    fn load(&self) -> Result<Db, Self::Error>;
+
+    fn save(&mut self, db: &Db) -> Result<(), Self::Error>;
+}
+
+/// The format a database file is written in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Json,
+    /// Friendlier than JSON for editing the database by hand.
+    Toml,
+}
+
+impl Format {
+    /// Databases are stored as TOML if their file has a `.toml` extension, and as JSON otherwise.
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(extension) if extension == "toml" => Self::Toml,
+            _ => Self::Json,
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "toml" => Ok(Self::Toml),
+            _ => Err(Error::UnknownFormat(s.to_string())),
+        }
+    }
+}
+
+/// Keeps the database in a file, as JSON or TOML.
+#[derive(Debug)]
+pub struct JsonFileStorage {
+    path: PathBuf,
+    format: Format,
+}
+
+impl JsonFileStorage {
+    /// Keeps the database at `path`, in the format given by its extension.
+    pub fn new(path: PathBuf) -> Self {
+        let format = Format::for_path(&path);
+        Self::with_format(path, format)
+    }
+
+    pub fn with_format(path: PathBuf, format: Format) -> Self {
+        Self { path, format }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Storage for JsonFileStorage {
+    type Error = Error;
+
+    /// Reads the database, migrating it first if it was saved by an older version of t.
+    fn load(&self) -> Result<Db, Self::Error> {
+        let contents = fs::read_to_string(&self.path)?;
+
+        match self.format {
+            Format::Json => Ok(Db::from_json(&contents)?),
+            Format::Toml => Ok(Db::from_toml(&contents)?),
+        }
+    }
+
+    /// Saves the database tagged with the current schema version.
+    fn save(&mut self, db: &Db) -> Result<(), Self::Error> {
+        let contents = match self.format {
+            Format::Json => db.to_json(),
+            Format::Toml => db.to_toml(),
+        };
+
+        Ok(write_atomically(&self.path, contents.as_bytes())?)
+    }
+}
+
+/// Writes a file by writing to a temporary file next to the real one, and then renaming the
+/// temporary file over the real one. Since renames are atomic, this means that the file is never
+/// left half-written if the program is killed while saving. The file’s directory is created if it
+/// doesn’t exist yet.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent_path) = path.parent() {
+        fs::create_dir_all(parent_path)?;
+    }
+
+    let mut temp_file_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_file_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_file_name);
+
+    let mut temp_file = fs::File::create(&temp_path)?;
+    temp_file.write_all(contents)?;
+    temp_file.sync_all()?;
+
+    fs::rename(temp_path, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::Task;
+
+    /// Keeps the database as JSON in memory, so that it goes through the same serialization as
+    /// a database saved to a file.
+    #[derive(Default)]
+    struct InMemoryStorage {
+        json: Option<String>,
+    }
+
+    impl Storage for InMemoryStorage {
+        type Error = schema::Error;
+
+        fn load(&self) -> Result<Db, Self::Error> {
+            match &self.json {
+                Some(json) => Db::from_json(json),
+                None => Ok(Db::new_in_memory()),
+            }
+        }
+
+        fn save(&mut self, db: &Db) -> Result<(), Self::Error> {
+            self.json = Some(db.to_json());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn db_is_stored_as_json_unless_its_extension_is_toml() {
+        assert_eq!(Format::for_path(Path::new("db.json")), Format::Json);
+        assert_eq!(Format::for_path(Path::new("tasks")), Format::Json);
+        assert_eq!(Format::for_path(Path::new("db.toml")), Format::Toml);
+    }
+
+    #[test]
+    fn db_survives_being_saved_and_loaded() {
+        let mut storage = InMemoryStorage::default();
+
+        let mut db = storage.load().unwrap();
+        let task_list = db.get_current_task_list_mut().unwrap();
+        let id = task_list
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        task_list
+            .add_task(Task::new("Fix the fence".to_string()))
+            .unwrap();
+        task_list.complete_task(id).unwrap();
+        storage.save(&db).unwrap();
+
+        let loaded = storage.load().unwrap();
+
+        assert_eq!(loaded, db);
+        assert_eq!(loaded.stats().task_lists[0].1.complete, 1);
+    }
+}
@@ -1,14 +1,98 @@
+use crate::priority::Priority;
+use crate::recurrence::Recurrence;
+use crate::render;
+use crate::render::RenderOptions;
 use crate::status::Status;
+use chrono::DateTime;
 use chrono::NaiveDate;
+use chrono::NaiveDateTime;
+use chrono::Utc;
 use serde::Deserialize;
 use serde::Serialize;
+use std::collections::HashMap;
 use std::fmt;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("task is not valid: {0}")]
+    Malformed(#[from] serde_json::Error),
+}
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Task {
     title: String,
     status: Status,
     reminders: Vec<NaiveDate>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    priority: Priority,
+    #[serde(default)]
+    due: Option<NaiveDate>,
+    #[serde(default)]
+    notes: Option<String>,
+    #[serde(default = "unknown_creation_time")]
+    created: DateTime<Utc>,
+    #[serde(default)]
+    completed: Option<DateTime<Utc>>,
+    #[serde(default)]
+    recurrence: Option<Recurrence>,
+    #[serde(default)]
+    pinned: bool,
+    /// The IDs of tasks in the same task list that have to be finished before this one.
+    #[serde(default)]
+    depends_on: Vec<u32>,
+    /// The steps the task is broken into, in order.
+    #[serde(default)]
+    subtasks: Vec<Subtask>,
+}
+
+/// A step of a larger task. Subtasks are only ever complete or incomplete, and are referred to by
+/// their position in their task, counting from 0.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Subtask {
+    title: String,
+    status: Status,
+}
+
+/// A task as given by the user in JSON. It has the same fields as [`Task`], but only the title is
+/// required, and fields [`Task`] doesn’t have are refused rather than ignored, so that typos don’t
+/// go unnoticed.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TaskJson {
+    title: String,
+    #[serde(default)]
+    status: Option<Status>,
+    #[serde(default)]
+    reminders: Vec<NaiveDate>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    priority: Priority,
+    #[serde(default)]
+    due: Option<NaiveDate>,
+    #[serde(default)]
+    notes: Option<String>,
+    #[serde(default)]
+    created: Option<DateTime<Utc>>,
+    #[serde(default)]
+    completed: Option<DateTime<Utc>>,
+    #[serde(default)]
+    recurrence: Option<Recurrence>,
+    #[serde(default)]
+    pinned: bool,
+    #[serde(default)]
+    depends_on: Vec<u32>,
+    #[serde(default)]
+    subtasks: Vec<Subtask>,
+}
+
+/// Tasks from before creation times were recorded are treated as having been created at the Unix
+/// epoch, which keeps them deserializing to the same task every time.
+fn unknown_creation_time() -> DateTime<Utc> {
+    DateTime::from_utc(NaiveDateTime::from_timestamp(0, 0), Utc)
 }
 
 impl Task {
@@ -17,31 +101,552 @@ impl Task {
             title,
             status: Status::Incomplete,
             reminders: Vec::new(),
+            tags: Vec::new(),
+            priority: Priority::default(),
+            due: None,
+            notes: None,
+            created: Utc::now(),
+            completed: None,
+            recurrence: None,
+            pinned: false,
+            depends_on: Vec::new(),
+            subtasks: Vec::new(),
+        }
+    }
+
+    /// Reads a task written as JSON, such as one taken from `t export`. Only the title is required;
+    /// anything else left out is as it would be for a new task.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let input: TaskJson = serde_json::from_str(json)?;
+        let mut task = Self::new(input.title);
+
+        if let Some(status) = input.status {
+            task.set_status(status);
+        }
+
+        for reminder in input.reminders {
+            task.add_reminder(reminder);
+        }
+
+        for tag in input.tags {
+            task.add_tag(tag);
+        }
+
+        for dependency in input.depends_on {
+            task.add_dependency(dependency);
+        }
+
+        task.priority = input.priority;
+        task.due = input.due;
+        task.notes = input.notes;
+        task.created = input.created.unwrap_or(task.created);
+        // Only a complete task has a completion time, whatever the JSON says.
+        if task.is_complete() {
+            task.completed = input.completed.or(task.completed);
+        }
+        task.recurrence = input.recurrence;
+        task.pinned = input.pinned;
+        task.subtasks = input.subtasks;
+
+        Ok(task)
+    }
+
+    /// Returns a copy of the task as if it had just been created, so it is incomplete however far
+    /// along the original is.
+    pub(crate) fn duplicate(&self) -> Self {
+        Self {
+            status: Status::Incomplete,
+            created: Utc::now(),
+            completed: None,
+            subtasks: self
+                .subtasks
+                .iter()
+                .map(|subtask| Subtask {
+                    status: Status::Incomplete,
+                    ..subtask.clone()
+                })
+                .collect(),
+            ..self.clone()
         }
     }
 
     pub(crate) fn complete(&mut self) {
+        // Completing a task again shouldn’t move back when it was first completed.
+        if !self.is_complete() {
+            self.completed = Some(Utc::now());
+        }
+
         self.status = Status::Complete;
     }
 
+    /// Marks the task as being worked on. Starting a completed task makes it incomplete again.
+    pub(crate) fn start(&mut self) {
+        self.status = Status::InProgress;
+        self.completed = None;
+    }
+
+    /// Marks the task as dropped rather than done. Cancelled tasks are not complete.
+    pub(crate) fn cancel(&mut self) {
+        self.status = Status::Cancelled;
+        self.completed = None;
+    }
+
+    pub(crate) fn uncomplete(&mut self) {
+        self.status = Status::Incomplete;
+        self.completed = None;
+    }
+
+    pub fn created(&self) -> DateTime<Utc> {
+        self.created
+    }
+
+    pub fn completed(&self) -> Option<DateTime<Utc>> {
+        self.completed
+    }
+
+    pub(crate) fn title(&self) -> &str {
+        &self.title
+    }
+
     pub(crate) fn rename(&mut self, new_title: String) {
         self.title = new_title;
     }
 
+    pub(crate) fn status(&self) -> Status {
+        self.status.clone()
+    }
+
+    /// Sets the status directly, stamping the completion time as completing the task would.
+    pub(crate) fn set_status(&mut self, status: Status) {
+        match status {
+            Status::Incomplete => self.uncomplete(),
+            Status::InProgress => self.start(),
+            Status::Complete => self.complete(),
+            Status::Cancelled => self.cancel(),
+        }
+    }
+
     pub(crate) fn is_complete(&self) -> bool {
         matches!(self.status, Status::Complete)
     }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        matches!(self.status, Status::Cancelled)
+    }
+
+    /// Returns whether the task no longer needs doing, either because it was done or because it
+    /// was dropped.
+    pub(crate) fn is_finished(&self) -> bool {
+        self.is_complete() || self.is_cancelled()
+    }
+
+    /// Returns the task’s reminders in chronological order.
+    pub fn reminders(&self) -> &[NaiveDate] {
+        &self.reminders
+    }
+
+    /// Adds a reminder, keeping the reminders sorted and free of duplicates.
+    ///
+    /// Returns whether the reminder was newly added, which is not the case if the task already had
+    /// a reminder on that date.
+    pub fn add_reminder(&mut self, date: NaiveDate) -> bool {
+        match self.reminders.binary_search(&date) {
+            Ok(_) => false,
+            Err(i) => {
+                self.reminders.insert(i, date);
+                true
+            }
+        }
+    }
+
+    /// Removes a reminder, returning whether the task had a reminder on that date.
+    pub fn remove_reminder(&mut self, date: NaiveDate) -> bool {
+        let len_before = self.reminders.len();
+        self.reminders.retain(|reminder| *reminder != date);
+
+        self.reminders.len() != len_before
+    }
+
+    /// Returns whether the reminders are sorted and free of duplicates, which they might not be if
+    /// the database was edited by hand.
+    pub(crate) fn reminders_are_tidy(&self) -> bool {
+        self.reminders.windows(2).all(|pair| pair[0] < pair[1])
+    }
+
+    pub(crate) fn tidy_reminders(&mut self) {
+        self.reminders.sort_unstable();
+        self.reminders.dedup();
+    }
+
+    pub(crate) fn is_pinned(&self) -> bool {
+        self.pinned
+    }
+
+    pub(crate) fn set_pinned(&mut self, pinned: bool) {
+        self.pinned = pinned;
+    }
+
+    pub(crate) fn depends_on(&self) -> &[u32] {
+        &self.depends_on
+    }
+
+    /// Adds a dependency on another task, returning whether the task didn’t already depend on it.
+    pub(crate) fn add_dependency(&mut self, id: u32) -> bool {
+        if self.depends_on.contains(&id) {
+            return false;
+        }
+
+        self.depends_on.push(id);
+        true
+    }
+
+    /// Removes a dependency on another task, returning whether the task depended on it.
+    pub(crate) fn remove_dependency(&mut self, id: u32) -> bool {
+        let len_before = self.depends_on.len();
+        self.depends_on.retain(|dependency| *dependency != id);
+
+        self.depends_on.len() != len_before
+    }
+
+    /// Keeps only the dependencies on tasks with IDs for which `keep` returns true.
+    pub(crate) fn retain_dependencies(&mut self, mut keep: impl FnMut(u32) -> bool) {
+        self.depends_on.retain(|dependency| keep(*dependency));
+    }
+
+    /// Points each dependency at the task’s new ID once tasks have been given new IDs, dropping
+    /// dependencies on tasks that weren’t.
+    pub(crate) fn remap_dependencies(&mut self, new_ids: &HashMap<u32, u32>) {
+        self.depends_on = self
+            .depends_on
+            .iter()
+            .filter_map(|dependency| new_ids.get(dependency).copied())
+            .collect();
+    }
+
+    pub(crate) fn subtasks(&self) -> &[Subtask] {
+        &self.subtasks
+    }
+
+    /// Adds an incomplete subtask after the existing ones, returning its index.
+    pub(crate) fn add_subtask(&mut self, title: String) -> usize {
+        self.subtasks.push(Subtask {
+            title,
+            status: Status::Incomplete,
+        });
+
+        self.subtasks.len() - 1
+    }
+
+    /// Completes the subtask at an index, returning whether there is one.
+    pub(crate) fn complete_subtask(&mut self, index: usize) -> bool {
+        match self.subtasks.get_mut(index) {
+            Some(subtask) => {
+                subtask.status = Status::Complete;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns how many subtasks are complete and how many there are in all.
+    pub(crate) fn subtask_progress(&self) -> (usize, usize) {
+        let num_complete = self
+            .subtasks
+            .iter()
+            .filter(|subtask| subtask.status == Status::Complete)
+            .count();
+
+        (num_complete, self.subtasks.len())
+    }
+
+    pub(crate) fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    pub fn set_priority(&mut self, priority: Priority) {
+        self.priority = priority;
+    }
+
+    pub(crate) fn due(&self) -> Option<NaiveDate> {
+        self.due
+    }
+
+    pub fn set_due(&mut self, due: Option<NaiveDate>) {
+        self.due = due;
+    }
+
+    pub(crate) fn recurrence(&self) -> Option<Recurrence> {
+        self.recurrence
+    }
+
+    pub(crate) fn set_recurrence(&mut self, recurrence: Option<Recurrence>) {
+        self.recurrence = recurrence;
+    }
+
+    pub(crate) fn notes(&self) -> Option<&str> {
+        self.notes.as_deref()
+    }
+
+    pub(crate) fn set_notes(&mut self, notes: Option<String>) {
+        self.notes = notes;
+    }
+
+    pub(crate) fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn add_tag(&mut self, tag: String) {
+        if !self.tags.contains(&tag) {
+            self.tags.push(tag);
+        }
+    }
+
+    /// Removes a tag, returning whether the task had it.
+    pub(crate) fn remove_tag(&mut self, tag: &str) -> bool {
+        let len_before = self.tags.len();
+        self.tags.retain(|t| t != tag);
+
+        self.tags.len() != len_before
+    }
+
+    /// Returns whether any tag appears more than once, which can happen if the database was edited
+    /// by hand.
+    pub(crate) fn has_duplicate_tags(&self) -> bool {
+        self.tags
+            .iter()
+            .enumerate()
+            .any(|(i, tag)| self.tags[..i].contains(tag))
+    }
+
+    /// Removes repeated tags, keeping the first of each.
+    pub(crate) fn dedup_tags(&mut self) {
+        let mut seen = Vec::new();
+        self.tags.retain(|tag| {
+            let is_new = !seen.contains(tag);
+            seen.push(tag.clone());
+            is_new
+        });
+    }
+
+    pub(crate) fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Replaces a tag with another, returning whether the task had the old tag. If the task
+    /// already has the new tag, the old tag is just removed.
+    pub(crate) fn rename_tag(&mut self, old: &str, new: &str) -> bool {
+        let i = match self.tags.iter().position(|tag| tag == old) {
+            Some(i) => i,
+            None => return false,
+        };
+
+        if self.tags.iter().any(|tag| tag == new) {
+            self.tags.remove(i);
+        } else {
+            self.tags[i] = new.to_string();
+        }
+
+        true
+    }
+
+    /// Renders the task as its status followed by its details, the way it appears in a task list
+    /// but without its ID.
+    pub fn render(&self, options: &RenderOptions) -> String {
+        format!(
+            "{} {}",
+            self.render_status(options),
+            self.render_details(options)
+        )
+    }
+
+    pub(crate) fn render_status(&self, options: &RenderOptions) -> String {
+        let status = options.glyph(&self.status);
+
+        // Completed tasks that are dimmed as a whole don’t get their status styled separately,
+        // since the escape code ending that style would also end the dimming.
+        if !options.use_color || (options.dim_completed && self.is_complete()) {
+            status.to_string()
+        } else {
+            match self.status {
+                Status::Incomplete => render::cyan(status),
+                Status::InProgress => render::yellow(status),
+                Status::Complete | Status::Cancelled => render::dim(status),
+            }
+        }
+    }
+
+    /// Renders everything that comes after the status: the title, followed by the due date,
+    /// recurrence, tags and (in verbose mode) timestamps.
+    pub(crate) fn render_details(&self, options: &RenderOptions) -> String {
+        let mut rendered = self.title.clone();
+
+        if !self.subtasks.is_empty() {
+            let (num_complete, num_subtasks) = self.subtask_progress();
+            rendered.push_str(&format!(" [{}/{}]", num_complete, num_subtasks));
+        }
+
+        if let Some(due) = self.due {
+            rendered.push_str(&format!(" (due {})", options.format_date(due)));
+        }
+
+        if let Some(recurrence) = self.recurrence {
+            rendered.push_str(&format!(" ({})", recurrence));
+        }
+
+        // Like statuses, tags on completed tasks that are dimmed as a whole aren’t colored, since
+        // the escape code ending the color would also end the dimming.
+        let is_dimmed = options.use_color && options.dim_completed && self.is_complete();
+
+        for tag in &self.tags {
+            if is_dimmed {
+                rendered.push_str(&format!(" #{}", tag));
+            } else {
+                rendered.push_str(&format!(" {}", options.render_tag(tag)));
+            }
+        }
+
+        if options.verbose {
+            rendered.push_str(&format!(" (created {}", format_time(self.created)));
+
+            if let Some(completed) = self.completed {
+                rendered.push_str(&format!(", completed {}", format_time(completed)));
+            }
+
+            rendered.push(')');
+        }
+
+        rendered
+    }
+
+    /// Renders a warning if the task is overdue or due today, judged by `options.today`. Finished
+    /// tasks never get one, however long ago they were due.
+    pub(crate) fn render_due_marker(&self, options: &RenderOptions) -> Option<String> {
+        if self.is_complete() || self.is_cancelled() {
+            return None;
+        }
+
+        let (today, due) = (options.today?, self.due?);
+
+        let (marker, style): (_, fn(&str) -> String) = if due < today {
+            ("(overdue)", render::red)
+        } else if due == today {
+            ("(due today)", render::yellow)
+        } else {
+            return None;
+        };
+
+        Some(if options.use_color {
+            style(marker)
+        } else {
+            marker.to_string()
+        })
+    }
+
+    /// Renders everything about the task as a block with a labeled line for each detail.
+    pub fn render_detail(&self, options: &RenderOptions) -> String {
+        let status = match self.status {
+            Status::Incomplete => "incomplete",
+            Status::InProgress => "in progress",
+            Status::Complete => "complete",
+            Status::Cancelled => "cancelled",
+        };
+
+        let due = self
+            .due
+            .map_or_else(|| "none".to_string(), |due| options.format_date(due));
+
+        let tags = if self.tags.is_empty() {
+            "none".to_string()
+        } else {
+            self.tags
+                .iter()
+                .map(|tag| format!("#{}", tag))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        let priority = match self.priority {
+            Priority::Low => "low",
+            Priority::Medium => "medium",
+            Priority::High => "high",
+        };
+
+        // Notes can span several lines, so we line every line up after the label.
+        let notes = self
+            .notes
+            .as_deref()
+            .unwrap_or("none")
+            .lines()
+            .collect::<Vec<_>>()
+            .join(&format!("\n{}", " ".repeat(10)));
+
+        format!(
+            "Title:    {}\nStatus:   {} {}\nDue:      {}\nTags:     {}\nPriority: {}\nNotes:    {}\n",
+            self.title,
+            options.glyph(&self.status),
+            status,
+            due,
+            tags,
+            priority,
+            notes
+        )
+    }
+}
+
+impl Subtask {
+    /// Renders the subtask as its status followed by its title.
+    pub(crate) fn render(&self, options: &RenderOptions) -> String {
+        let status = options.glyph(&self.status);
+
+        let status = match self.status {
+            Status::Complete if options.use_color => render::dim(status),
+            _ if options.use_color => render::cyan(status),
+            _ => status.to_string(),
+        };
+
+        format!("{} {}", status, self.title)
+    }
+}
+
+pub(crate) fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%d %H:%M UTC").to_string()
 }
 
 impl fmt::Display for Task {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} {}", self.status, self.title)
+        f.write_str(&self.render(&RenderOptions::default()))
+    }
+}
+
+#[cfg(test)]
+impl Task {
+    /// Creates a task with a particular status and, where given, particular creation and completion
+    /// times in RFC 3339, for tests that can’t rely on the clock.
+    pub(crate) fn with_times(
+        title: &str,
+        status: Status,
+        created: Option<&str>,
+        completed: Option<&str>,
+    ) -> Self {
+        let mut task = Self::new(title.to_string());
+        task.status = status;
+
+        if let Some(created) = created {
+            task.created = created.parse().unwrap();
+        }
+
+        task.completed = completed.map(|completed| completed.parse().unwrap());
+
+        task
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::render::DateStyle;
+    use crate::render::StatusStyle;
+    use chrono::TimeZone;
 
     #[test]
     fn when_a_task_is_created_its_status_is_set_to_incomplete() {
@@ -54,6 +659,142 @@ mod tests {
     #[test]
     fn when_a_task_is_created_it_has_no_reminders() {
         assert_eq!(Task::new("Buy some milk".to_string()).reminders, Vec::new());
+        assert!(Task::new("Buy some milk".to_string())
+            .reminders()
+            .is_empty());
+    }
+
+    #[test]
+    fn when_a_task_is_created_its_creation_time_is_recorded() {
+        let before = Utc::now();
+        let task = Task::new("Buy some milk".to_string());
+
+        assert!(task.created() >= before);
+        assert!(task.created() <= Utc::now());
+        assert_eq!(task.completed(), None);
+    }
+
+    #[test]
+    fn completing_task_records_completion_time() {
+        let mut task = Task::new("Buy some milk".to_string());
+
+        let before = Utc::now();
+        task.complete();
+        let completed = task.completed().unwrap();
+
+        assert!(completed >= before);
+        assert!(completed <= Utc::now());
+
+        // Completing the task again keeps the original completion time.
+        task.complete();
+        assert_eq!(task.completed(), Some(completed));
+    }
+
+    #[test]
+    fn uncompleting_task_clears_completion_time() {
+        let mut task = Task::new("Buy some milk".to_string());
+        task.complete();
+        task.uncomplete();
+
+        assert_eq!(task.completed(), None);
+    }
+
+    #[test]
+    fn started_task_is_complete_once_completed() {
+        let mut task = Task::new("Buy some milk".to_string());
+        task.start();
+
+        assert!(!task.is_complete());
+        assert_eq!(task.to_string(), "◐ Buy some milk");
+
+        task.complete();
+
+        assert_eq!(task.status, Status::Complete);
+        assert!(task.completed().is_some());
+    }
+
+    #[test]
+    fn in_progress_status_survives_serialization() {
+        let mut task = Task::new("Buy some milk".to_string());
+        task.start();
+
+        let json = serde_json::to_string(&task).unwrap();
+
+        assert!(json.contains(r#""status":"InProgress""#));
+        assert_eq!(serde_json::from_str::<Task>(&json).unwrap(), task);
+    }
+
+    #[test]
+    fn cancelled_task_is_not_complete() {
+        let mut task = Task::new("Buy some milk".to_string());
+        task.complete();
+        task.cancel();
+
+        assert!(!task.is_complete());
+        assert_eq!(task.completed(), None);
+        assert_eq!(task.to_string(), "✗ Buy some milk");
+    }
+
+    #[test]
+    fn timestamps_are_only_displayed_in_verbose_mode() {
+        let mut task = Task::new("Buy some milk".to_string());
+        task.created = Utc.ymd(2024, 5, 1).and_hms(9, 30, 0);
+        task.status = Status::Complete;
+        task.completed = Some(Utc.ymd(2024, 5, 2).and_hms(17, 0, 0));
+
+        let options = RenderOptions {
+            verbose: true,
+            ..RenderOptions::default()
+        };
+
+        assert_eq!(format!("{}", task), "– Buy some milk");
+        assert_eq!(
+            task.render(&options),
+            "– Buy some milk (created 2024-05-01 09:30 UTC, completed 2024-05-02 17:00 UTC)"
+        );
+    }
+
+    #[test]
+    fn duplicated_task_is_incomplete_and_newly_created() {
+        let mut task = Task::new("Buy some milk".to_string());
+        task.created = Utc.ymd(2024, 5, 1).and_hms(9, 30, 0);
+        task.add_tag("errands".to_string());
+        task.set_priority(Priority::High);
+        task.set_due(Some(NaiveDate::from_ymd(2024, 5, 2)));
+        task.complete();
+
+        let duplicate = task.duplicate();
+
+        assert_eq!(duplicate.title, "Buy some milk");
+        assert_eq!(duplicate.tags(), ["errands"]);
+        assert_eq!(duplicate.priority(), Priority::High);
+        assert_eq!(duplicate.due(), Some(NaiveDate::from_ymd(2024, 5, 2)));
+        assert!(!duplicate.is_complete());
+        assert_eq!(duplicate.completed(), None);
+        assert!(duplicate.created() > task.created());
+    }
+
+    #[test]
+    fn subtask_progress_counts_complete_subtasks() {
+        let mut task = Task::new("Fix the fence".to_string());
+        assert_eq!(task.subtask_progress(), (0, 0));
+
+        assert_eq!(task.add_subtask("Buy nails".to_string()), 0);
+        assert_eq!(task.add_subtask("Paint".to_string()), 1);
+        assert!(task.complete_subtask(0));
+        assert!(!task.complete_subtask(2));
+
+        assert_eq!(task.subtask_progress(), (1, 2));
+        assert_eq!(task.to_string(), "• Fix the fence [1/2]");
+    }
+
+    #[test]
+    fn duplicated_task_has_incomplete_subtasks() {
+        let mut task = Task::new("Fix the fence".to_string());
+        task.add_subtask("Buy nails".to_string());
+        task.complete_subtask(0);
+
+        assert_eq!(task.duplicate().subtask_progress(), (0, 1));
     }
 
     #[test]
@@ -72,6 +813,15 @@ mod tests {
         assert_eq!(task.status, Status::Complete);
     }
 
+    #[test]
+    fn tasks_can_be_uncompleted() {
+        let mut task = Task::new("Buy some milk".to_string());
+        task.complete();
+        task.uncomplete();
+
+        assert_eq!(task.status, Status::Incomplete);
+    }
+
     #[test]
     fn tasks_can_be_renamed() {
         let mut task = Task::new("Buy some milk".to_string());
@@ -80,12 +830,137 @@ mod tests {
         assert_eq!(task.title, "Purchase some milk");
     }
 
+    #[test]
+    fn reminders_are_kept_in_chronological_order() {
+        let mut task = Task::new("Buy some milk".to_string());
+        task.add_reminder(NaiveDate::from_ymd(2024, 5, 3));
+        task.add_reminder(NaiveDate::from_ymd(2024, 5, 1));
+        task.add_reminder(NaiveDate::from_ymd(2024, 5, 2));
+
+        assert_eq!(
+            task.reminders(),
+            [
+                NaiveDate::from_ymd(2024, 5, 1),
+                NaiveDate::from_ymd(2024, 5, 2),
+                NaiveDate::from_ymd(2024, 5, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicate_reminders_are_not_added() {
+        let mut task = Task::new("Buy some milk".to_string());
+
+        assert!(task.add_reminder(NaiveDate::from_ymd(2024, 5, 1)));
+        assert!(!task.add_reminder(NaiveDate::from_ymd(2024, 5, 1)));
+
+        assert_eq!(task.reminders(), [NaiveDate::from_ymd(2024, 5, 1)]);
+    }
+
+    #[test]
+    fn when_a_task_is_created_it_has_medium_priority() {
+        assert_eq!(
+            Task::new("Buy some milk".to_string()).priority(),
+            Priority::Medium
+        );
+    }
+
+    #[test]
+    fn when_a_task_is_created_it_has_no_due_date() {
+        assert_eq!(Task::new("Buy some milk".to_string()).due(), None);
+    }
+
+    #[test]
+    fn when_a_task_is_created_it_has_no_notes() {
+        assert_eq!(Task::new("Buy some milk".to_string()).notes(), None);
+    }
+
+    #[test]
+    fn when_a_task_is_created_it_has_no_tags() {
+        assert!(Task::new("Buy some milk".to_string()).tags().is_empty());
+    }
+
+    #[test]
+    fn tags_can_be_added_to_tasks_only_once() {
+        let mut task = Task::new("Buy some milk".to_string());
+        task.add_tag("errands".to_string());
+        task.add_tag("shopping".to_string());
+        task.add_tag("errands".to_string());
+
+        assert_eq!(task.tags(), ["errands", "shopping"]);
+    }
+
+    #[test]
+    fn tags_can_be_removed_from_tasks() {
+        let mut task = Task::new("Buy some milk".to_string());
+        task.add_tag("errands".to_string());
+        task.add_tag("shopping".to_string());
+
+        assert!(task.remove_tag("errands"));
+        assert!(!task.remove_tag("errands"));
+        assert_eq!(task.tags(), ["shopping"]);
+        assert!(!task.has_tag("errands"));
+        assert!(task.has_tag("shopping"));
+    }
+
+    #[test]
+    fn tags_are_displayed_after_title() {
+        let mut task = Task::new("Buy some milk".to_string());
+        task.set_due(Some(NaiveDate::from_ymd(2024, 5, 1)));
+        task.add_tag("errands".to_string());
+        task.add_tag("shopping".to_string());
+
+        assert_eq!(
+            format!("{}", task),
+            "• Buy some milk (due 2024-05-01) #errands #shopping"
+        );
+    }
+
+    #[test]
+    fn tags_can_be_renamed() {
+        let mut task = Task::new("Buy some milk".to_string());
+        task.add_tag("errands".to_string());
+        task.add_tag("shopping".to_string());
+
+        assert!(task.rename_tag("errands", "town"));
+        assert_eq!(task.tags(), ["town", "shopping"]);
+    }
+
+    #[test]
+    fn renaming_tag_to_one_the_task_already_has_does_not_duplicate_it() {
+        let mut task = Task::new("Buy some milk".to_string());
+        task.add_tag("errands".to_string());
+        task.add_tag("town".to_string());
+
+        assert!(task.rename_tag("errands", "town"));
+        assert_eq!(task.tags(), ["town"]);
+    }
+
+    #[test]
+    fn renaming_tag_the_task_does_not_have_does_nothing() {
+        let mut task = Task::new("Buy some milk".to_string());
+        task.add_tag("shopping".to_string());
+
+        assert!(!task.rename_tag("errands", "town"));
+        assert_eq!(task.tags(), ["shopping"]);
+    }
+
     #[test]
     fn the_status_of_a_task_can_be_accessed() {
         let mut task = Task {
             title: "Buy some milk".to_string(),
             status: Status::Incomplete,
             reminders: Vec::new(),
+            tags: Vec::new(),
+            priority: Priority::default(),
+            due: None,
+            notes: None,
+            created: Utc::now(),
+            completed: None,
+            recurrence: None,
+            pinned: false,
+            depends_on: Vec::new(),
+            subtasks: Vec::new(),
         };
         assert!(!task.is_complete());
 
@@ -99,17 +974,272 @@ mod tests {
             title: "Buy some milk".to_string(),
             status: Status::Incomplete,
             reminders: Vec::new(),
+            tags: Vec::new(),
+            priority: Priority::default(),
+            due: None,
+            notes: None,
+            created: Utc::now(),
+            completed: None,
+            recurrence: None,
+            pinned: false,
+            depends_on: Vec::new(),
+            subtasks: Vec::new(),
         };
 
         assert_eq!(format!("{}", task), "• Buy some milk");
     }
 
+    #[test]
+    fn due_date_is_displayed_after_title() {
+        let mut task = Task::new("Buy some milk".to_string());
+        task.set_due(Some(NaiveDate::from_ymd(2024, 5, 1)));
+
+        assert_eq!(format!("{}", task), "• Buy some milk (due 2024-05-01)");
+    }
+
+    #[test]
+    fn due_date_can_be_displayed_relative_to_today() {
+        let mut task = Task::new("Buy some milk".to_string());
+        task.set_due(Some(NaiveDate::from_ymd(2024, 5, 4)));
+
+        let options = RenderOptions {
+            today: Some(NaiveDate::from_ymd(2024, 5, 1)),
+            ..RenderOptions::default()
+        };
+
+        assert_eq!(
+            task.render(&RenderOptions {
+                dates: DateStyle::Relative,
+                ..options.clone()
+            }),
+            "• Buy some milk (due in 3 days)"
+        );
+        assert_eq!(task.render(&options), "• Buy some milk (due 2024-05-04)");
+    }
+
+    #[test]
+    fn recurrence_is_displayed_after_due_date() {
+        let mut task = Task::new("Water the plants".to_string());
+        task.set_due(Some(NaiveDate::from_ymd(2024, 5, 1)));
+        task.set_recurrence(Some(Recurrence::Weekly));
+
+        assert_eq!(
+            format!("{}", task),
+            "• Water the plants (due 2024-05-01) (every week)"
+        );
+    }
+
+    #[test]
+    fn task_can_be_read_from_json() {
+        let task = Task::from_json(
+            r#"{"title":"Fix the fence","tags":["home","weekend"],"due":"2024-05-01","priority":"High"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(task.title(), "Fix the fence");
+        assert_eq!(task.tags(), ["home", "weekend"]);
+        assert_eq!(task.due(), Some(NaiveDate::from_ymd(2024, 5, 1)));
+        assert_eq!(task.priority(), Priority::High);
+        assert_eq!(task.status(), Status::Incomplete);
+    }
+
+    #[test]
+    fn task_read_from_json_as_complete_is_given_a_completion_time() {
+        let task = Task::from_json(r#"{"title":"Fix the fence","status":"Complete"}"#).unwrap();
+
+        assert!(task.is_complete());
+        assert!(task.completed().is_some());
+    }
+
+    #[test]
+    fn task_read_from_json_as_incomplete_has_no_completion_time() {
+        let task = Task::from_json(
+            r#"{"title":"Fix the fence","status":"InProgress","completed":"2024-05-01T09:00:00Z"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(task.completed(), None);
+    }
+
+    #[test]
+    fn task_json_with_unknown_fields_is_refused() {
+        let error = Task::from_json(r#"{"title":"Fix the fence","tgas":["home"]}"#).unwrap_err();
+
+        assert!(
+            error.to_string().contains("unknown field `tgas`"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn exported_task_can_be_read_back_from_json() {
+        let mut task = Task::new("Fix the fence".to_string());
+        task.add_tag("home".to_string());
+        task.set_due(Some(NaiveDate::from_ymd(2024, 5, 1)));
+        task.complete();
+
+        assert_eq!(
+            Task::from_json(&serde_json::to_string(&task).unwrap()).unwrap(),
+            task
+        );
+    }
+
+    #[test]
+    fn tasks_without_due_date_from_older_databases_can_be_deserialized() {
+        let task: Task = serde_json::from_str(
+            r#"{"title":"Buy some milk","status":"Incomplete","reminders":[]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            task,
+            Task {
+                created: unknown_creation_time(),
+                ..Task::new("Buy some milk".to_string())
+            }
+        );
+    }
+
+    #[test]
+    fn statuses_can_be_drawn_with_custom_glyphs() {
+        let options = RenderOptions {
+            glyphs: render::Glyphs {
+                incomplete: Some("☐".to_string()),
+                complete: Some("✅".to_string()),
+                ..render::Glyphs::default()
+            },
+            ..RenderOptions::default()
+        };
+
+        let mut task = Task::new("Buy some milk".to_string());
+        assert_eq!(task.render(&options), "☐ Buy some milk");
+
+        task.complete();
+        assert_eq!(task.render(&options), "✅ Buy some milk");
+
+        task.start();
+        assert_eq!(task.render(&options), "◐ Buy some milk");
+    }
+
+    #[test]
+    fn custom_glyphs_do_not_replace_ascii_glyphs() {
+        let options = RenderOptions {
+            status_style: StatusStyle::Ascii,
+            glyphs: render::Glyphs {
+                incomplete: Some("☐".to_string()),
+                ..render::Glyphs::default()
+            },
+            ..RenderOptions::default()
+        };
+
+        assert_eq!(
+            Task::new("Buy some milk".to_string()).render(&options),
+            "[ ] Buy some milk"
+        );
+    }
+
+    #[test]
+    fn statuses_are_colored_when_using_color() {
+        let options = RenderOptions {
+            use_color: true,
+            ..RenderOptions::default()
+        };
+
+        let mut task = Task::new("Buy some milk".to_string());
+        assert_eq!(task.render(&options), "\x1b[36m•\x1b[0m Buy some milk");
+
+        task.complete();
+        assert_eq!(task.render(&options), "\x1b[2m–\x1b[0m Buy some milk");
+    }
+
+    #[test]
+    fn statuses_can_be_rendered_as_checkboxes() {
+        let options = RenderOptions {
+            status_style: StatusStyle::Ascii,
+            ..RenderOptions::default()
+        };
+
+        let mut task = Task::new("Buy some milk".to_string());
+        assert_eq!(task.render(&options), "[ ] Buy some milk");
+
+        task.complete();
+        assert_eq!(task.render(&options), "[x] Buy some milk");
+    }
+
+    #[test]
+    fn detail_lists_every_field() {
+        let mut task = Task::new("Fix the fence".to_string());
+        task.set_due(Some(NaiveDate::from_ymd(2024, 5, 1)));
+        task.add_tag("home".to_string());
+        task.add_tag("weekend".to_string());
+        task.set_priority(Priority::High);
+        task.set_notes(Some("Buy nails first\nBorrow a hammer".to_string()));
+
+        assert_eq!(
+            task.render_detail(&RenderOptions::default()),
+            "\
+Title:    Fix the fence
+Status:   • incomplete
+Due:      2024-05-01
+Tags:     #home #weekend
+Priority: high
+Notes:    Buy nails first
+          Borrow a hammer
+"
+        );
+    }
+
+    #[test]
+    fn detail_shows_due_date_in_chosen_style() {
+        let mut task = Task::new("Fix the fence".to_string());
+        task.set_due(Some(NaiveDate::from_ymd(2024, 5, 4)));
+
+        let options = RenderOptions {
+            today: Some(NaiveDate::from_ymd(2024, 5, 1)),
+            dates: DateStyle::Relative,
+            ..RenderOptions::default()
+        };
+
+        assert!(task
+            .render_detail(&options)
+            .contains("Due:      in 3 days\n"));
+    }
+
+    #[test]
+    fn detail_marks_missing_fields_as_none() {
+        let mut task = Task::new("Buy some milk".to_string());
+        task.complete();
+
+        assert_eq!(
+            task.render_detail(&RenderOptions::default()),
+            "\
+Title:    Buy some milk
+Status:   – complete
+Due:      none
+Tags:     none
+Priority: medium
+Notes:    none
+"
+        );
+    }
+
     #[test]
     fn complete_tasks_get_en_dash() {
         let task = Task {
             title: "Buy some milk".to_string(),
             status: Status::Complete,
             reminders: Vec::new(),
+            tags: Vec::new(),
+            priority: Priority::default(),
+            due: None,
+            notes: None,
+            created: Utc::now(),
+            completed: None,
+            recurrence: None,
+            pinned: false,
+            depends_on: Vec::new(),
+            subtasks: Vec::new(),
         };
 
         assert_eq!(format!("{}", task), "– Buy some milk");
@@ -1,239 +1,2549 @@
+use crate::priority::Priority;
+use crate::recurrence::Recurrence;
+use crate::render;
+use crate::render::GroupBy;
+use crate::render::RenderOptions;
+use crate::render::TaskOrder;
+use crate::status::Status;
 use crate::task::Task;
-use indexmap::map::Entry;
+use chrono::Date;
+use chrono::Local;
+use chrono::NaiveDate;
+use chrono::TimeZone;
 use indexmap::IndexMap;
 use serde::Deserialize;
 use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fmt;
+use std::ops::RangeInclusive;
 use thiserror::Error;
+use unicode_width::UnicodeWidthStr;
 
 #[derive(Debug, Error, PartialEq)]
 pub enum Error {
     #[error("task with ID {0} does not exist")]
-    NonExistentTaskId(u8),
+    NonExistentTaskId(u32),
+    #[error("there are no task IDs left to give to a new task")]
+    NoAvailableTaskId,
+    #[error("task ID {0} is already taken")]
+    TaskIdTaken(u32),
+    #[error("task {id} cannot depend on task {on}, since task {on} already depends on task {id}")]
+    DependencyCycle { id: u32, on: u32 },
+    #[error("task {id} does not have a subtask {index}")]
+    NonExistentSubtask { id: u32, index: usize },
+    #[error("no incomplete task has a title containing ‘{0}’")]
+    NoMatchingTask(String),
+    #[error("‘{text}’ matches several tasks: {}", format_candidates(.candidates))]
+    AmbiguousTaskTitle {
+        text: String,
+        candidates: Vec<(u32, String)>,
+    },
+}
+
+fn format_candidates(candidates: &[(u32, String)]) -> String {
+    candidates
+        .iter()
+        .map(|(id, title)| format!("[{}] {}", id, title))
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TaskList {
-    tasks: IndexMap<u8, Task>,
+    tasks: IndexMap<u32, Task>,
+    #[serde(default)]
+    default_priority: Option<Priority>,
+    #[serde(default)]
+    default_tags: Vec<String>,
 }
 
-impl TaskList {
-    pub(crate) fn is_empty(&self) -> bool {
-        self.tasks.is_empty()
+impl TaskList {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (u32, &Task)> {
+        self.tasks.iter().map(|(id, task)| (*id, task))
+    }
+
+    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = (u32, &mut Task)> {
+        self.tasks.iter_mut().map(|(id, task)| (*id, task))
+    }
+
+    /// Counts the tasks with a status, or every task if no status is given.
+    pub fn count(&self, filter: Option<Status>) -> usize {
+        match filter {
+            Some(status) => self
+                .tasks
+                .values()
+                .filter(|task| task.status() == status)
+                .count(),
+            None => self.tasks.len(),
+        }
+    }
+
+    /// Returns the task with the given ID, if there is one.
+    pub fn get(&self, id: u32) -> Option<&Task> {
+        self.tasks.get(&id)
+    }
+
+    /// Returns the ID of the first task whose title is exactly the given one, case included.
+    pub fn find_by_exact_title(&self, title: &str) -> Option<u32> {
+        self.iter()
+            .find(|(_, task)| task.title() == title)
+            .map(|(id, _)| id)
+    }
+
+    /// Adds a task with the lowest available ID, returning that ID.
+    pub fn add_task(&mut self, task: Task) -> Result<u32, Error> {
+        let id = (0..=u32::MAX)
+            .find(|id| !self.tasks.contains_key(id))
+            .ok_or(Error::NoAvailableTaskId)?;
+
+        self.tasks.insert(id, task);
+
+        Ok(id)
+    }
+
+    /// Adds a task read from elsewhere, such as with [`Task::from_json`], like
+    /// [`TaskList::add_task`] does, as long as every task it depends on is in the task list.
+    pub fn add_task_checking_dependencies(&mut self, task: Task) -> Result<u32, Error> {
+        if let Some(&on) = task
+            .depends_on()
+            .iter()
+            .find(|&&on| !self.tasks.contains_key(&on))
+        {
+            return Err(Error::NonExistentTaskId(on));
+        }
+
+        self.add_task(task)
+    }
+
+    /// Creates a task ready to be added with [`TaskList::add_task`]. The task list’s default
+    /// priority and tags are used unless a priority or tags are given.
+    pub fn new_task(
+        &self,
+        title: String,
+        priority: Option<Priority>,
+        tags: Option<Vec<String>>,
+    ) -> Task {
+        let mut task = Task::new(title);
+
+        if let Some(priority) = priority.or(self.default_priority) {
+            task.set_priority(priority);
+        }
+
+        for tag in tags.unwrap_or_else(|| self.default_tags.clone()) {
+            task.add_tag(tag);
+        }
+
+        task
+    }
+
+    /// Sets the priority and tags that tasks created with [`TaskList::new_task`] start with.
+    pub fn set_defaults(&mut self, priority: Option<Priority>, tags: Vec<String>) {
+        self.default_priority = priority;
+        self.default_tags = tags;
+    }
+
+    pub fn remove_task(&mut self, id: u32) -> Result<(), Error> {
+        self.tasks
+            .shift_remove(&id)
+            .ok_or(Error::NonExistentTaskId(id))?;
+        self.forget_removed_dependencies();
+
+        Ok(())
+    }
+
+    /// Drops dependencies on tasks that have been removed, so that a task later given one of their
+    /// IDs isn’t mistaken for the task depended on.
+    fn forget_removed_dependencies(&mut self) {
+        let ids: HashSet<_> = self.tasks.keys().copied().collect();
+
+        for task in self.tasks.values_mut() {
+            task.retain_dependencies(|id| ids.contains(&id));
+        }
+    }
+
+    /// Adds a copy of every task in another task list with the lowest available IDs, keeping their
+    /// dependencies on each other pointing at the same tasks.
+    pub(crate) fn append(&mut self, other: &TaskList) -> Result<(), Error> {
+        let mut new_ids = HashMap::new();
+
+        for (id, task) in other.iter() {
+            new_ids.insert(id, self.add_task(task.clone())?);
+        }
+
+        for new_id in new_ids.values() {
+            self.tasks[new_id].remap_dependencies(&new_ids);
+        }
+
+        Ok(())
+    }
+
+    /// Adds a task with a particular ID rather than the lowest available one, refusing to replace
+    /// a task that already has that ID.
+    pub fn insert_task_with_id(&mut self, id: u32, task: Task) -> Result<(), Error> {
+        if self.tasks.contains_key(&id) {
+            return Err(Error::TaskIdTaken(id));
+        }
+
+        self.tasks.insert(id, task);
+
+        Ok(())
+    }
+
+    /// Adds an incomplete copy of a task with the lowest available ID, returning that ID.
+    pub fn duplicate_task(&mut self, id: u32) -> Result<u32, Error> {
+        let duplicate = self
+            .tasks
+            .get(&id)
+            .ok_or(Error::NonExistentTaskId(id))?
+            .duplicate();
+
+        self.add_task(duplicate)
+    }
+
+    /// Removes every task whose ID is in the range, returning how many were removed. IDs in the
+    /// range without a task are skipped.
+    pub fn remove_range(&mut self, ids: RangeInclusive<u32>) -> usize {
+        let len_before = self.tasks.len();
+        self.tasks.retain(|id, _| !ids.contains(id));
+        self.forget_removed_dependencies();
+
+        len_before - self.tasks.len()
+    }
+
+    /// Removes a task, handing it back so it can be added somewhere else. The task no longer
+    /// depends on anything, since the IDs it depended on only mean something in this task list.
+    pub(crate) fn take_task(&mut self, id: u32) -> Result<Task, Error> {
+        let mut task = self
+            .tasks
+            .shift_remove(&id)
+            .ok_or(Error::NonExistentTaskId(id))?;
+
+        task.retain_dependencies(|_| false);
+        self.forget_removed_dependencies();
+
+        Ok(task)
+    }
+
+    /// Swaps a task with the one displayed before it. Moving the first task up does nothing.
+    pub fn move_up(&mut self, id: u32) -> Result<(), Error> {
+        let i = self
+            .tasks
+            .get_index_of(&id)
+            .ok_or(Error::NonExistentTaskId(id))?;
+
+        if i > 0 {
+            self.tasks.swap_indices(i, i - 1);
+        }
+
+        Ok(())
+    }
+
+    /// Swaps a task with the one displayed after it. Moving the last task down does nothing.
+    pub fn move_down(&mut self, id: u32) -> Result<(), Error> {
+        let i = self
+            .tasks
+            .get_index_of(&id)
+            .ok_or(Error::NonExistentTaskId(id))?;
+
+        if i + 1 < self.tasks.len() {
+            self.tasks.swap_indices(i, i + 1);
+        }
+
+        Ok(())
+    }
+
+    /// Swaps where two tasks are displayed. Each task keeps its ID.
+    pub fn swap(&mut self, id_a: u32, id_b: u32) -> Result<(), Error> {
+        let a = self
+            .tasks
+            .get_index_of(&id_a)
+            .ok_or(Error::NonExistentTaskId(id_a))?;
+        let b = self
+            .tasks
+            .get_index_of(&id_b)
+            .ok_or(Error::NonExistentTaskId(id_b))?;
+
+        self.tasks.swap_indices(a, b);
+
+        Ok(())
+    }
+
+    /// Reorders the tasks alphabetically by title, ignoring case. Tasks keep their IDs.
+    pub fn sort_by_title(&mut self) {
+        self.tasks.sort_by(|_, task_a, _, task_b| {
+            task_a
+                .title()
+                .to_lowercase()
+                .cmp(&task_b.title().to_lowercase())
+        });
+    }
+
+    /// Reorders the tasks so that completed ones come last, keeping the order of tasks with the
+    /// same status. Tasks keep their IDs.
+    pub fn sort_by_status(&mut self) {
+        self.tasks
+            .sort_by(|_, task_a, _, task_b| task_a.is_complete().cmp(&task_b.is_complete()));
+    }
+
+    pub fn rename_task(&mut self, id: u32, new_title: String) -> Result<(), Error> {
+        self.tasks.get_mut(&id).map_or_else(
+            || Err(Error::NonExistentTaskId(id)),
+            |task| {
+                task.rename(new_title);
+                Ok(())
+            },
+        )
+    }
+
+    /// Replaces a task’s title with one worked out from its current title.
+    pub fn edit_title(&mut self, id: u32, edit: impl FnOnce(&str) -> String) -> Result<(), Error> {
+        self.tasks.get_mut(&id).map_or_else(
+            || Err(Error::NonExistentTaskId(id)),
+            |task| {
+                let new_title = edit(task.title());
+                task.rename(new_title);
+                Ok(())
+            },
+        )
+    }
+
+    /// Completes a task. Recurring tasks stay incomplete and come due again one interval after
+    /// their due date instead, or one interval from today if they have no due date.
+    pub fn complete_task(&mut self, id: u32) -> Result<(), Error> {
+        self.tasks.get_mut(&id).map_or_else(
+            || Err(Error::NonExistentTaskId(id)),
+            |task| {
+                match task.recurrence() {
+                    Some(recurrence) => {
+                        let due = task.due().unwrap_or_else(|| Local::today().naive_local());
+                        task.set_due(Some(recurrence.advance(due)));
+                    }
+                    None => task.complete(),
+                }
+
+                Ok(())
+            },
+        )
+    }
+
+    /// Completes the one incomplete task whose title contains some text, ignoring case, and returns
+    /// its ID. Cancelled tasks aren’t considered. Nothing is completed if no task matches or
+    /// several do.
+    pub fn complete_by_title(&mut self, text: &str) -> Result<u32, Error> {
+        let lowercase_text = text.to_lowercase();
+
+        let candidates: Vec<_> = self
+            .iter()
+            .filter(|(_, task)| {
+                !task.is_finished() && task.title().to_lowercase().contains(&lowercase_text)
+            })
+            .map(|(id, task)| (id, task.title().to_string()))
+            .collect();
+
+        match candidates.as_slice() {
+            [] => Err(Error::NoMatchingTask(text.to_string())),
+            [(id, _)] => {
+                let id = *id;
+                self.complete_task(id)?;
+                Ok(id)
+            }
+            _ => Err(Error::AmbiguousTaskTitle {
+                text: text.to_string(),
+                candidates,
+            }),
+        }
+    }
+
+    /// Completes every task with one of the given IDs, even if some of the IDs don’t exist. Those
+    /// that don’t are returned as the error.
+    pub fn complete_tasks(&mut self, ids: &[u32]) -> Result<(), Vec<u32>> {
+        let non_existent_ids: Vec<_> = ids
+            .iter()
+            .copied()
+            .filter(|id| self.complete_task(*id).is_err())
+            .collect();
+
+        if non_existent_ids.is_empty() {
+            Ok(())
+        } else {
+            Err(non_existent_ids)
+        }
+    }
+
+    /// Marks a task as incomplete again. Tasks that are already incomplete are left as they are.
+    pub fn uncomplete_task(&mut self, id: u32) -> Result<(), Error> {
+        self.tasks.get_mut(&id).map_or_else(
+            || Err(Error::NonExistentTaskId(id)),
+            |task| {
+                task.uncomplete();
+                Ok(())
+            },
+        )
+    }
+
+    /// Marks a task as being worked on.
+    pub fn start_task(&mut self, id: u32) -> Result<(), Error> {
+        self.tasks.get_mut(&id).map_or_else(
+            || Err(Error::NonExistentTaskId(id)),
+            |task| {
+                task.start();
+                Ok(())
+            },
+        )
+    }
+
+    /// Marks a task as dropped rather than done.
+    pub fn cancel_task(&mut self, id: u32) -> Result<(), Error> {
+        self.tasks.get_mut(&id).map_or_else(
+            || Err(Error::NonExistentTaskId(id)),
+            |task| {
+                task.cancel();
+                Ok(())
+            },
+        )
+    }
+
+    pub fn set_priority(&mut self, id: u32, priority: Priority) -> Result<(), Error> {
+        self.tasks.get_mut(&id).map_or_else(
+            || Err(Error::NonExistentTaskId(id)),
+            |task| {
+                task.set_priority(priority);
+                Ok(())
+            },
+        )
+    }
+
+    pub fn set_recurrence(&mut self, id: u32, recurrence: Option<Recurrence>) -> Result<(), Error> {
+        self.tasks.get_mut(&id).map_or_else(
+            || Err(Error::NonExistentTaskId(id)),
+            |task| {
+                task.set_recurrence(recurrence);
+                Ok(())
+            },
+        )
+    }
+
+    pub fn set_due(&mut self, id: u32, due: Option<NaiveDate>) -> Result<(), Error> {
+        self.tasks.get_mut(&id).map_or_else(
+            || Err(Error::NonExistentTaskId(id)),
+            |task| {
+                task.set_due(due);
+                Ok(())
+            },
+        )
+    }
+
+    /// Sets the free-form notes of a task. Blank notes are removed entirely.
+    pub fn set_notes(&mut self, id: u32, notes: Option<String>) -> Result<(), Error> {
+        let notes = notes.filter(|notes| !notes.trim().is_empty());
+
+        self.tasks.get_mut(&id).map_or_else(
+            || Err(Error::NonExistentTaskId(id)),
+            |task| {
+                task.set_notes(notes);
+                Ok(())
+            },
+        )
+    }
+
+    /// Returns the notes of a task, if it has any.
+    pub fn notes(&self, id: u32) -> Result<Option<&str>, Error> {
+        self.tasks
+            .get(&id)
+            .map(Task::notes)
+            .ok_or(Error::NonExistentTaskId(id))
+    }
+
+    /// Adds a reminder to a task. Adding a reminder the task already has does nothing.
+    pub fn add_reminder(&mut self, id: u32, date: NaiveDate) -> Result<(), Error> {
+        self.tasks.get_mut(&id).map_or_else(
+            || Err(Error::NonExistentTaskId(id)),
+            |task| {
+                task.add_reminder(date);
+                Ok(())
+            },
+        )
+    }
+
+    pub fn remove_reminder(&mut self, id: u32, date: NaiveDate) -> Result<(), Error> {
+        self.tasks.get_mut(&id).map_or_else(
+            || Err(Error::NonExistentTaskId(id)),
+            |task| {
+                task.remove_reminder(date);
+                Ok(())
+            },
+        )
+    }
+
+    /// Makes a task depend on another task in the task list, so that it is blocked until the
+    /// other task is finished. Dependencies that would form a cycle are refused.
+    pub fn add_dependency(&mut self, id: u32, on: u32) -> Result<(), Error> {
+        if !self.tasks.contains_key(&on) {
+            return Err(Error::NonExistentTaskId(on));
+        }
+
+        if !self.tasks.contains_key(&id) {
+            return Err(Error::NonExistentTaskId(id));
+        }
+
+        if self.depends_on(on, id) {
+            return Err(Error::DependencyCycle { id, on });
+        }
+
+        self.tasks[&id].add_dependency(on);
+
+        Ok(())
+    }
+
+    pub fn remove_dependency(&mut self, id: u32, on: u32) -> Result<(), Error> {
+        self.tasks.get_mut(&id).map_or_else(
+            || Err(Error::NonExistentTaskId(id)),
+            |task| {
+                task.remove_dependency(on);
+                Ok(())
+            },
+        )
+    }
+
+    /// Returns whether a task depends on another, directly or through other tasks. Every task is
+    /// considered to depend on itself.
+    fn depends_on(&self, id: u32, on: u32) -> bool {
+        let mut to_visit = vec![id];
+        let mut visited = Vec::new();
+
+        while let Some(id) = to_visit.pop() {
+            if id == on {
+                return true;
+            }
+
+            if visited.contains(&id) {
+                continue;
+            }
+
+            visited.push(id);
+
+            if let Some(task) = self.tasks.get(&id) {
+                to_visit.extend(task.depends_on());
+            }
+        }
+
+        false
+    }
+
+    /// Finds the tasks a task depends on that are yet to be finished. Dependencies on tasks that
+    /// have since been removed or cancelled don’t count.
+    pub fn unmet_dependencies(&self, id: u32) -> Result<Vec<u32>, Error> {
+        let task = self.tasks.get(&id).ok_or(Error::NonExistentTaskId(id))?;
+
+        Ok(self.unmet_dependencies_of(task))
+    }
+
+    fn unmet_dependencies_of(&self, task: &Task) -> Vec<u32> {
+        task.depends_on()
+            .iter()
+            .copied()
+            .filter(|id| {
+                self.tasks.get(id).is_some_and(|dependency| {
+                    !dependency.is_complete() && !dependency.is_cancelled()
+                })
+            })
+            .collect()
+    }
+
+    /// Returns whether a task is still to be done but has to wait for other tasks first.
+    pub(crate) fn is_blocked(&self, task: &Task) -> bool {
+        !task.is_complete() && !task.is_cancelled() && !self.unmet_dependencies_of(task).is_empty()
+    }
+
+    /// Adds an incomplete subtask to a task after its existing subtasks, returning the subtask’s
+    /// index.
+    pub fn add_subtask(&mut self, id: u32, title: String) -> Result<usize, Error> {
+        self.tasks.get_mut(&id).map_or_else(
+            || Err(Error::NonExistentTaskId(id)),
+            |task| Ok(task.add_subtask(title)),
+        )
+    }
+
+    /// Completes the subtask at an index of a task. If `complete_parent` is set and that was the
+    /// last incomplete subtask, the task itself is completed too.
+    pub fn complete_subtask(
+        &mut self,
+        id: u32,
+        index: usize,
+        complete_parent: bool,
+    ) -> Result<(), Error> {
+        let task = self
+            .tasks
+            .get_mut(&id)
+            .ok_or(Error::NonExistentTaskId(id))?;
+
+        if !task.complete_subtask(index) {
+            return Err(Error::NonExistentSubtask { id, index });
+        }
+
+        let (num_complete, num_subtasks) = task.subtask_progress();
+
+        if complete_parent && num_complete == num_subtasks {
+            self.complete_task(id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Pins a task so that it is listed before every unpinned task, whatever its status or
+    /// priority.
+    pub fn pin(&mut self, id: u32) -> Result<(), Error> {
+        self.tasks.get_mut(&id).map_or_else(
+            || Err(Error::NonExistentTaskId(id)),
+            |task| {
+                task.set_pinned(true);
+                Ok(())
+            },
+        )
+    }
+
+    pub fn unpin(&mut self, id: u32) -> Result<(), Error> {
+        self.tasks.get_mut(&id).map_or_else(
+            || Err(Error::NonExistentTaskId(id)),
+            |task| {
+                task.set_pinned(false);
+                Ok(())
+            },
+        )
+    }
+
+    pub fn add_tag(&mut self, id: u32, tag: String) -> Result<(), Error> {
+        self.tasks.get_mut(&id).map_or_else(
+            || Err(Error::NonExistentTaskId(id)),
+            |task| {
+                task.add_tag(tag);
+                Ok(())
+            },
+        )
+    }
+
+    pub fn remove_tag(&mut self, id: u32, tag: &str) -> Result<(), Error> {
+        self.tasks.get_mut(&id).map_or_else(
+            || Err(Error::NonExistentTaskId(id)),
+            |task| {
+                task.remove_tag(tag);
+                Ok(())
+            },
+        )
+    }
+
+    /// Replaces a tag on every task that has it, returning how many tasks were changed.
+    pub(crate) fn rename_tag(&mut self, old: &str, new: &str) -> usize {
+        self.tasks
+            .values_mut()
+            .map(|task| task.rename_tag(old, new))
+            .filter(|renamed| *renamed)
+            .count()
+    }
+
+    /// Removes every completed task, returning how many were removed.
+    pub fn remove_completed_tasks(&mut self) -> usize {
+        let len_before = self.tasks.len();
+        self.tasks.retain(|_, task| !task.is_complete());
+        self.forget_removed_dependencies();
+
+        len_before - self.tasks.len()
+    }
+
+    /// Removes every completed task, handing them back in their usual order.
+    pub(crate) fn take_completed_tasks(&mut self) -> Vec<Task> {
+        let (completed, remaining) = self
+            .tasks
+            .drain(..)
+            .partition(|(_, task)| task.is_complete());
+
+        self.tasks = remaining;
+        self.forget_removed_dependencies();
+
+        completed.into_values().collect()
+    }
+
+    /// Removes the tasks completed before (not on) a date, going by the date they were completed
+    /// on in the time zone of `cutoff`, and returns how many were removed. Incomplete tasks, and
+    /// completed ones without a completion time, are kept.
+    pub fn remove_completed_before<Tz: TimeZone>(&mut self, cutoff: Date<Tz>) -> usize {
+        let len_before = self.tasks.len();
+        self.tasks.retain(|_, task| {
+            !(task.is_complete()
+                && task.completed().is_some_and(|completed| {
+                    completed.with_timezone(&cutoff.timezone()).date() < cutoff
+                }))
+        });
+        self.forget_removed_dependencies();
+
+        len_before - self.tasks.len()
+    }
+
+    /// Removes every cancelled task, returning how many were removed.
+    pub fn remove_cancelled_tasks(&mut self) -> usize {
+        let len_before = self.tasks.len();
+        self.tasks.retain(|_, task| !task.is_cancelled());
+        self.forget_removed_dependencies();
+
+        len_before - self.tasks.len()
+    }
+
+    /// Removes every task, so that IDs start from 0 again. Returns how many tasks were removed.
+    pub fn clear(&mut self) -> usize {
+        let len = self.tasks.len();
+        self.tasks.clear();
+
+        len
+    }
+
+    /// Renders the task list with titles wrapped to fit within `width` columns.
+    pub fn render_wrapped(&self, width: usize) -> String {
+        self.render(&RenderOptions {
+            width: Some(width),
+            ..RenderOptions::default()
+        })
+    }
+
+    pub fn render(&self, options: &RenderOptions) -> String {
+        render::transliterate_if_ascii(self.render_in_columns(self.columns(), options), options)
+    }
+
+    /// Renders the task list with the given columns, which may be wider than the task list needs
+    /// so that several task lists line up with each other.
+    pub(crate) fn render_in_columns(&self, columns: Columns, options: &RenderOptions) -> String {
+        match options.group_by {
+            None => self
+                .iter_in_order(options.order)
+                .map(|(id, task)| render_task(id, columns, task, self.is_blocked(task), options))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Some(GroupBy::Tag) => self.render_grouped_by_tag(columns, options),
+        }
+    }
+
+    /// Iterates over the tasks in the given order, except that pinned tasks come first.
+    fn iter_in_order(&self, order: TaskOrder) -> impl Iterator<Item = (u32, &Task)> {
+        let mut tasks: Vec<_> = match order {
+            TaskOrder::Priority => self.iter_by_priority().collect(),
+            TaskOrder::Age => self.iter_by_age().collect(),
+        };
+
+        // The sort is stable, so pinned and unpinned tasks each keep their order.
+        tasks.sort_by_key(|(_, task)| !task.is_pinned());
+
+        tasks.into_iter()
+    }
+
+    /// Iterates over the tasks from highest to lowest priority, keeping tasks of equal priority in
+    /// their usual order.
+    fn iter_by_priority(&self) -> impl Iterator<Item = (u32, &Task)> {
+        let mut tasks: Vec<_> = self.iter().collect();
+        tasks.sort_by_key(|(_, task)| Reverse(task.priority()));
+
+        tasks.into_iter()
+    }
+
+    /// Iterates over the tasks from oldest to newest, going by ID for tasks created at the same
+    /// time. The order the tasks are stored in is left alone.
+    pub fn iter_by_age(&self) -> impl Iterator<Item = (u32, &Task)> {
+        let mut tasks: Vec<_> = self.iter().collect();
+        tasks.sort_by_key(|(id, task)| (task.created(), *id));
+
+        tasks.into_iter()
+    }
+
+    fn render_grouped_by_tag(&self, columns: Columns, options: &RenderOptions) -> String {
+        let mut groups: IndexMap<&str, Vec<String>> = IndexMap::new();
+        let mut untagged = Vec::new();
+
+        // Tasks are indented beneath their tag.
+        let task_options = options.indented(2);
+
+        for (id, task) in self.iter_in_order(options.order) {
+            let line = render_task(id, columns, task, self.is_blocked(task), &task_options);
+
+            if task.tags().is_empty() {
+                untagged.push(line);
+                continue;
+            }
+
+            for tag in task.tags() {
+                groups.entry(tag).or_default().push(line.clone());
+            }
+        }
+
+        if !untagged.is_empty() {
+            groups.insert("(untagged)", untagged);
+        }
+
+        groups
+            .into_iter()
+            .map(|(tag, lines)| {
+                let lines: Vec<_> = lines
+                    .iter()
+                    .flat_map(|line| line.lines())
+                    .map(|line| format!("  {}", line))
+                    .collect();
+                format!("{}\n{}", tag, lines.join("\n"))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Works out how wide the columns before task titles need to be to line up every task.
+    pub(crate) fn columns(&self) -> Columns {
+        Columns {
+            // IDs are padded to at least three digits, or more if needed to keep them aligned.
+            id_width: self
+                .tasks
+                .keys()
+                .max()
+                .map_or(0, |max_id| max_id.to_string().len())
+                .max(3),
+            marker_width: self
+                .tasks
+                .values()
+                .map(|task| task.priority().marker().width())
+                .max()
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// The widths of the columns that come before each task’s status and title.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub(crate) struct Columns {
+    id_width: usize,
+    /// How wide priority markers are, which is zero if no task has one.
+    marker_width: usize,
+}
+
+impl Columns {
+    /// Returns columns wide enough for both sets of columns.
+    pub(crate) fn widest(self, other: Self) -> Self {
+        Self {
+            id_width: self.id_width.max(other.id_width),
+            marker_width: self.marker_width.max(other.marker_width),
+        }
+    }
+}
+
+fn render_task(
+    id: u32,
+    columns: Columns,
+    task: &Task,
+    blocked: bool,
+    options: &RenderOptions,
+) -> String {
+    let Columns {
+        id_width,
+        marker_width,
+    } = columns;
+
+    let prefix = if marker_width == 0 {
+        format!("[{:>width$}] ", id, width = id_width)
+    } else {
+        format!(
+            "[{:>id_width$}] {:<marker_width$} ",
+            id,
+            task.priority().marker(),
+            id_width = id_width,
+            marker_width = marker_width
+        )
+    };
+
+    let status = task.render_status(options);
+    let details = task.render_details(options);
+
+    let details = match options.width {
+        Some(width) => {
+            // Continuation lines are lined up with the title, just past the status and a space.
+            let status_width = options.glyph(&task.status()).width();
+            let indent = prefix.width() + status_width + 1;
+
+            wrap_details(&details, indent, width)
+        }
+        None => details,
+    };
+
+    let mut line = format!("{}{} {}", prefix, status, details);
+
+    // The marker goes after the details are wrapped, since its escape codes would throw off the
+    // width they are wrapped to.
+    if let Some(marker) = task.render_due_marker(options) {
+        line.push(' ');
+        line.push_str(&marker);
+    }
+
+    if blocked {
+        line.push(' ');
+        line.push_str(&if options.use_color {
+            render::dim("(blocked)")
+        } else {
+            "(blocked)".to_string()
+        });
+    }
+
+    let line = if options.use_color && options.dim_completed && task.is_complete() {
+        render::dim(&line)
+    } else {
+        line
+    };
+
+    // Subtasks are lined up with the task’s title, beneath it.
+    let subtask_indentation =
+        " ".repeat(prefix.width() + options.glyph(&task.status()).width() + 1);
+
+    let line = task.subtasks().iter().fold(line, |line, subtask| {
+        format!(
+            "{}\n{}{}",
+            line,
+            subtask_indentation,
+            subtask.render(options)
+        )
+    });
+
+    match task.notes() {
+        Some(notes) => {
+            // Notes are lined up with the task’s status, just past the ID.
+            let indentation = " ".repeat(id_width + 3);
+
+            let notes: Vec<_> = notes
+                .trim_end()
+                .lines()
+                .map(|line| format!("{}{}", indentation, line).trim_end().to_string())
+                .collect();
+
+            format!("{}\n{}", line, notes.join("\n"))
+        }
+        None => line,
+    }
+}
+
+/// Wraps a rendered task’s details so that they fit within `width` once `indent` columns of ID,
+/// priority marker and status come before them. Continuation lines are indented by `indent`.
+fn wrap_details(details: &str, indent: usize, width: usize) -> String {
+    render::wrap(details, width.saturating_sub(indent).max(1))
+        .join(&format!("\n{}", " ".repeat(indent)))
+}
+
+impl fmt::Display for TaskList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(&RenderOptions::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::StatusStyle;
+    use chrono::FixedOffset;
+    use chrono::Utc;
+
+    #[test]
+    fn emptiness_can_be_checked() {
+        let mut task_list = TaskList::default();
+        assert!(task_list.is_empty());
+        task_list
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        assert!(!task_list.is_empty());
+    }
+
+    #[test]
+    fn new_tasks_inherit_the_task_list_defaults() {
+        let mut task_list = TaskList::default();
+        task_list.set_defaults(Some(Priority::High), vec!["urgent".to_string()]);
+
+        let id = task_list
+            .add_task(task_list.new_task("Fix the fence".to_string(), None, None))
+            .unwrap();
+
+        let task = task_list.get(id).unwrap();
+        assert_eq!(task.priority(), Priority::High);
+        assert_eq!(task.tags(), ["urgent"]);
+    }
+
+    #[test]
+    fn explicit_priority_and_tags_override_the_task_list_defaults() {
+        let mut task_list = TaskList::default();
+        task_list.set_defaults(Some(Priority::High), vec!["urgent".to_string()]);
+
+        let task = task_list.new_task(
+            "Fix the fence".to_string(),
+            Some(Priority::Medium),
+            Some(vec!["garden".to_string()]),
+        );
+
+        assert_eq!(task.priority(), Priority::Medium);
+        assert_eq!(task.tags(), ["garden"]);
+    }
+
+    #[test]
+    fn new_tasks_without_task_list_defaults_are_plain() {
+        let task = TaskList::default().new_task("Fix the fence".to_string(), None, None);
+
+        assert_eq!(task.priority(), Priority::default());
+        assert!(task.tags().is_empty());
+    }
+
+    #[test]
+    fn added_tasks_are_left_as_they_are() {
+        let mut task_list = TaskList::default();
+        task_list.set_defaults(Some(Priority::High), vec!["urgent".to_string()]);
+
+        let id = task_list
+            .add_task(Task::new("Fix the fence".to_string()))
+            .unwrap();
+        task_list
+            .insert_task_with_id(5, Task::new("Buy some milk".to_string()))
+            .unwrap();
+        let duplicate_id = task_list.duplicate_task(id).unwrap();
+
+        for id in [id, 5, duplicate_id] {
+            let task = task_list.get(id).unwrap();
+            assert_eq!(task.priority(), Priority::default());
+            assert!(task.tags().is_empty());
+        }
+    }
+
+    #[test]
+    fn tasks_depending_on_non_existent_tasks_are_not_added() {
+        let mut task_list = task_list_with_tasks(&["Buy some milk"]);
+        let task = Task::from_json(r#"{"title":"Make tea","depends_on":[0,2]}"#).unwrap();
+
+        assert_eq!(
+            task_list.add_task_checking_dependencies(task.clone()),
+            Err(Error::NonExistentTaskId(2))
+        );
+        assert_eq!(task_list.tasks.len(), 1);
+
+        task_list
+            .add_task(Task::new("Call mum".to_string()))
+            .unwrap();
+        task_list
+            .add_task(Task::new("Fix the fence".to_string()))
+            .unwrap();
+        assert_eq!(task_list.add_task_checking_dependencies(task), Ok(3));
+    }
+
+    #[test]
+    fn tasks_can_be_added() {
+        let task_to_add = Task::new("Buy some milk".to_string());
+
+        let mut task_list = TaskList::default();
+        task_list.add_task(task_to_add.clone()).unwrap();
+
+        assert_eq!(
+            task_list,
+            TaskList {
+                tasks: {
+                    let mut tasks = IndexMap::new();
+                    tasks.insert(0, task_to_add);
+                    tasks
+                },
+                ..TaskList::default()
+            }
+        );
+    }
+
+    #[test]
+    fn ids_are_chosen_by_the_lowest_available_one() {
+        let task0 = Task::new("Buy some milk".to_string());
+        let task1 = Task::new("Learn Haskell".to_string());
+        let task2 = Task::new("Finish Chapter 10 of my novel".to_string());
+
+        let mut task_list = TaskList::default();
+        task_list.add_task(task0.clone()).unwrap();
+        task_list.add_task(task1.clone()).unwrap();
+        task_list.add_task(task2.clone()).unwrap();
+
+        assert_eq!(task_list.tasks[&0], task0);
+        assert_eq!(task_list.tasks[&1], task1);
+        assert_eq!(task_list.tasks[&2], task2);
+    }
+
+    #[test]
+    fn tasks_can_be_added_with_a_particular_id() {
+        let mut task_list = TaskList::default();
+        let task = Task::new("Buy some milk".to_string());
+
+        assert_eq!(task_list.insert_task_with_id(5, task.clone()), Ok(()));
+        assert_eq!(task_list.tasks[&5], task);
+
+        // IDs below the explicit one are still handed out as usual.
+        assert_eq!(
+            task_list.add_task(Task::new("Learn Haskell".to_string())),
+            Ok(0)
+        );
+    }
+
+    #[test]
+    fn adding_task_with_taken_id_gives_error() {
+        let mut task_list = task_list_with_tasks(&["Buy some milk"]);
+        let original = task_list.clone();
+
+        assert_eq!(
+            task_list.insert_task_with_id(0, Task::new("Learn Haskell".to_string())),
+            Err(Error::TaskIdTaken(0))
+        );
+        assert_eq!(task_list, original);
+    }
+
+    #[test]
+    fn adding_task_returns_its_id() {
+        let mut task_list = TaskList::default();
+
+        assert_eq!(
+            task_list.add_task(Task::new("Buy some milk".to_string())),
+            Ok(0)
+        );
+        assert_eq!(
+            task_list.add_task(Task::new("Learn Haskell".to_string())),
+            Ok(1)
+        );
+    }
+
+    #[test]
+    fn ids_keep_increasing_past_256_tasks() {
+        let mut task_list = TaskList::default();
+
+        for i in 0..300 {
+            task_list
+                .add_task(Task::new(format!("Task {}", i)))
+                .unwrap();
+        }
+
+        assert_eq!(task_list.tasks.len(), 300);
+        assert_eq!(
+            task_list.tasks.keys().copied().collect::<Vec<_>>(),
+            (0..300).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn tasks_can_be_removed_by_id() {
+        let mut task_list = TaskList::default();
+
+        task_list
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap(); // ID: 0
+        task_list
+            .add_task(Task::new("Learn Haskell".to_string()))
+            .unwrap(); // ID: 1
+        task_list.remove_task(0).unwrap();
+
+        // The task takes the lowest available ID, which is now 0.
+        task_list
+            .add_task(Task::new("Finish Chapter 10 of my novel".to_string()))
+            .unwrap();
+        task_list.remove_task(1).unwrap();
+        task_list.remove_task(0).unwrap();
+
+        assert!(task_list.tasks.is_empty());
+    }
+
+    #[test]
+    fn removing_a_task_keeps_the_order_of_the_rest() {
+        let mut task_list = task_list_with_tasks(&[
+            "Buy some milk",
+            "Learn Haskell",
+            "Call mum",
+            "Fix the fence",
+        ]);
+        task_list.move_up(3).unwrap();
+        task_list.remove_task(1).unwrap();
+
+        assert_eq!(
+            format!("{}", task_list),
+            "\
+[  0] ! • Buy some milk
+[  3] ! • Fix the fence
+[  2] ! • Call mum"
+        );
+    }
+
+    #[test]
+    fn completed_tasks_are_duplicated_as_incomplete() {
+        let mut task_list = task_list_with_tasks(&["Buy some milk", "Learn Haskell"]);
+        task_list.add_tag(0, "errands".to_string()).unwrap();
+        task_list.complete_task(0).unwrap();
+
+        assert_eq!(task_list.duplicate_task(0), Ok(2));
+        assert_eq!(task_list.tasks[&2].title(), "Buy some milk");
+        assert_eq!(task_list.tasks[&2].tags(), ["errands"]);
+        assert!(!task_list.tasks[&2].is_complete());
+        assert!(task_list.tasks[&0].is_complete());
+    }
+
+    #[test]
+    fn duplicating_non_existent_task_gives_error() {
+        let mut task_list = TaskList::default();
+
+        assert_eq!(
+            task_list.duplicate_task(5),
+            Err(Error::NonExistentTaskId(5))
+        );
+    }
+
+    #[test]
+    fn ranges_of_tasks_can_be_removed_skipping_gaps() {
+        let mut task_list = task_list_with_tasks(&[
+            "Buy some milk",
+            "Learn Haskell",
+            "Call mum",
+            "Fix the fence",
+            "Post letter",
+            "Write report",
+        ]);
+        task_list.remove_task(2).unwrap();
+
+        assert_eq!(task_list.remove_range(1..=4), 3);
+
+        let ids: Vec<_> = task_list.iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, [0, 5]);
+    }
+
+    #[test]
+    fn removing_range_without_tasks_removes_nothing() {
+        let mut task_list = task_list_with_tasks(&["Buy some milk"]);
+
+        assert_eq!(task_list.remove_range(3..=9), 0);
+        assert!(!task_list.is_empty());
+    }
+
+    #[test]
+    fn removing_non_existent_task_gives_error() {
+        let mut task_list = TaskList::default();
+
+        assert_eq!(
+            task_list.remove_task(123),
+            Err(Error::NonExistentTaskId(123))
+        );
+    }
+
+    fn task_list_with_tasks(titles: &[&str]) -> TaskList {
+        let mut task_list = TaskList::default();
+
+        for title in titles {
+            task_list.add_task(Task::new(title.to_string())).unwrap();
+        }
+
+        task_list
+    }
+
+    #[test]
+    fn text_can_be_prepended_and_appended_to_titles() {
+        let mut task_list = task_list_with_tasks(&["Buy some milk"]);
+
+        task_list
+            .edit_title(0, |title| format!("(urgent) {}", title))
+            .unwrap();
+        task_list
+            .edit_title(0, |title| format!("{} today", title))
+            .unwrap();
+
+        assert_eq!(task_list.tasks[&0].title(), "(urgent) Buy some milk today");
+    }
+
+    #[test]
+    fn editing_title_of_non_existent_task_gives_error() {
+        assert_eq!(
+            TaskList::default().edit_title(0, str::to_string),
+            Err(Error::NonExistentTaskId(0))
+        );
+    }
+
+    #[test]
+    fn tasks_can_be_iterated_from_oldest_to_newest() {
+        let mut task_list = TaskList::default();
+        task_list
+            .insert_task_with_id(
+                0,
+                Task::with_times(
+                    "Call mum",
+                    Status::Incomplete,
+                    Some("2024-05-03T09:00:00Z"),
+                    None,
+                ),
+            )
+            .unwrap();
+        task_list
+            .insert_task_with_id(
+                1,
+                Task::with_times(
+                    "Buy some milk",
+                    Status::Incomplete,
+                    Some("2024-05-01T09:00:00Z"),
+                    None,
+                ),
+            )
+            .unwrap();
+        task_list
+            .insert_task_with_id(
+                3,
+                Task::with_times(
+                    "Learn Haskell",
+                    Status::Incomplete,
+                    Some("2024-05-02T09:00:00Z"),
+                    None,
+                ),
+            )
+            .unwrap();
+        task_list
+            .insert_task_with_id(
+                2,
+                Task::with_times(
+                    "Fix the fence",
+                    Status::Incomplete,
+                    Some("2024-05-02T09:00:00Z"),
+                    None,
+                ),
+            )
+            .unwrap();
+
+        assert_eq!(
+            task_list
+                .iter_by_age()
+                .map(|(id, task)| (id, task.title()))
+                .collect::<Vec<_>>(),
+            [
+                (1, "Buy some milk"),
+                (2, "Fix the fence"),
+                (3, "Learn Haskell"),
+                (0, "Call mum")
+            ]
+        );
+
+        // Only the order tasks are displayed in changes.
+        assert_eq!(
+            task_list.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            [0, 1, 3, 2]
+        );
+        assert_eq!(
+            task_list.render(&RenderOptions {
+                order: TaskOrder::Age,
+                ..RenderOptions::default()
+            }),
+            "\
+[  1] ! • Buy some milk
+[  2] ! • Fix the fence
+[  3] ! • Learn Haskell
+[  0] ! • Call mum"
+        );
+    }
+
+    #[test]
+    fn tasks_with_unfinished_dependencies_are_rendered_as_blocked() {
+        let mut task_list = task_list_with_tasks(&["Buy paint", "Paint the fence", "Buy brushes"]);
+        task_list.add_dependency(1, 0).unwrap();
+        task_list.add_dependency(1, 2).unwrap();
+        task_list.complete_task(0).unwrap();
+
+        assert_eq!(task_list.unmet_dependencies(1), Ok(vec![2]));
+        assert_eq!(
+            task_list.to_string(),
+            "\
+[  0] ! – Buy paint
+[  1] ! • Paint the fence (blocked)
+[  2] ! • Buy brushes"
+        );
+
+        task_list.complete_task(2).unwrap();
+
+        assert_eq!(task_list.unmet_dependencies(1), Ok(Vec::new()));
+        assert!(!task_list.to_string().contains("(blocked)"));
+    }
+
+    #[test]
+    fn removed_dependencies_no_longer_block() {
+        let mut task_list = task_list_with_tasks(&["Buy paint", "Paint the fence"]);
+        task_list.add_dependency(1, 0).unwrap();
+        task_list.remove_dependency(1, 0).unwrap();
+
+        assert!(!task_list.to_string().contains("(blocked)"));
+    }
+
+    #[test]
+    fn removing_a_task_forgets_dependencies_on_it() {
+        let mut task_list = task_list_with_tasks(&["Buy paint", "Paint the fence"]);
+        task_list.add_dependency(1, 0).unwrap();
+        task_list.remove_task(0).unwrap();
+        task_list
+            .add_task(Task::new("Unrelated new task".to_string()))
+            .unwrap();
+
+        assert_eq!(task_list.unmet_dependencies(1), Ok(Vec::new()));
+        assert!(!task_list.to_string().contains("(blocked)"));
+    }
+
+    #[test]
+    fn removing_tasks_in_bulk_forgets_dependencies_on_them() {
+        let mut task_list =
+            task_list_with_tasks(&["Buy paint", "Buy brushes", "Paint the fence", "Tidy up"]);
+        task_list.add_dependency(2, 0).unwrap();
+        task_list.add_dependency(2, 1).unwrap();
+        task_list.add_dependency(3, 2).unwrap();
+        task_list.complete_task(0).unwrap();
+
+        task_list.remove_completed_tasks();
+        assert_eq!(task_list.tasks[&2].depends_on(), [1]);
+
+        task_list.remove_range(1..=2);
+        assert!(task_list.tasks[&3].depends_on().is_empty());
+    }
+
+    #[test]
+    fn taken_task_no_longer_has_dependencies() {
+        let mut task_list = task_list_with_tasks(&["Buy paint", "Paint the fence"]);
+        task_list.add_dependency(1, 0).unwrap();
+        task_list.add_dependency(0, 1).unwrap_err();
+
+        let task = task_list.take_task(1).unwrap();
+
+        assert!(task.depends_on().is_empty());
+    }
+
+    #[test]
+    fn appended_tasks_keep_their_dependencies_on_each_other() {
+        let mut task_list = task_list_with_tasks(&["Write report"]);
+        let mut other = task_list_with_tasks(&["Buy paint", "Paint the fence"]);
+        other.add_dependency(1, 0).unwrap();
+
+        task_list.append(&other).unwrap();
+
+        assert_eq!(task_list.tasks[&2].depends_on(), [1]);
+    }
+
+    #[test]
+    fn dependency_cycles_are_refused() {
+        let mut task_list = task_list_with_tasks(&["Buy paint", "Paint the fence", "Admire it"]);
+        task_list.add_dependency(1, 0).unwrap();
+        task_list.add_dependency(2, 1).unwrap();
+        let original = task_list.clone();
+
+        assert_eq!(
+            task_list.add_dependency(0, 2),
+            Err(Error::DependencyCycle { id: 0, on: 2 })
+        );
+        assert_eq!(
+            task_list.add_dependency(1, 1),
+            Err(Error::DependencyCycle { id: 1, on: 1 })
+        );
+        assert_eq!(task_list, original);
+    }
+
+    #[test]
+    fn depending_on_non_existent_task_gives_error() {
+        let mut task_list = task_list_with_tasks(&["Buy paint"]);
+
+        assert_eq!(
+            task_list.add_dependency(0, 5),
+            Err(Error::NonExistentTaskId(5))
+        );
+        assert_eq!(
+            task_list.add_dependency(5, 0),
+            Err(Error::NonExistentTaskId(5))
+        );
+    }
+
+    #[test]
+    fn subtasks_are_rendered_beneath_their_task() {
+        let mut task_list = task_list_with_tasks(&["Buy some milk", "Fix the fence"]);
+        task_list.add_subtask(1, "Buy nails".to_string()).unwrap();
+        task_list.add_subtask(1, "Paint".to_string()).unwrap();
+        task_list.complete_subtask(1, 0, false).unwrap();
+
+        assert_eq!(
+            task_list.to_string(),
+            "\
+[  0] ! • Buy some milk
+[  1] ! • Fix the fence [1/2]
+          – Buy nails
+          • Paint"
+        );
+    }
+
+    #[test]
+    fn completing_subtask_that_does_not_exist_gives_error() {
+        let mut task_list = task_list_with_tasks(&["Fix the fence"]);
+        task_list.add_subtask(0, "Buy nails".to_string()).unwrap();
+
+        assert_eq!(
+            task_list.complete_subtask(0, 1, false),
+            Err(Error::NonExistentSubtask { id: 0, index: 1 })
+        );
+        assert_eq!(
+            task_list.add_subtask(1, "Paint".to_string()),
+            Err(Error::NonExistentTaskId(1))
+        );
+    }
+
+    #[test]
+    fn completing_last_subtask_can_complete_the_task() {
+        let mut task_list = task_list_with_tasks(&["Fix the fence", "Paint the shed"]);
+
+        for id in [0, 1] {
+            task_list.add_subtask(id, "Buy nails".to_string()).unwrap();
+            task_list.add_subtask(id, "Hammer".to_string()).unwrap();
+        }
+
+        task_list.complete_subtask(0, 0, true).unwrap();
+        assert!(!task_list.tasks[&0].is_complete());
+        task_list.complete_subtask(0, 1, true).unwrap();
+        assert!(task_list.tasks[&0].is_complete());
+
+        task_list.complete_subtask(1, 0, false).unwrap();
+        task_list.complete_subtask(1, 1, false).unwrap();
+        assert!(!task_list.tasks[&1].is_complete());
+    }
+
+    #[test]
+    fn pinned_tasks_are_rendered_before_unpinned_tasks() {
+        let mut task_list = task_list_with_tasks(&[
+            "Buy some milk",
+            "Learn Haskell",
+            "Call mum",
+            "Fix the fence",
+        ]);
+        task_list.set_priority(1, Priority::High).unwrap();
+        task_list.complete_task(3).unwrap();
+        task_list.pin(3).unwrap();
+        task_list.pin(2).unwrap();
+
+        assert_eq!(
+            task_list.to_string(),
+            "\
+[  2] !  • Call mum
+[  3] !  – Fix the fence
+[  1] !! • Learn Haskell
+[  0] !  • Buy some milk"
+        );
+
+        task_list.unpin(3).unwrap();
+
+        assert_eq!(
+            task_list.to_string(),
+            "\
+[  2] !  • Call mum
+[  1] !! • Learn Haskell
+[  0] !  • Buy some milk
+[  3] !  – Fix the fence"
+        );
+    }
+
+    #[test]
+    fn task_can_be_found_by_its_exact_title() {
+        let task_list = task_list_with_tasks(&["Buy some milk", "Learn Haskell", "Call mum"]);
+
+        assert_eq!(task_list.find_by_exact_title("Learn Haskell"), Some(1));
+        assert_eq!(task_list.find_by_exact_title("learn haskell"), None);
+        assert_eq!(task_list.find_by_exact_title("Learn"), None);
+    }
+
+    #[test]
+    fn cancelled_tasks_are_not_completed_by_title() {
+        let mut task_list = task_list_with_tasks(&["Buy some milk", "Buy some eggs"]);
+        task_list.cancel_task(0).unwrap();
+
+        assert_eq!(task_list.complete_by_title("buy"), Ok(1));
+        assert!(!task_list.tasks[&0].is_complete());
+    }
+
+    #[test]
+    fn task_can_be_completed_by_part_of_its_title() {
+        let mut task_list = task_list_with_tasks(&["Buy some milk", "Learn Haskell", "Call mum"]);
+
+        assert_eq!(task_list.complete_by_title("HASKELL"), Ok(1));
+        assert!(task_list.tasks[&1].is_complete());
+    }
+
+    #[test]
+    fn completing_by_title_without_matches_gives_error() {
+        let mut task_list = task_list_with_tasks(&["Buy some milk"]);
+
+        assert_eq!(
+            task_list.complete_by_title("eggs"),
+            Err(Error::NoMatchingTask("eggs".to_string()))
+        );
+    }
+
+    #[test]
+    fn completing_by_title_with_several_matches_lists_them() {
+        let mut task_list = task_list_with_tasks(&["Buy some milk", "Call mum", "Buy eggs"]);
+
+        let error = task_list.complete_by_title("buy").unwrap_err();
+
+        assert_eq!(
+            error,
+            Error::AmbiguousTaskTitle {
+                text: "buy".to_string(),
+                candidates: vec![
+                    (0, "Buy some milk".to_string()),
+                    (2, "Buy eggs".to_string())
+                ],
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "‘buy’ matches several tasks: [0] Buy some milk, [2] Buy eggs"
+        );
+        assert!(task_list.iter().all(|(_, task)| !task.is_complete()));
+    }
+
+    #[test]
+    fn only_tasks_completed_before_cutoff_are_removed() {
+        let mut task_list = TaskList::default();
+        task_list
+            .insert_task_with_id(
+                0,
+                Task::with_times(
+                    "Buy some milk",
+                    Status::Complete,
+                    None,
+                    Some("2024-04-30T23:00:00Z"),
+                ),
+            )
+            .unwrap();
+        task_list
+            .insert_task_with_id(
+                1,
+                Task::with_times(
+                    "Learn Haskell",
+                    Status::Complete,
+                    None,
+                    Some("2024-05-01T08:00:00Z"),
+                ),
+            )
+            .unwrap();
+        task_list
+            .insert_task_with_id(
+                2,
+                Task::with_times("Call mum", Status::Complete, None, None),
+            )
+            .unwrap();
+        task_list
+            .insert_task_with_id(3, Task::new("Fix the fence".to_string()))
+            .unwrap();
+
+        assert_eq!(task_list.remove_completed_before(Utc.ymd(2024, 5, 1)), 1);
+        assert_eq!(
+            task_list.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn tasks_completed_before_cutoff_go_by_the_date_in_its_time_zone() {
+        let mut task_list = TaskList::default();
+        // Already 1 May ten hours ahead of UTC, but still 30 April ten hours behind.
+        task_list
+            .insert_task_with_id(
+                0,
+                Task::with_times(
+                    "Buy some milk",
+                    Status::Complete,
+                    None,
+                    Some("2024-04-30T15:00:00Z"),
+                ),
+            )
+            .unwrap();
+
+        assert_eq!(
+            task_list.remove_completed_before(FixedOffset::east(10 * 60 * 60).ymd(2024, 5, 1)),
+            0
+        );
+        assert_eq!(
+            task_list.remove_completed_before(FixedOffset::west(10 * 60 * 60).ymd(2024, 5, 1)),
+            1
+        );
+    }
+
+    #[test]
+    fn tasks_can_be_counted_by_status() {
+        let mut task_list = task_list_with_tasks(&["Buy some milk", "Learn Haskell", "Call mum"]);
+        task_list.complete_task(1).unwrap();
+
+        assert_eq!(task_list.count(None), 3);
+        assert_eq!(task_list.count(Some(Status::Complete)), 1);
+        assert_eq!(task_list.count(Some(Status::Incomplete)), 2);
+        assert_eq!(TaskList::default().count(None), 0);
+    }
+
+    #[test]
+    fn tasks_can_be_looked_up_by_id() {
+        let task_list = task_list_with_tasks(&["Buy some milk", "Learn Haskell"]);
+
+        assert_eq!(task_list.get(1).map(Task::title), Some("Learn Haskell"));
+        assert!(std::ptr::eq(
+            task_list.get(1).unwrap(),
+            &task_list.tasks[&1]
+        ));
+        assert_eq!(task_list.get(2), None);
+    }
+
+    #[test]
+    fn tasks_can_be_moved_up() {
+        let mut task_list = task_list_with_tasks(&["Buy some milk", "Learn Haskell", "Call mum"]);
+        task_list.move_up(2).unwrap();
+
+        assert_eq!(
+            format!("{}", task_list),
+            "\
+[  0] ! • Buy some milk
+[  2] ! • Call mum
+[  1] ! • Learn Haskell"
+        );
+    }
+
+    #[test]
+    fn tasks_can_be_moved_down() {
+        let mut task_list = task_list_with_tasks(&["Buy some milk", "Learn Haskell", "Call mum"]);
+        task_list.move_down(0).unwrap();
+
+        assert_eq!(
+            format!("{}", task_list),
+            "\
+[  1] ! • Learn Haskell
+[  0] ! • Buy some milk
+[  2] ! • Call mum"
+        );
+    }
+
+    #[test]
+    fn tasks_can_be_swapped() {
+        let mut task_list = task_list_with_tasks(&["Buy some milk", "Learn Haskell", "Call mum"]);
+        task_list.swap(2, 0).unwrap();
+
+        assert_eq!(
+            format!("{}", task_list),
+            "\
+[  2] ! • Call mum
+[  1] ! • Learn Haskell
+[  0] ! • Buy some milk"
+        );
+    }
+
+    #[test]
+    fn swapping_task_with_itself_does_nothing() {
+        let mut task_list = task_list_with_tasks(&["Buy some milk", "Learn Haskell"]);
+        let original = task_list.clone();
+
+        task_list.swap(1, 1).unwrap();
+
+        assert_eq!(task_list, original);
+    }
+
+    #[test]
+    fn swapping_non_existent_task_gives_error() {
+        let mut task_list = task_list_with_tasks(&["Buy some milk"]);
+
+        assert_eq!(task_list.swap(0, 5), Err(Error::NonExistentTaskId(5)));
+        assert_eq!(task_list.swap(5, 0), Err(Error::NonExistentTaskId(5)));
+    }
+
+    #[test]
+    fn moving_first_task_up_or_last_task_down_does_nothing() {
+        let mut task_list = task_list_with_tasks(&["Buy some milk", "Learn Haskell"]);
+        let original = task_list.clone();
+
+        task_list.move_up(0).unwrap();
+        task_list.move_down(1).unwrap();
+
+        assert_eq!(task_list, original);
+    }
+
+    #[test]
+    fn moving_non_existent_task_up_or_down_gives_error() {
+        let mut task_list = TaskList::default();
+
+        assert_eq!(task_list.move_up(5), Err(Error::NonExistentTaskId(5)));
+        assert_eq!(task_list.move_down(5), Err(Error::NonExistentTaskId(5)));
+    }
+
+    #[test]
+    fn tasks_can_be_sorted_by_title() {
+        let mut task_list = task_list_with_tasks(&["learn Haskell", "Call mum", "Buy some milk"]);
+        task_list.sort_by_title();
+
+        let ids: Vec<_> = task_list.iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, [2, 1, 0]);
+    }
+
+    #[test]
+    fn tasks_can_be_sorted_by_status() {
+        let mut task_list = task_list_with_tasks(&[
+            "Buy some milk",
+            "Learn Haskell",
+            "Call mum",
+            "Fix the fence",
+        ]);
+        task_list.complete_task(0).unwrap();
+        task_list.complete_task(2).unwrap();
+        task_list.sort_by_status();
+
+        let ids: Vec<_> = task_list.iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, [1, 3, 0, 2]);
+    }
+
+    #[test]
+    fn tasks_can_be_renamed_by_providing_an_id_and_new_title() {
+        let mut task_list = TaskList::default();
+
+        task_list
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        task_list
+            .rename_task(0, "Purchase some milk".to_string())
+            .unwrap();
+
+        assert_eq!(task_list.tasks[&0].title(), "Purchase some milk");
+    }
+
+    #[test]
+    fn renaming_non_existent_task_gives_error() {
+        let mut task_list = TaskList::default();
+
+        assert_eq!(
+            task_list.rename_task(123, "Title for a task that does not exist".to_string()),
+            Err(Error::NonExistentTaskId(123))
+        );
+    }
+
+    #[test]
+    fn tasks_can_be_completed_by_id() {
+        let mut task_list = TaskList::default();
+
+        task_list
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        assert!(!task_list.tasks[&0].is_complete());
+
+        task_list.complete_task(0).unwrap();
+        assert!(task_list.tasks[&0].is_complete());
+    }
+
+    #[test]
+    fn completing_non_existent_task_gives_error() {
+        let mut task_list = TaskList::default();
+
+        assert_eq!(
+            task_list.complete_task(10),
+            Err(Error::NonExistentTaskId(10))
+        );
+    }
+
+    #[test]
+    fn multiple_tasks_can_be_completed_at_once() {
+        let mut task_list = TaskList::default();
+
+        for title in &["Buy some milk", "Learn Haskell", "Refactor code"] {
+            task_list.add_task(Task::new(title.to_string())).unwrap();
+        }
+
+        assert_eq!(task_list.complete_tasks(&[0, 2]), Ok(()));
+        assert!(task_list.tasks[&0].is_complete());
+        assert!(!task_list.tasks[&1].is_complete());
+        assert!(task_list.tasks[&2].is_complete());
+    }
+
+    #[test]
+    fn completing_multiple_non_existent_tasks_gives_their_ids() {
+        let mut task_list = TaskList::default();
+
+        assert_eq!(task_list.complete_tasks(&[4, 9]), Err(vec![4, 9]));
+    }
+
+    #[test]
+    fn completing_some_non_existent_tasks_still_completes_the_rest() {
+        let mut task_list = TaskList::default();
+
+        task_list
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        task_list
+            .add_task(Task::new("Learn Haskell".to_string()))
+            .unwrap();
+
+        assert_eq!(task_list.complete_tasks(&[5, 1, 8]), Err(vec![5, 8]));
+        assert!(!task_list.tasks[&0].is_complete());
+        assert!(task_list.tasks[&1].is_complete());
+    }
+
+    #[test]
+    fn completed_tasks_can_be_uncompleted_by_id() {
+        let mut task_list = TaskList::default();
+
+        task_list
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        task_list.complete_task(0).unwrap();
+        assert_eq!(format!("{}", task_list), "[  0] ! – Buy some milk");
+
+        task_list.uncomplete_task(0).unwrap();
+        assert_eq!(format!("{}", task_list), "[  0] ! • Buy some milk");
+    }
+
+    #[test]
+    fn uncompleting_incomplete_task_does_nothing() {
+        let mut task_list = TaskList::default();
+
+        task_list
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        task_list.uncomplete_task(0).unwrap();
+
+        assert!(!task_list.tasks[&0].is_complete());
+    }
+
+    #[test]
+    fn uncompleting_non_existent_task_gives_error() {
+        let mut task_list = TaskList::default();
+
+        assert_eq!(
+            task_list.uncomplete_task(7),
+            Err(Error::NonExistentTaskId(7))
+        );
+    }
+
+    #[test]
+    fn tasks_can_be_prioritized_by_id() {
+        let mut task_list = TaskList::default();
+
+        task_list
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        task_list.set_priority(0, Priority::High).unwrap();
+
+        assert_eq!(task_list.tasks[&0].priority(), Priority::High);
+    }
+
+    #[test]
+    fn prioritizing_non_existent_task_gives_error() {
+        let mut task_list = TaskList::default();
+
+        assert_eq!(
+            task_list.set_priority(3, Priority::Low),
+            Err(Error::NonExistentTaskId(3))
+        );
+    }
+
+    fn recurring_task_due_on(recurrence: Recurrence, due: NaiveDate) -> TaskList {
+        let mut task_list = task_list_with_tasks(&["Water the plants"]);
+        task_list.set_due(0, Some(due)).unwrap();
+        task_list.set_recurrence(0, Some(recurrence)).unwrap();
+
+        task_list
+    }
+
+    #[test]
+    fn completing_daily_task_reschedules_it_for_the_next_day() {
+        let mut task_list =
+            recurring_task_due_on(Recurrence::Daily, NaiveDate::from_ymd(2024, 5, 31));
+        task_list.complete_task(0).unwrap();
+
+        assert!(!task_list.tasks[&0].is_complete());
+        assert_eq!(
+            task_list.tasks[&0].due(),
+            Some(NaiveDate::from_ymd(2024, 6, 1))
+        );
+    }
+
+    #[test]
+    fn completing_weekly_task_reschedules_it_for_the_next_week() {
+        let mut task_list =
+            recurring_task_due_on(Recurrence::Weekly, NaiveDate::from_ymd(2024, 5, 1));
+        task_list.complete_task(0).unwrap();
+
+        assert!(!task_list.tasks[&0].is_complete());
+        assert_eq!(
+            task_list.tasks[&0].due(),
+            Some(NaiveDate::from_ymd(2024, 5, 8))
+        );
+    }
+
+    #[test]
+    fn completing_monthly_task_reschedules_it_for_the_next_month() {
+        let mut task_list =
+            recurring_task_due_on(Recurrence::Monthly, NaiveDate::from_ymd(2024, 1, 31));
+        task_list.complete_task(0).unwrap();
+
+        assert!(!task_list.tasks[&0].is_complete());
+        assert_eq!(
+            task_list.tasks[&0].due(),
+            Some(NaiveDate::from_ymd(2024, 2, 29))
+        );
+    }
+
+    #[test]
+    fn completing_recurring_task_without_due_date_schedules_it_from_today() {
+        let mut task_list = task_list_with_tasks(&["Water the plants"]);
+        task_list
+            .set_recurrence(0, Some(Recurrence::Daily))
+            .unwrap();
+        task_list.complete_task(0).unwrap();
+
+        assert_eq!(
+            task_list.tasks[&0].due(),
+            Some(Local::today().naive_local().succ())
+        );
+    }
+
+    #[test]
+    fn setting_recurrence_of_non_existent_task_gives_error() {
+        let mut task_list = TaskList::default();
+
+        assert_eq!(
+            task_list.set_recurrence(5, Some(Recurrence::Weekly)),
+            Err(Error::NonExistentTaskId(5))
+        );
+    }
+
+    #[test]
+    fn tasks_can_be_given_a_due_date_by_id() {
+        let mut task_list = TaskList::default();
+        let due = NaiveDate::from_ymd(2024, 5, 1);
+
+        task_list
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        task_list.set_due(0, Some(due)).unwrap();
+        assert_eq!(task_list.tasks[&0].due(), Some(due));
+
+        task_list.set_due(0, None).unwrap();
+        assert_eq!(task_list.tasks[&0].due(), None);
+    }
+
+    #[test]
+    fn setting_due_date_of_non_existent_task_gives_error() {
+        let mut task_list = TaskList::default();
+
+        assert_eq!(task_list.set_due(3, None), Err(Error::NonExistentTaskId(3)));
+    }
+
+    #[test]
+    fn tasks_can_be_given_notes_by_id() {
+        let mut task_list = TaskList::default();
+
+        task_list
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        task_list
+            .set_notes(0, Some("Get the organic one".to_string()))
+            .unwrap();
+        assert_eq!(task_list.notes(0), Ok(Some("Get the organic one")));
+
+        task_list.set_notes(0, None).unwrap();
+        assert_eq!(task_list.notes(0), Ok(None));
     }
 
-    pub fn add_task(&mut self, task: Task) {
-        let mut id_candidate = 0;
+    #[test]
+    fn blank_notes_are_removed() {
+        let mut task_list = TaskList::default();
 
-        loop {
-            match self.tasks.entry(id_candidate) {
-                Entry::Vacant(vacant_entry) => {
-                    vacant_entry.insert(task);
-                    return;
-                }
-                Entry::Occupied(_) => id_candidate += 1,
-            }
-        }
+        task_list
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        task_list.set_notes(0, Some(" \n\n".to_string())).unwrap();
+
+        assert_eq!(task_list.notes(0), Ok(None));
     }
 
-    pub fn remove_task(&mut self, id: u8) -> Result<(), Error> {
-        self.tasks
-            .remove(&id)
-            .ok_or_else(|| Error::NonExistentTaskId(id))
-            .map(|_| ())
+    #[test]
+    fn setting_notes_of_non_existent_task_gives_error() {
+        let mut task_list = TaskList::default();
+
+        assert_eq!(
+            task_list.set_notes(2, Some("Get the organic one".to_string())),
+            Err(Error::NonExistentTaskId(2))
+        );
+        assert_eq!(task_list.notes(2), Err(Error::NonExistentTaskId(2)));
     }
 
-    pub fn rename_task(&mut self, id: u8, new_title: String) -> Result<(), Error> {
-        self.tasks.get_mut(&id).map_or_else(
-            || Err(Error::NonExistentTaskId(id)),
-            |task| {
-                task.rename(new_title);
-                Ok(())
-            },
-        )
+    #[test]
+    fn notes_are_displayed_beneath_their_task() {
+        let mut task_list = TaskList::default();
+
+        task_list
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        task_list
+            .add_task(Task::new("Learn Haskell".to_string()))
+            .unwrap();
+        task_list
+            .set_notes(0, Some("Get the organic one.\n\nFull cream!\n".to_string()))
+            .unwrap();
+
+        assert_eq!(
+            format!("{}", task_list),
+            "\
+[  0] ! • Buy some milk
+      Get the organic one.
+
+      Full cream!
+[  1] ! • Learn Haskell"
+        );
     }
 
-    pub fn complete_task(&mut self, id: u8) -> Result<(), Error> {
-        self.tasks.get_mut(&id).map_or_else(
-            || Err(Error::NonExistentTaskId(id)),
-            |task| {
-                task.complete();
-                Ok(())
-            },
-        )
+    #[test]
+    fn tasks_can_be_tagged_by_id() {
+        let mut task_list = TaskList::default();
+
+        task_list
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        task_list.add_tag(0, "errands".to_string()).unwrap();
+
+        assert_eq!(task_list.tasks[&0].tags(), ["errands"]);
     }
 
-    pub fn remove_completed_tasks(&mut self) {
-        self.tasks.retain(|_, task| !task.is_complete());
+    #[test]
+    fn tagging_non_existent_task_gives_error() {
+        let mut task_list = TaskList::default();
+
+        assert_eq!(
+            task_list.add_tag(5, "errands".to_string()),
+            Err(Error::NonExistentTaskId(5))
+        );
     }
-}
 
-impl fmt::Display for TaskList {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let len = self.tasks.len();
+    #[test]
+    fn tagging_task_twice_only_adds_tag_once() {
+        let mut task_list = TaskList::default();
+
+        task_list
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        task_list.add_tag(0, "errands".to_string()).unwrap();
+        task_list.add_tag(0, "errands".to_string()).unwrap();
 
-        let is_at_last_task = |i| i + 1 == len;
+        assert_eq!(task_list.tasks[&0].tags(), ["errands"]);
+    }
 
-        for (i, (id, task)) in self.tasks.iter().enumerate() {
-            write!(f, "[{:>3}] {}", id, task)?;
+    #[test]
+    fn tags_can_be_removed_by_id() {
+        let mut task_list = TaskList::default();
 
-            if !is_at_last_task(i) {
-                f.write_str("\n")?;
-            }
-        }
+        task_list
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        task_list.add_tag(0, "errands".to_string()).unwrap();
+        task_list.add_tag(0, "shopping".to_string()).unwrap();
+        task_list.remove_tag(0, "errands").unwrap();
 
-        Ok(())
+        assert_eq!(task_list.tasks[&0].tags(), ["shopping"]);
+
+        // Removing a tag the task doesn’t have does nothing.
+        task_list.remove_tag(0, "errands").unwrap();
+        assert_eq!(task_list.tasks[&0].tags(), ["shopping"]);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn untagging_non_existent_task_gives_error() {
+        let mut task_list = TaskList::default();
+
+        assert_eq!(
+            task_list.remove_tag(5, "errands"),
+            Err(Error::NonExistentTaskId(5))
+        );
+    }
 
     #[test]
-    fn emptiness_can_be_checked() {
+    fn completed_tasks_can_be_removed() {
         let mut task_list = TaskList::default();
-        assert!(task_list.is_empty());
-        task_list.add_task(Task::new("Buy some milk".to_string()));
-        assert!(!task_list.is_empty());
+
+        task_list
+            .add_task(Task::new("Go to the dentist".to_string()))
+            .unwrap();
+        task_list
+            .add_task(Task::new("Write some tests".to_string()))
+            .unwrap();
+        task_list
+            .add_task(Task::new("Refactor code".to_string()))
+            .unwrap();
+        task_list.complete_task(1).unwrap();
+        task_list.complete_task(2).unwrap();
+
+        assert_eq!(task_list.remove_completed_tasks(), 2);
+
+        assert_eq!(
+            task_list
+                .iter()
+                .map(|(id, task)| (id, task.title()))
+                .collect::<Vec<_>>(),
+            [(0, "Go to the dentist")]
+        );
     }
 
     #[test]
-    fn tasks_can_be_added() {
-        let task_to_add = Task::new("Buy some milk".to_string());
+    fn cancelled_tasks_survive_removing_completed_tasks() {
+        let mut task_list = task_list_with_tasks(&["Buy some milk", "Learn Haskell", "Call mum"]);
+        task_list.complete_task(0).unwrap();
+        task_list.cancel_task(1).unwrap();
 
-        let mut task_list = TaskList::default();
-        task_list.add_task(task_to_add.clone());
+        assert_eq!(task_list.remove_completed_tasks(), 1);
+        assert_eq!(
+            task_list
+                .iter()
+                .map(|(id, task)| (id, task.title()))
+                .collect::<Vec<_>>(),
+            [(1, "Learn Haskell"), (2, "Call mum")]
+        );
 
+        assert_eq!(task_list.remove_cancelled_tasks(), 1);
         assert_eq!(
-            task_list,
-            TaskList {
-                tasks: {
-                    let mut tasks = IndexMap::new();
-                    tasks.insert(0, task_to_add);
-                    tasks
-                }
-            }
+            task_list
+                .iter()
+                .map(|(id, task)| (id, task.title()))
+                .collect::<Vec<_>>(),
+            [(2, "Call mum")]
         );
     }
 
     #[test]
-    fn ids_are_chosen_by_the_lowest_available_one() {
-        let task0 = Task::new("Buy some milk".to_string());
-        let task1 = Task::new("Learn Haskell".to_string());
-        let task2 = Task::new("Finish Chapter 10 of my novel".to_string());
+    fn reminders_can_be_added_and_removed() {
+        let mut task_list = task_list_with_tasks(&["Buy some milk"]);
+        let may_1 = NaiveDate::from_ymd(2024, 5, 1);
+        let may_3 = NaiveDate::from_ymd(2024, 5, 3);
 
-        let mut task_list = TaskList::default();
-        task_list.add_task(task0.clone());
-        task_list.add_task(task1.clone());
-        task_list.add_task(task2.clone());
+        task_list.add_reminder(0, may_3).unwrap();
+        task_list.add_reminder(0, may_1).unwrap();
+        task_list.add_reminder(0, may_3).unwrap();
+        assert_eq!(task_list.tasks[&0].reminders(), [may_1, may_3]);
 
-        assert_eq!(task_list.tasks[&0], task0);
-        assert_eq!(task_list.tasks[&1], task1);
-        assert_eq!(task_list.tasks[&2], task2);
+        task_list.remove_reminder(0, may_1).unwrap();
+        assert_eq!(task_list.tasks[&0].reminders(), [may_3]);
     }
 
     #[test]
-    fn tasks_can_be_removed_by_id() {
-        let mut task_list = TaskList::default();
+    fn adding_reminder_to_non_existent_task_gives_error() {
+        assert_eq!(
+            TaskList::default().add_reminder(0, NaiveDate::from_ymd(2024, 5, 1)),
+            Err(Error::NonExistentTaskId(0))
+        );
+    }
 
-        task_list.add_task(Task::new("Buy some milk".to_string())); // ID: 0
-        task_list.add_task(Task::new("Learn Haskell".to_string())); // ID: 1
-        task_list.remove_task(0).unwrap();
+    #[test]
+    fn cancelling_non_existent_task_gives_error() {
+        assert_eq!(
+            TaskList::default().cancel_task(0),
+            Err(Error::NonExistentTaskId(0))
+        );
+    }
 
-        // The task takes the lowest available ID, which is now 0.
-        task_list.add_task(Task::new("Finish Chapter 10 of my novel".to_string()));
-        task_list.remove_task(1).unwrap();
-        task_list.remove_task(0).unwrap();
+    #[test]
+    fn cleared_task_list_starts_ids_from_zero_again() {
+        let mut task_list = task_list_with_tasks(&["Buy some milk", "Learn Haskell", "Call mum"]);
+        assert_eq!(task_list.clear(), 3);
 
-        assert!(task_list.tasks.is_empty());
+        assert!(task_list.is_empty());
+        assert_eq!(
+            task_list.add_task(Task::new("Fix the fence".to_string())),
+            Ok(0)
+        );
     }
 
     #[test]
-    fn removing_non_existent_task_gives_error() {
+    fn completed_tasks_are_dimmed_when_using_color() {
         let mut task_list = TaskList::default();
+        task_list
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        task_list
+            .add_task(Task::new("Learn Haskell".to_string()))
+            .unwrap();
+        task_list.complete_task(1).unwrap();
+
+        let options = RenderOptions {
+            use_color: true,
+            dim_completed: true,
+            ..RenderOptions::default()
+        };
 
         assert_eq!(
-            task_list.remove_task(123),
-            Err(Error::NonExistentTaskId(123))
+            task_list.render(&options),
+            "\
+[  0] ! \x1b[36m•\x1b[0m Buy some milk
+\x1b[2m[  1] ! – Learn Haskell\x1b[0m"
         );
     }
 
     #[test]
-    fn tasks_can_be_renamed_by_providing_an_id_and_new_title() {
-        let mut task_list = TaskList::default();
+    fn tags_on_dimmed_completed_tasks_are_not_colored() {
+        let mut task_list = task_list_with_tasks(&["Learn Haskell"]);
+        task_list.add_tag(0, "study".to_string()).unwrap();
+        task_list.complete_task(0).unwrap();
+
+        let options = RenderOptions {
+            use_color: true,
+            dim_completed: true,
+            tag_colors: vec![("study".to_string(), render::Color::Blue)]
+                .into_iter()
+                .collect(),
+            ..RenderOptions::default()
+        };
+
+        assert_eq!(
+            task_list.render(&options),
+            "\x1b[2m[  0] ! – Learn Haskell #study\x1b[0m"
+        );
+    }
 
-        task_list.add_task(Task::new("Buy some milk".to_string()));
+    #[test]
+    fn completed_tasks_are_not_dimmed_without_color() {
+        let mut task_list = TaskList::default();
         task_list
-            .rename_task(0, "Purchase some milk".to_string())
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        task_list
+            .add_task(Task::new("Learn Haskell".to_string()))
             .unwrap();
+        task_list.complete_task(1).unwrap();
+
+        let options = RenderOptions {
+            use_color: false,
+            dim_completed: true,
+            ..RenderOptions::default()
+        };
 
         assert_eq!(
-            task_list.tasks[&0],
-            Task::new("Purchase some milk".to_string())
+            task_list.render(&options),
+            "\
+[  0] ! • Buy some milk
+[  1] ! – Learn Haskell"
         );
     }
 
     #[test]
-    fn renaming_non_existent_task_gives_error() {
+    fn tasks_can_be_grouped_by_tag() {
         let mut task_list = TaskList::default();
+        task_list
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        task_list
+            .add_task(Task::new("Call mum".to_string()))
+            .unwrap();
+        task_list
+            .add_task(Task::new("Fix the fence".to_string()))
+            .unwrap();
+        task_list
+            .add_task(Task::new("Post letter".to_string()))
+            .unwrap();
+        task_list.add_tag(0, "errands".to_string()).unwrap();
+        task_list.add_tag(2, "home".to_string()).unwrap();
+        task_list.add_tag(2, "weekend".to_string()).unwrap();
+        task_list.add_tag(3, "errands".to_string()).unwrap();
+        task_list.add_tag(3, "weekend".to_string()).unwrap();
+
+        let options = RenderOptions {
+            group_by: Some(GroupBy::Tag),
+            ..RenderOptions::default()
+        };
 
         assert_eq!(
-            task_list.rename_task(123, "Title for a task that does not exist".to_string()),
-            Err(Error::NonExistentTaskId(123))
+            task_list.render(&options),
+            "\
+errands
+  [  0] ! • Buy some milk #errands
+  [  3] ! • Post letter #errands #weekend
+home
+  [  2] ! • Fix the fence #home #weekend
+weekend
+  [  2] ! • Fix the fence #home #weekend
+  [  3] ! • Post letter #errands #weekend
+(untagged)
+  [  1] ! • Call mum"
         );
     }
 
     #[test]
-    fn tasks_can_be_completed_by_id() {
+    fn long_titles_are_wrapped_and_lined_up_with_title() {
+        let mut task_list = task_list_with_tasks(&[
+            "Buy some milk",
+            "Write the quarterly report for the board meeting",
+        ]);
+        task_list.set_priority(1, Priority::High).unwrap();
+
+        assert_eq!(
+            task_list.render_wrapped(30),
+            "\
+[  1] !! • Write the quarterly
+           report for the
+           board meeting
+[  0] !  • Buy some milk"
+        );
+    }
+
+    #[test]
+    fn wrapped_titles_line_up_with_checkbox_statuses() {
+        let task_list = task_list_with_tasks(&["Write the quarterly report for the board meeting"]);
+
+        let options = RenderOptions {
+            width: Some(32),
+            status_style: StatusStyle::Ascii,
+            ..RenderOptions::default()
+        };
+
+        assert_eq!(
+            task_list.render(&options),
+            "\
+[  0] ! [ ] Write the quarterly
+            report for the board
+            meeting"
+        );
+    }
+
+    #[test]
+    fn wrapped_titles_and_subtasks_line_up_with_wide_glyphs() {
+        let mut task_list =
+            task_list_with_tasks(&["Write the quarterly report for the board meeting"]);
+        task_list
+            .add_subtask(0, "Gather figures".to_string())
+            .unwrap();
+
+        let options = RenderOptions {
+            width: Some(32),
+            glyphs: render::Glyphs {
+                incomplete: Some("⬜".to_string()),
+                ..render::Glyphs::default()
+            },
+            ..RenderOptions::default()
+        };
+
+        assert_eq!(
+            task_list.render(&options),
+            "\
+[  0] ! ⬜ Write the quarterly
+           report for the board
+           meeting [0/1]
+           ⬜ Gather figures"
+        );
+    }
+
+    #[test]
+    fn wrapping_leaves_short_titles_alone() {
+        let task_list = task_list_with_tasks(&["Buy some milk", "Learn Haskell"]);
+
+        assert_eq!(task_list.render_wrapped(80), task_list.to_string());
+    }
+
+    #[test]
+    fn task_list_can_be_rendered_as_ascii() {
         let mut task_list = TaskList::default();
+        task_list
+            .add_task(Task::new("Bake crème brûlée 🍮".to_string()))
+            .unwrap();
+        task_list
+            .add_task(Task::new("Learn Haskell".to_string()))
+            .unwrap();
+        task_list.complete_task(1).unwrap();
 
-        task_list.add_task(Task::new("Buy some milk".to_string()));
-        assert!(!task_list.tasks[&0].is_complete());
+        let options = RenderOptions {
+            ascii: true,
+            ..RenderOptions::default()
+        };
 
-        task_list.complete_task(0).unwrap();
-        assert!(task_list.tasks[&0].is_complete());
+        assert_eq!(
+            task_list.render(&options),
+            "\
+[  0] ! * Bake creme brulee custard
+[  1] ! - Learn Haskell"
+        );
     }
 
     #[test]
-    fn completing_non_existent_task_gives_error() {
+    fn tasks_are_displayed_from_highest_to_lowest_priority() {
         let mut task_list = TaskList::default();
+        task_list
+            .add_task(Task::new("Sort socks".to_string()))
+            .unwrap();
+        task_list
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        task_list
+            .add_task(Task::new("File taxes".to_string()))
+            .unwrap();
+        task_list
+            .add_task(Task::new("Learn Haskell".to_string()))
+            .unwrap();
+        task_list
+            .add_task(Task::new("Renew passport".to_string()))
+            .unwrap();
+        task_list.set_priority(0, Priority::Low).unwrap();
+        task_list.set_priority(2, Priority::High).unwrap();
+        task_list.set_priority(4, Priority::High).unwrap();
 
         assert_eq!(
-            task_list.complete_task(10),
-            Err(Error::NonExistentTaskId(10))
+            format!("{}", task_list),
+            "\
+[  2] !! • File taxes
+[  4] !! • Renew passport
+[  1] !  • Buy some milk
+[  3] !  • Learn Haskell
+[  0]    • Sort socks"
         );
     }
 
     #[test]
-    fn completed_tasks_can_be_removed() {
+    fn titles_line_up_whatever_the_status_and_priority() {
+        let mut task_list = task_list_with_tasks(&["Buy some milk", "File taxes", "Sort socks"]);
+        task_list.complete_task(0).unwrap();
+        task_list.set_priority(1, Priority::High).unwrap();
+        task_list.set_priority(2, Priority::Low).unwrap();
+
+        let rendered = task_list.to_string();
+        let title_columns: Vec<_> = rendered
+            .lines()
+            .map(|line| line.chars().position(|c| c.is_ascii_uppercase()).unwrap())
+            .collect();
+
+        assert_eq!(title_columns, [11, 11, 11]);
+    }
+
+    #[test]
+    fn ids_are_padded_to_the_width_of_the_largest_id() {
         let mut task_list = TaskList::default();
 
-        task_list.add_task(Task::new("Go to the dentist".to_string()));
-        task_list.add_task(Task::new("Write some tests".to_string()));
-        task_list.add_task(Task::new("Refactor code".to_string()));
-        task_list.complete_task(1).unwrap();
-        task_list.complete_task(2).unwrap();
+        for i in 0..1001 {
+            task_list
+                .add_task(Task::new(format!("Task {}", i)))
+                .unwrap();
+        }
 
-        task_list.remove_completed_tasks();
+        for id in 2..1000 {
+            task_list.remove_task(id).unwrap();
+        }
 
         assert_eq!(
-            task_list.tasks.into_iter().collect::<Vec<_>>(),
-            vec![(0, Task::new("Go to the dentist".to_string()))]
+            format!("{}", task_list),
+            "\
+[   0] ! • Task 0
+[   1] ! • Task 1
+[1000] ! • Task 1000"
         );
     }
 
     #[test]
     fn task_list_implements_display() {
         let mut task_list = TaskList::default();
-        task_list.add_task(Task::new("Buy some milk".to_string()));
-        task_list.add_task(Task::new("Learn Haskell".to_string()));
+        task_list
+            .add_task(Task::new("Buy some milk".to_string()))
+            .unwrap();
+        task_list
+            .add_task(Task::new("Learn Haskell".to_string()))
+            .unwrap();
 
         assert_eq!(
             format!("{}", task_list),
             "\
-[  0] • Buy some milk
-[  1] • Learn Haskell"
+[  0] ! • Buy some milk
+[  1] ! • Learn Haskell"
         );
     }
 }